@@ -15,6 +15,10 @@ complete -c hx -l vsplit -d "Splits all given files vertically"
 complete -c hx -l hsplit -d "Splits all given files horizontally"
 complete -c hx -s c -l config -r -d "Specifies a file to use for config"
 complete -c hx -l log -r -d "Specifies a file to use for logging"
+complete -c hx -l remote -d "Opens the given files in a running instance"
+complete -c hx -l remote-wait -d "Opens the given files in a running instance and waits until they are closed"
+complete -c hx -l remote-command -x -d "Runs a command in a running instance"
+complete -c hx -l remote-current-path -d "Prints the path of the current buffer of a running instance"
 complete -c hx -s w -l working-dir -d "Specify initial working directory" -xa "(__fish_complete_directories)"
 
 function __hx_langs_ops
@@ -64,6 +64,11 @@ pub enum DiagnosticProvider {
         /// not clear the pull diagnostics and vice-versa.
         identifier: Option<Arc<str>>,
     },
+    /// An external linter configured for the language.
+    Linter {
+        /// The name of the linter.
+        name: Arc<str>,
+    },
     // Future internal features can go here...
 }
 
@@ -71,7 +76,7 @@ impl DiagnosticProvider {
     pub fn language_server_id(&self) -> Option<LanguageServerId> {
         match self {
             Self::Lsp { server_id, .. } => Some(*server_id),
-            // _ => None,
+            _ => None,
         }
     }
 }
@@ -1,7 +1,10 @@
 use std::ops::Range;
 use std::time::Instant;
 
-use imara_diff::{Algorithm, Diff, Hunk, IndentHeuristic, IndentLevel, InternedInput};
+use imara_diff::{
+    Algorithm, BasicLineDiffPrinter, Diff, Hunk, IndentHeuristic, IndentLevel, InternedInput,
+    UnifiedDiffConfig,
+};
 use ropey::RopeSlice;
 
 use crate::{ChangeSet, Rope, Tendril, Transaction};
@@ -168,6 +171,22 @@ pub fn compare_ropes(before: &Rope, after: &Rope) -> Transaction {
     res
 }
 
+/// Formats the line changes between `before` and `after` as a unified diff
+/// without file headers.
+pub fn unified_diff(before: &Rope, after: &Rope) -> String {
+    let before = before.to_string();
+    let after = after.to_string();
+    let input = InternedInput::new(before.as_str(), after.as_str());
+    let mut diff = Diff::compute(Algorithm::Histogram, &input);
+    diff.postprocess_lines(&input);
+    diff.unified_diff(
+        &BasicLineDiffPrinter(&input.interner),
+        UnifiedDiffConfig::default(),
+        &input,
+    )
+    .to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -208,4 +227,15 @@ fn new_file() {
     fn deleted_file() {
         test_identity("foo", "");
     }
+
+    #[test]
+    fn unified() {
+        let before = Rope::from("a\nb\nc\n");
+        let after = Rope::from("a\nB\nc\nd\n");
+        assert_eq!(
+            unified_diff(&before, &after),
+            "@@ -1,3 +1,4 @@\n a\n-b\n+B\n c\n+d\n"
+        );
+        assert_eq!(unified_diff(&before, &before), "");
+    }
 }
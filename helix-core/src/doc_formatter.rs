@@ -37,6 +37,13 @@ pub enum GraphemeSource {
     VirtualText {
         highlight: Option<Highlight>,
     },
+    /// A folded region of the document that is shown as a single placeholder
+    /// grapheme. Like virtual text the placeholder is highlighted by the
+    /// document formatter but it still covers the folded document chars.
+    Fold {
+        codepoints: u32,
+        highlight: Option<Highlight>,
+    },
 }
 
 impl GraphemeSource {
@@ -52,7 +59,9 @@ pub fn is_eof(self) -> bool {
 
     pub fn doc_chars(self) -> usize {
         match self {
-            GraphemeSource::Document { codepoints } => codepoints as usize,
+            GraphemeSource::Document { codepoints } | GraphemeSource::Fold { codepoints, .. } => {
+                codepoints as usize
+            }
             GraphemeSource::VirtualText { .. } => 0,
         }
     }
@@ -141,6 +150,9 @@ fn is_word_boundary(&self) -> bool {
     }
 }
 
+/// The grapheme shown in place of folded text.
+pub const FOLD_PLACEHOLDER: &str = "⋯";
+
 #[derive(Debug, Clone)]
 pub struct TextFormat {
     pub soft_wrap: bool,
@@ -173,6 +185,7 @@ fn default() -> Self {
 pub struct DocumentFormatter<'t> {
     text_fmt: &'t TextFormat,
     annotations: &'t TextAnnotations<'t>,
+    text: RopeSlice<'t>,
 
     /// The visual position at the end of the last yielded word boundary
     visual_pos: Position,
@@ -181,6 +194,9 @@ pub struct DocumentFormatter<'t> {
     char_pos: usize,
     /// The line pos of the `graphemes` iter used for inserting annotations
     line_pos: usize,
+    /// The number of virtual lines reserved above the first line of the document
+    /// if the traversal starts at the start of the document
+    virtual_lines_above_text: usize,
     exhausted: bool,
 
     inline_annotation_graphemes: Option<(Graphemes<'t>, Option<Highlight>)>,
@@ -212,14 +228,28 @@ pub fn new_at_prev_checkpoint(
         char_idx: usize,
     ) -> Self {
         // TODO divide long lines into blocks to avoid bad performance for long lines
-        let block_line_idx = text.char_to_line(char_idx.min(text.len_chars()));
-        let block_char_idx = text.line_to_char(block_line_idx);
+        let mut block_line_idx = text.char_to_line(char_idx.min(text.len_chars()));
+        let mut block_char_idx = text.line_to_char(block_line_idx);
+        // a block can not start inside of a fold, use the line the fold starts on instead
+        if let Some(fold) = annotations.fold_hiding_line(block_char_idx) {
+            block_line_idx = text.char_to_line(fold.start);
+            block_char_idx = text.line_to_char(block_line_idx);
+        }
         annotations.reset_pos(block_char_idx);
+        let virtual_lines_above_text = if block_char_idx == 0 {
+            annotations.virtual_lines_above_text()
+        } else {
+            0
+        };
 
         DocumentFormatter {
             text_fmt,
             annotations,
-            visual_pos: Position { row: 0, col: 0 },
+            text,
+            visual_pos: Position {
+                row: virtual_lines_above_text,
+                col: 0,
+            },
             graphemes: text.slice(block_char_idx..).graphemes(),
             char_pos: block_char_idx,
             exhausted: false,
@@ -228,6 +258,7 @@ pub fn new_at_prev_checkpoint(
             word_buf: Vec::with_capacity(64),
             word_i: 0,
             line_pos: block_line_idx,
+            virtual_lines_above_text,
             inline_annotation_graphemes: None,
         }
     }
@@ -262,6 +293,22 @@ fn advance_grapheme(&mut self, col: usize, char_pos: usize) -> Option<GraphemeWi
         let (grapheme, source) =
             if let Some((grapheme, highlight)) = self.next_inline_annotation_grapheme(char_pos) {
                 (grapheme.into(), GraphemeSource::VirtualText { highlight })
+            } else if let Some((fold, highlight)) = self
+                .annotations
+                .fold_at(char_pos)
+                // folds are ignored if the text is truncated before the end of the fold
+                .filter(|(fold, _)| fold.end <= self.text.len_chars())
+            {
+                self.graphemes = self.text.slice(fold.end..).graphemes();
+                self.annotations.skip_concealed(fold.end);
+                let codepoints = (fold.end - char_pos) as u32;
+                (
+                    FOLD_PLACEHOLDER.into(),
+                    GraphemeSource::Fold {
+                        codepoints,
+                        highlight,
+                    },
+                )
             } else if let Some(grapheme) = self.graphemes.next() {
                 let codepoints = grapheme.len_chars() as u32;
 
@@ -427,6 +474,23 @@ pub fn next_char_pos(&self) -> usize {
     pub fn next_visual_pos(&self) -> Position {
         self.visual_pos
     }
+
+    /// Returns the number of virtual lines reserved above the first line of the
+    /// document, which precede the first yielded grapheme. This is always zero
+    /// if the traversal does not start at the start of the document.
+    pub fn virtual_lines_above_text(&self) -> usize {
+        self.virtual_lines_above_text
+    }
+
+    /// Returns the row where the visual line at `row` starts. The virtual lines
+    /// above the first line of the document are part of its first visual line.
+    pub fn visual_line_start(&self, row: usize) -> usize {
+        if row == self.virtual_lines_above_text {
+            0
+        } else {
+            row
+        }
+    }
 }
 
 impl<'t> Iterator for DocumentFormatter<'t> {
@@ -472,6 +536,10 @@ fn next(&mut self) -> Option<Self::Item> {
                 self.line_pos += 1;
             }
         } else {
+            if let GraphemeSource::Fold { .. } = grapheme.source {
+                // the fold may conceal any number of lines
+                self.line_pos = self.text.char_to_line(self.char_pos);
+            }
             self.visual_pos.col += grapheme.width();
         }
         Some(grapheme)
@@ -1,5 +1,5 @@
 use crate::doc_formatter::{DocumentFormatter, TextFormat};
-use crate::text_annotations::{InlineAnnotation, Overlay, TextAnnotations};
+use crate::text_annotations::{Fold, InlineAnnotation, Overlay, TextAnnotations};
 
 impl TextFormat {
     fn new_test(softwrap: bool) -> Self {
@@ -211,3 +211,66 @@ fn annotation_and_overlay() {
         "fooo  bar "
     );
 }
+
+fn fold_text(text: &str, char_pos: usize, softwrap: bool, folds: &[Fold]) -> String {
+    DocumentFormatter::new_at_prev_checkpoint(
+        text.into(),
+        &TextFormat::new_test(softwrap),
+        TextAnnotations::default().add_folds(folds, None),
+        char_pos,
+    )
+    .collect_to_str()
+}
+
+#[test]
+fn fold() {
+    let text = "fn a {\n  b\n  c\n}\nd\n";
+    assert_eq!(
+        fold_text(text, 0, false, &[Fold::new(6, 16)]),
+        "fn a {⋯ \nd \n "
+    );
+    assert_eq!(
+        fold_text(text, 0, true, &[Fold::new(6, 16)]),
+        "fn a {⋯ \nd \n "
+    );
+    // starting inside of a fold renders from the start of the folded line
+    assert_eq!(
+        fold_text(text, 12, false, &[Fold::new(6, 16)]),
+        "fn a {⋯ \nd \n "
+    );
+}
+
+#[test]
+fn fold_line_idx() {
+    let text = "a\nb\nc\nd\n";
+    let folds = [Fold::new(1, 5)];
+    let mut annotations = TextAnnotations::default();
+    annotations.add_folds(&folds, None);
+    let lines: Vec<_> = DocumentFormatter::new_at_prev_checkpoint(
+        text.into(),
+        &TextFormat::new_test(false),
+        &annotations,
+        0,
+    )
+    .map(|grapheme| (grapheme.line_idx, grapheme.visual_pos.row))
+    .collect();
+    assert_eq!(lines, [(0, 0), (0, 0), (2, 0), (3, 1), (3, 1), (4, 2)]);
+}
+
+#[test]
+fn fold_and_annotations() {
+    let annotations = [InlineAnnotation::new(3, "x"), InlineAnnotation::new(5, "y")];
+    let folds = [Fold::new(1, 4)];
+    assert_eq!(
+        DocumentFormatter::new_at_prev_checkpoint(
+            "a\nbc\nd".into(),
+            &TextFormat::new_test(false),
+            TextAnnotations::default()
+                .add_inline_annotations(annotations.as_slice(), None)
+                .add_folds(folds.as_slice(), None),
+            0,
+        )
+        .collect_to_str(),
+        "a⋯ \nyd "
+    );
+}
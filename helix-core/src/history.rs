@@ -1,8 +1,11 @@
-use crate::{Assoc, ChangeSet, Range, Rope, Selection, Transaction};
+use crate::{transaction::Operation, Assoc, ChangeSet, Range, Rope, Selection, Transaction};
+use anyhow::bail;
 use once_cell::sync::Lazy;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
 use std::num::NonZeroUsize;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 #[derive(Debug, Clone)]
 pub struct State {
@@ -47,7 +50,7 @@ pub struct State {
 ///    delete, we also store an inversion of the transaction.
 ///
 /// Using time to navigate the history: <https://github.com/helix-editor/helix/pull/194>
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct History {
     revisions: Vec<Revision>,
     current: usize,
@@ -302,6 +305,260 @@ pub fn later(&mut self, uk: UndoKind) -> Vec<Transaction> {
     }
 }
 
+/// On-disk representation of a [History], see [History::serialize].
+#[derive(Serialize, Deserialize)]
+struct SerializedHistory {
+    /// Hash of the document text at the `current` revision.
+    text_hash: String,
+    current: usize,
+    revisions: Vec<SerializedRevision>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedRevision {
+    parent: usize,
+    last_child: Option<usize>,
+    transaction: SerializedTransaction,
+    inversion: SerializedTransaction,
+    /// Milliseconds since the Unix epoch.
+    timestamp: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedTransaction {
+    changes: Vec<SerializedOperation>,
+    selection: Option<SerializedSelection>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum SerializedOperation {
+    Retain(usize),
+    Delete(usize),
+    Insert(String),
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedSelection {
+    ranges: Vec<(usize, usize)>,
+    primary_index: usize,
+}
+
+impl From<&Transaction> for SerializedTransaction {
+    fn from(transaction: &Transaction) -> Self {
+        let changeset = transaction.changes();
+        let mut changes: Vec<_> = changeset
+            .changes()
+            .iter()
+            .map(|op| match op {
+                Operation::Retain(n) => SerializedOperation::Retain(*n),
+                Operation::Delete(n) => SerializedOperation::Delete(*n),
+                Operation::Insert(text) => SerializedOperation::Insert(text.to_string()),
+            })
+            .collect();
+        // A change set without operations retains the whole document.
+        let len = changeset.len();
+        if changes.is_empty() && len > 0 {
+            changes.push(SerializedOperation::Retain(len));
+        }
+        let selection = transaction
+            .selection()
+            .map(|selection| SerializedSelection {
+                ranges: selection
+                    .ranges()
+                    .iter()
+                    .map(|range| (range.anchor, range.head))
+                    .collect(),
+                primary_index: selection.primary_index(),
+            });
+        Self { changes, selection }
+    }
+}
+
+impl TryFrom<SerializedTransaction> for Transaction {
+    type Error = anyhow::Error;
+
+    fn try_from(transaction: SerializedTransaction) -> anyhow::Result<Self> {
+        let mut changes = ChangeSet::with_capacity(transaction.changes.len());
+        for op in transaction.changes {
+            match op {
+                SerializedOperation::Retain(n) => changes.retain(n),
+                SerializedOperation::Delete(n) => changes.delete(n),
+                SerializedOperation::Insert(text) => changes.insert(text.into()),
+            }
+        }
+        let mut transaction_out = Transaction::from(changes);
+        if let Some(selection) = transaction.selection {
+            if selection.primary_index >= selection.ranges.len() {
+                bail!("invalid selection in undo history");
+            }
+            let ranges = selection
+                .ranges
+                .into_iter()
+                .map(|(anchor, head)| Range::new(anchor, head))
+                .collect();
+            transaction_out =
+                transaction_out.with_selection(Selection::new(ranges, selection.primary_index));
+        }
+        Ok(transaction_out)
+    }
+}
+
+/// Hashes the document text. Used to check that a serialized history matches the
+/// contents of the file it is restored for.
+fn text_hash(text: &Rope) -> String {
+    use sha1::{Digest, Sha1};
+
+    let mut hasher = Sha1::new();
+    for chunk in text.chunks() {
+        hasher.update(chunk.as_bytes());
+    }
+    let hash = hasher.finalize();
+    hash.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Checks that the changes of the revisions apply to the lengths of the document at their
+/// parent revisions, so that undoing and redoing them can't panic. `len` is the length of
+/// the document at the `current` revision.
+fn validate_lengths(revisions: &[Revision], current: usize, len: usize) -> anyhow::Result<()> {
+    // The lengths at the revisions from `current` up to the root are known from the
+    // inversions and the lengths of the other revisions from the transactions.
+    let mut lengths = vec![None; revisions.len()];
+    lengths[current] = Some(len);
+    let mut revision = current;
+    while revision != 0 {
+        let inversion = revisions[revision].inversion.changes();
+        let parent = revisions[revision].parent;
+        if Some(inversion.len()) != lengths[revision] {
+            bail!("undo history does not match the document length");
+        }
+        lengths[parent] = Some(inversion.len_after());
+        revision = parent;
+    }
+    for (i, revision) in revisions.iter().enumerate().skip(1) {
+        let transaction = revision.transaction.changes();
+        let inversion = revision.inversion.changes();
+        let parent_len = lengths[revision.parent];
+        if Some(transaction.len()) != parent_len
+            || lengths[i].is_some_and(|len| len != transaction.len_after())
+            || inversion.len() != transaction.len_after()
+            || Some(inversion.len_after()) != parent_len
+        {
+            bail!("invalid changes in revision {i} of the undo history");
+        }
+        lengths[i] = Some(transaction.len_after());
+        let selections_in_bounds = |selection: Option<&Selection>, len: usize| {
+            selection.is_none_or(|selection| selection.iter().all(|range| range.to() <= len))
+        };
+        if !selections_in_bounds(revision.transaction.selection(), transaction.len_after())
+            || !selections_in_bounds(revision.inversion.selection(), inversion.len_after())
+        {
+            bail!("invalid selection in revision {i} of the undo history");
+        }
+    }
+    Ok(())
+}
+
+impl History {
+    /// Writes the revision tree to `writer` with `revision` as the current revision.
+    /// `text` is the document's contents at that revision: the history may only be
+    /// restored for a document with the same contents.
+    pub fn serialize<W: Write>(
+        &self,
+        writer: W,
+        revision: usize,
+        text: &Rope,
+    ) -> anyhow::Result<()> {
+        if revision >= self.revisions.len() {
+            bail!("revision {revision} does not exist");
+        }
+        let now = Instant::now();
+        let system_now = SystemTime::now();
+        let revisions = self
+            .revisions
+            .iter()
+            .map(|revision| {
+                let timestamp = system_now
+                    .checked_sub(now.saturating_duration_since(revision.timestamp))
+                    .unwrap_or(UNIX_EPOCH)
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default();
+                SerializedRevision {
+                    parent: revision.parent,
+                    last_child: revision.last_child.map(NonZeroUsize::get),
+                    transaction: (&revision.transaction).into(),
+                    inversion: (&revision.inversion).into(),
+                    timestamp: timestamp.as_millis() as u64,
+                }
+            })
+            .collect();
+        let history = SerializedHistory {
+            text_hash: text_hash(text),
+            current: revision,
+            revisions,
+        };
+        serde_json::to_writer(writer, &history)?;
+        Ok(())
+    }
+
+    /// Reads a revision tree written by [History::serialize]. Fails if the history
+    /// was written for a document with contents other than `text`.
+    pub fn deserialize<R: Read>(reader: R, text: &Rope) -> anyhow::Result<Self> {
+        let history: SerializedHistory = serde_json::from_reader(reader)?;
+        if history.text_hash != text_hash(text) {
+            bail!("undo history does not match the document contents");
+        }
+        let len = history.revisions.len();
+        if history.current >= len {
+            bail!("invalid current revision in undo history");
+        }
+
+        let now = Instant::now();
+        let system_now = SystemTime::now();
+        let mut revisions = Vec::with_capacity(len);
+        // Revisions are stored in the order they were committed so timestamps only
+        // increase. Timestamps too old to be represented as an `Instant` are clamped
+        // to the next revision's to keep them sorted.
+        let mut next_timestamp = now;
+        for (i, revision) in history.revisions.into_iter().enumerate().rev() {
+            let valid_parent = if i == 0 {
+                revision.parent == 0
+            } else {
+                revision.parent < i
+            };
+            let valid_last_child = revision
+                .last_child
+                .is_none_or(|child| child > i && child < len);
+            if !valid_parent || !valid_last_child {
+                bail!("invalid revision {i} in undo history");
+            }
+
+            let age = system_now
+                .duration_since(UNIX_EPOCH + Duration::from_millis(revision.timestamp))
+                .unwrap_or_default();
+            let timestamp = now
+                .checked_sub(age)
+                .unwrap_or(next_timestamp)
+                .min(next_timestamp);
+            next_timestamp = timestamp;
+
+            revisions.push(Revision {
+                parent: revision.parent,
+                last_child: revision.last_child.and_then(NonZeroUsize::new),
+                transaction: revision.transaction.try_into()?,
+                inversion: revision.inversion.try_into()?,
+                timestamp,
+            });
+        }
+        revisions.reverse();
+        validate_lengths(&revisions, history.current, text.len_chars())?;
+
+        Ok(Self {
+            revisions,
+            current: history.current,
+        })
+    }
+}
+
 /// Whether to undo by a number of edits or a duration of time.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum UndoKind {
@@ -441,6 +698,82 @@ fn redo(history: &mut History, state: &mut State) {
         assert_eq!("hello", state.doc);
     }
 
+    #[test]
+    fn test_serialize() {
+        let mut history = History::default();
+        let mut state = State {
+            doc: Rope::from("hello"),
+            selection: Selection::point(0),
+        };
+
+        let transaction1 =
+            Transaction::change(&state.doc, vec![(5, 5, Some(" world!".into()))].into_iter());
+        history.commit_revision(&transaction1, &state);
+        transaction1.apply(&mut state.doc);
+
+        let transaction2 =
+            Transaction::change(&state.doc, vec![(6, 11, Some("世界".into()))].into_iter());
+        history.commit_revision(&transaction2, &state);
+        let saved = state.doc.clone();
+        transaction2.apply(&mut state.doc);
+
+        // Serialize the history as of the first revision.
+        let mut buf = Vec::new();
+        history.serialize(&mut buf, 1, &saved).unwrap();
+
+        assert!(History::deserialize(buf.as_slice(), &state.doc).is_err());
+        let mut restored = History::deserialize(buf.as_slice(), &saved).unwrap();
+        assert_eq!(restored.current_revision(), 1);
+        assert_eq!(restored.revisions.len(), history.revisions.len());
+        assert!(restored
+            .revisions
+            .windows(2)
+            .all(|w| w[0].timestamp <= w[1].timestamp));
+
+        let mut doc = saved;
+        let redo = restored.redo().unwrap();
+        redo.apply(&mut doc);
+        assert_eq!("hello 世界!", doc);
+        let undo = restored.undo().unwrap();
+        assert_eq!(undo.selection(), Some(&Selection::point(0)));
+        undo.apply(&mut doc);
+        assert_eq!("hello world!", doc);
+        restored.undo().unwrap().apply(&mut doc);
+        assert_eq!("hello", doc);
+    }
+
+    #[test]
+    fn test_deserialize_invalid_changes() {
+        let mut history = History::default();
+        let mut state = State {
+            doc: Rope::from("hello"),
+            selection: Selection::point(0),
+        };
+        let transaction =
+            Transaction::change(&state.doc, vec![(5, 5, Some(" world".into()))].into_iter());
+        history.commit_revision(&transaction, &state);
+        transaction.apply(&mut state.doc);
+
+        let mut buf = Vec::new();
+        history.serialize(&mut buf, 1, &state.doc).unwrap();
+        let mut serialized: SerializedHistory = serde_json::from_slice(&buf).unwrap();
+        History::deserialize(buf.as_slice(), &state.doc).unwrap();
+
+        // The inversion doesn't apply to the document anymore.
+        serialized.revisions[1].inversion.changes = vec![SerializedOperation::Retain(3)];
+        let buf = serde_json::to_vec(&serialized).unwrap();
+        assert!(History::deserialize(buf.as_slice(), &state.doc).is_err());
+    }
+
+    #[test]
+    fn test_text_hash() {
+        // The hash is persisted so it must not change between versions.
+        assert_eq!(
+            text_hash(&Rope::from("hello")),
+            "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d"
+        );
+    }
+
     #[test]
     fn test_earlier_later() {
         let mut history = History::default();
@@ -17,8 +17,10 @@
 pub mod increment;
 pub mod indent;
 pub mod line_ending;
+pub mod linter;
 pub mod macros;
 pub mod match_brackets;
+pub mod merge_conflict;
 pub mod movement;
 pub mod object;
 mod position;
@@ -57,7 +59,8 @@ pub mod unicode {
 
 pub use position::{
     char_idx_at_visual_offset, coords_at_pos, pos_at_coords, softwrapped_dimensions,
-    visual_offset_from_anchor, visual_offset_from_block, Position, VisualOffsetError,
+    view_offset_at_visual_offset, visual_offset_from_anchor, visual_offset_from_block, Position,
+    VisualOffsetError,
 };
 #[allow(deprecated)]
 pub use position::{pos_at_visual_coords, visual_coords_at_pos};
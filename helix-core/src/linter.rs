@@ -0,0 +1,209 @@
+//! Parsing the output of external linters configured with [LinterConfiguration].
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+use crate::{
+    diagnostic::Severity,
+    syntax::config::{LinterConfiguration, LinterFormat},
+};
+
+/// A diagnostic printed by a linter. Lines and columns start at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinterDiagnostic {
+    pub line: usize,
+    pub column: Option<usize>,
+    pub end_line: Option<usize>,
+    /// The column after the last character of the diagnostic.
+    pub end_column: Option<usize>,
+    pub severity: Option<Severity>,
+    pub message: String,
+    pub code: Option<String>,
+}
+
+/// Parses the `output` of `linter`. Entries without a line or message are skipped.
+pub fn parse_output(linter: &LinterConfiguration, output: &str) -> Result<Vec<LinterDiagnostic>> {
+    let diagnostics = match &linter.format {
+        LinterFormat::Regex { pattern } => output
+            .lines()
+            .filter_map(|line| {
+                let captures = pattern.captures(line)?;
+                let group = |name| captures.name(name).map(|group| group.as_str());
+                let number = |name| group(name)?.trim().parse().ok();
+                Some(LinterDiagnostic {
+                    line: number("line")?,
+                    column: number("column"),
+                    end_line: number("end_line"),
+                    end_column: number("end_column"),
+                    severity: group("severity")
+                        .and_then(|severity| parse_severity(linter, severity)),
+                    message: group("message")?.trim().to_owned(),
+                    code: group("code").map(str::to_owned),
+                })
+            })
+            .collect(),
+        LinterFormat::Json {
+            diagnostics,
+            line,
+            column,
+            end_line,
+            end_column,
+            severity,
+            message,
+            code,
+        } => {
+            if output.trim().is_empty() {
+                return Ok(Vec::new());
+            }
+            let output: Value = serde_json::from_str(output)?;
+            let entries = output
+                .pointer(diagnostics)
+                .and_then(Value::as_array)
+                .ok_or_else(|| anyhow!("no array of diagnostics at '{diagnostics}'"))?;
+            entries
+                .iter()
+                .filter_map(|entry| {
+                    let field = |pointer: &Option<String>| entry.pointer(pointer.as_deref()?);
+                    let number = |pointer: &Option<String>| json_number(field(pointer)?);
+                    Some(LinterDiagnostic {
+                        line: json_number(entry.pointer(line)?)?,
+                        column: number(column),
+                        end_line: number(end_line),
+                        end_column: number(end_column),
+                        severity: field(severity)
+                            .and_then(json_string)
+                            .and_then(|severity| parse_severity(linter, &severity)),
+                        message: json_string(entry.pointer(message)?)?,
+                        code: field(code).and_then(json_string),
+                    })
+                })
+                .collect()
+        }
+    };
+    Ok(diagnostics)
+}
+
+fn json_number(value: &Value) -> Option<usize> {
+    match value {
+        Value::Number(number) => number.as_u64()?.try_into().ok(),
+        Value::String(string) => string.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn json_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(string) => Some(string.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+fn parse_severity(linter: &LinterConfiguration, severity: &str) -> Option<Severity> {
+    if let Some(&severity) = linter.severities.get(severity) {
+        return Some(severity);
+    }
+    match severity.to_lowercase().as_str() {
+        "error" | "err" | "e" | "fatal" => Some(Severity::Error),
+        "warning" | "warn" | "w" => Some(Severity::Warning),
+        "info" | "information" | "i" | "note" | "style" => Some(Severity::Info),
+        "hint" | "h" => Some(Severity::Hint),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn linter(config: &str) -> LinterConfiguration {
+        toml::from_str(config).unwrap()
+    }
+
+    #[test]
+    fn regex() {
+        let linter = linter(
+            r#"
+            name = "shellcheck"
+            command = "shellcheck"
+            format = { type = "regex", pattern = '^-:(?P<line>\d+):(?P<column>\d+): (?P<severity>\w+): (?P<message>.*) \[(?P<code>SC\d+)\]$' }
+            "#,
+        );
+        let output = "-:3:5: warning: foo is unused. [SC2034]\nnot a diagnostic\n-:10:1: note: Double quote. [SC2086]\n";
+        assert_eq!(
+            parse_output(&linter, output).unwrap(),
+            [
+                LinterDiagnostic {
+                    line: 3,
+                    column: Some(5),
+                    end_line: None,
+                    end_column: None,
+                    severity: Some(Severity::Warning),
+                    message: "foo is unused.".into(),
+                    code: Some("SC2034".into()),
+                },
+                LinterDiagnostic {
+                    line: 10,
+                    column: Some(1),
+                    end_line: None,
+                    end_column: None,
+                    severity: Some(Severity::Info),
+                    message: "Double quote.".into(),
+                    code: Some("SC2086".into()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn regex_requires_groups() {
+        let config = r#"
+            name = "lint"
+            command = "lint"
+            format = { type = "regex", pattern = '(?P<line>\d+)' }
+        "#;
+        assert!(toml::from_str::<LinterConfiguration>(config).is_err());
+    }
+
+    #[test]
+    fn json() {
+        let linter = linter(
+            r#"
+            name = "eslint"
+            command = "eslint"
+            format = { type = "json", diagnostics = "/0/messages", line = "/line", column = "/column", end-line = "/endLine", end-column = "/endColumn", severity = "/severity", message = "/message", code = "/ruleId" }
+            severities = { "1" = "warning", "2" = "error" }
+            "#,
+        );
+        let output = r#"[{"filePath": "a.js", "messages": [
+            {"ruleId": "no-unused-vars", "severity": 2, "message": "'a' is unused.", "line": 1, "column": 7, "endLine": 1, "endColumn": 8},
+            {"ruleId": null, "severity": 1, "message": "Parsing warning", "line": "4"},
+            {"message": "no line"}
+        ]}]"#;
+        assert_eq!(
+            parse_output(&linter, output).unwrap(),
+            [
+                LinterDiagnostic {
+                    line: 1,
+                    column: Some(7),
+                    end_line: Some(1),
+                    end_column: Some(8),
+                    severity: Some(Severity::Error),
+                    message: "'a' is unused.".into(),
+                    code: Some("no-unused-vars".into()),
+                },
+                LinterDiagnostic {
+                    line: 4,
+                    column: None,
+                    end_line: None,
+                    end_column: None,
+                    severity: Some(Severity::Warning),
+                    message: "Parsing warning".into(),
+                    code: None,
+                },
+            ]
+        );
+        assert!(parse_output(&linter, "").unwrap().is_empty());
+        assert!(parse_output(&linter, "{}").is_err());
+    }
+}
@@ -0,0 +1,340 @@
+//! Detection and resolution of merge conflicts delimited by conflict markers:
+//!
+//! ```text
+//! <<<<<<< ours
+//! our lines
+//! ||||||| base
+//! lines of the common ancestor (only with the diff3 conflict style)
+//! =======
+//! their lines
+//! >>>>>>> theirs
+//! ```
+
+use std::ops::Range;
+
+use crate::{Assoc, ChangeSet, Operation, Rope, RopeSlice, Tendril, Transaction};
+
+const MARKER_LEN: usize = 7;
+
+/// A merge conflict. All fields are the line indices of the conflict markers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conflict {
+    /// The line of the `<<<<<<<` marker.
+    pub start: usize,
+    /// The line of the `|||||||` marker if the conflict includes the common ancestor.
+    pub base: Option<usize>,
+    /// The line of the `=======` marker.
+    pub separator: usize,
+    /// The line of the `>>>>>>>` marker.
+    pub end: usize,
+}
+
+/// Which side of a conflict to keep when resolving it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Ours,
+    Theirs,
+    /// Our lines followed by their lines.
+    Both,
+    /// The lines of the common ancestor.
+    Base,
+}
+
+impl Conflict {
+    /// All lines of the conflict including the markers.
+    pub fn lines(&self) -> Range<usize> {
+        self.start..self.end + 1
+    }
+
+    /// The lines of our side of the conflict.
+    pub fn ours(&self) -> Range<usize> {
+        self.start + 1..self.base.unwrap_or(self.separator)
+    }
+
+    /// The lines of the common ancestor if the conflict includes them.
+    pub fn base_lines(&self) -> Option<Range<usize>> {
+        self.base.map(|base| base + 1..self.separator)
+    }
+
+    /// The lines of their side of the conflict.
+    pub fn theirs(&self) -> Range<usize> {
+        self.separator + 1..self.end
+    }
+
+    /// Whether `line` is one of the conflict markers.
+    pub fn is_marker(&self, line: usize) -> bool {
+        line == self.start || Some(line) == self.base || line == self.separator || line == self.end
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Marker {
+    Start,
+    Base,
+    Separator,
+    End,
+}
+
+fn marker(line: RopeSlice) -> Option<Marker> {
+    let ch = line.get_char(0)?;
+    let marker = match ch {
+        '<' => Marker::Start,
+        '|' => Marker::Base,
+        '=' => Marker::Separator,
+        '>' => Marker::End,
+        _ => return None,
+    };
+    if line.len_chars() < MARKER_LEN || line.chars().take(MARKER_LEN).any(|c| c != ch) {
+        return None;
+    }
+    // The marker must be followed by a label, the line ending or the end of the text. The
+    // separator never has a label.
+    match line.get_char(MARKER_LEN) {
+        None | Some('\n' | '\r') => Some(marker),
+        Some(' ') if marker != Marker::Separator => Some(marker),
+        _ => None,
+    }
+}
+
+/// Finds all conflicts in `text`, sorted by their position.
+pub fn find_conflicts(text: RopeSlice) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    let mut start = None;
+    let mut base = None;
+    let mut separator = None;
+    for (line_idx, line) in text.lines().enumerate() {
+        match marker(line) {
+            // A start marker inside an unfinished conflict restarts it.
+            Some(Marker::Start) => {
+                start = Some(line_idx);
+                base = None;
+                separator = None;
+            }
+            Some(Marker::Base) if start.is_some() && base.is_none() && separator.is_none() => {
+                base = Some(line_idx);
+            }
+            Some(Marker::Separator) if start.is_some() && separator.is_none() => {
+                separator = Some(line_idx);
+            }
+            Some(Marker::End) => {
+                if let (Some(start), Some(separator)) = (start, separator) {
+                    conflicts.push(Conflict {
+                        start,
+                        base,
+                        separator,
+                        end: line_idx,
+                    });
+                }
+                start = None;
+                base = None;
+                separator = None;
+            }
+            _ => (),
+        }
+    }
+    conflicts
+}
+
+/// Updates `conflicts` after `changes` turned `old_text` into `text`. The document is only
+/// scanned again if one of the changed lines was or is now a conflict marker, otherwise the
+/// conflicts are moved along with the unchanged marker lines.
+pub fn update_conflicts(
+    conflicts: &mut Vec<Conflict>,
+    old_text: RopeSlice,
+    text: RopeSlice,
+    changes: &ChangeSet,
+) {
+    let has_marker = |text: RopeSlice, start: usize, end: usize| {
+        (text.char_to_line(start)..=text.char_to_line(end))
+            .any(|line| marker(text.line(line)).is_some())
+    };
+    let mut old_pos = 0;
+    let mut new_pos = 0;
+    let mut rescan = false;
+    for change in changes.changes() {
+        match change {
+            Operation::Retain(n) => {
+                old_pos += n;
+                new_pos += n;
+            }
+            Operation::Delete(n) => {
+                rescan |= has_marker(old_text, old_pos, old_pos + n)
+                    || has_marker(text, new_pos, new_pos);
+                old_pos += n;
+            }
+            Operation::Insert(insert) => {
+                let len = insert.chars().count();
+                rescan |= has_marker(old_text, old_pos, old_pos)
+                    || has_marker(text, new_pos, new_pos + len);
+                new_pos += len;
+            }
+        }
+        if rescan {
+            *conflicts = find_conflicts(text);
+            return;
+        }
+    }
+
+    let map_line = |line: usize| {
+        let pos = changes.map_pos(old_text.line_to_char(line), Assoc::After);
+        text.char_to_line(pos)
+    };
+    for conflict in conflicts {
+        conflict.start = map_line(conflict.start);
+        conflict.base = conflict.base.map(map_line);
+        conflict.separator = map_line(conflict.separator);
+        conflict.end = map_line(conflict.end);
+    }
+}
+
+/// Returns the transaction replacing each of the conflicts with the lines chosen by
+/// `resolution`. Returns `None` if a conflict has no base section and `resolution` is
+/// [Resolution::Base].
+pub fn resolve<'a>(
+    doc: &Rope,
+    conflicts: impl IntoIterator<Item = &'a Conflict>,
+    resolution: Resolution,
+) -> Option<Transaction> {
+    let text = doc.slice(..);
+    let lines_text = |lines: Range<usize>| -> Tendril {
+        text.slice(text.line_to_char(lines.start)..text.line_to_char(lines.end))
+            .chunks()
+            .collect()
+    };
+    let changes = conflicts
+        .into_iter()
+        .map(|conflict| {
+            let replacement = match resolution {
+                Resolution::Ours => lines_text(conflict.ours()),
+                Resolution::Theirs => lines_text(conflict.theirs()),
+                Resolution::Both => {
+                    let mut both = lines_text(conflict.ours());
+                    both.push_str(&lines_text(conflict.theirs()));
+                    both
+                }
+                Resolution::Base => lines_text(conflict.base_lines()?),
+            };
+            let lines = conflict.lines();
+            Some((
+                text.line_to_char(lines.start),
+                text.line_to_char(lines.end),
+                (!replacement.is_empty()).then_some(replacement),
+            ))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(Transaction::change(doc, changes.into_iter()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn find() {
+        let text = Rope::from(
+            "a\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\nb\n\
+             <<<<<<< HEAD\n||||||| base\nbase\n=======\n>>>>>>> branch\n",
+        );
+        assert_eq!(
+            find_conflicts(text.slice(..)),
+            [
+                Conflict {
+                    start: 1,
+                    base: None,
+                    separator: 3,
+                    end: 5
+                },
+                Conflict {
+                    start: 7,
+                    base: Some(8),
+                    separator: 10,
+                    end: 11
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn find_ignores_incomplete() {
+        let text = Rope::from("=======\n<<<<<<<\n<<<<<<<< long\n>>>>>>>\n=======\n>>>>>>>");
+        assert!(find_conflicts(text.slice(..)).is_empty());
+
+        let text = Rope::from("<<<<<<<\n<<<<<<<\nours\n=======\n>>>>>>>");
+        assert_eq!(
+            find_conflicts(text.slice(..)),
+            [Conflict {
+                start: 1,
+                base: None,
+                separator: 3,
+                end: 4
+            }]
+        );
+    }
+
+    #[test]
+    fn update() {
+        let update = |text: &str, change: (usize, usize, Option<&str>)| {
+            let old_text = Rope::from(text);
+            let mut conflicts = find_conflicts(old_text.slice(..));
+            let transaction = Transaction::change(
+                &old_text,
+                std::iter::once((change.0, change.1, change.2.map(Tendril::from))),
+            );
+            let mut text = old_text.clone();
+            transaction.apply(&mut text);
+            update_conflicts(
+                &mut conflicts,
+                old_text.slice(..),
+                text.slice(..),
+                transaction.changes(),
+            );
+            assert_eq!(conflicts, find_conflicts(text.slice(..)));
+            conflicts.len()
+        };
+
+        let conflict = "a\n<<<<<<<\nours\n=======\ntheirs\n>>>>>>>\nb\n";
+        // typing the last char of a marker
+        assert_eq!(
+            update("<<<<<<<\nours\n=======\n>>>>>>\n", (27, 27, Some(">"))),
+            1
+        );
+        // deleting a char that breaks up a marker
+        assert_eq!(
+            update("<<<<<<<\nours\n=======\n>>>x>>>>\n", (24, 25, None)),
+            1
+        );
+        // deleting a marker
+        assert_eq!(update(conflict, (2, 10, None)), 0);
+        // edits that move the conflict
+        assert_eq!(update(conflict, (0, 0, Some("x\ny\n"))), 1);
+        assert_eq!(update(conflict, (10, 10, Some("more\n"))), 1);
+        assert_eq!(update(conflict, (0, 2, None)), 1);
+    }
+
+    #[test]
+    fn resolve_conflicts() {
+        let doc = Rope::from(
+            "a\n<<<<<<< HEAD\nours\n||||||| base\nbase\n=======\ntheirs\n>>>>>>> branch\nb\n",
+        );
+        let conflicts = find_conflicts(doc.slice(..));
+        let resolved = |resolution| {
+            let mut doc = doc.clone();
+            let transaction = resolve(&doc, &conflicts, resolution).unwrap();
+            transaction.apply(&mut doc);
+            doc.to_string()
+        };
+        assert_eq!(resolved(Resolution::Ours), "a\nours\nb\n");
+        assert_eq!(resolved(Resolution::Theirs), "a\ntheirs\nb\n");
+        assert_eq!(resolved(Resolution::Both), "a\nours\ntheirs\nb\n");
+        assert_eq!(resolved(Resolution::Base), "a\nbase\nb\n");
+
+        let doc = Rope::from("<<<<<<< HEAD\nours\n=======\n>>>>>>> branch\n");
+        let conflicts = find_conflicts(doc.slice(..));
+        assert!(resolve(&doc, &conflicts, Resolution::Base).is_none());
+        let mut resolved = doc.clone();
+        resolve(&doc, &conflicts, Resolution::Theirs)
+            .unwrap()
+            .apply(&mut resolved);
+        assert_eq!(resolved, "");
+    }
+}
@@ -126,10 +126,7 @@ pub fn move_vertically(
     let line_idx = slice.char_to_line(pos);
 
     // Compute the new position.
-    let mut new_line_idx = match dir {
-        Direction::Forward => line_idx.saturating_add(count),
-        Direction::Backward => line_idx.saturating_sub(count),
-    };
+    let mut new_line_idx = move_line_idx(slice, line_idx, dir, count, annotations);
 
     let line = if new_line_idx >= slice.len_lines() - 1 {
         // there is no line terminator for the last line
@@ -165,6 +162,41 @@ pub fn move_vertically(
     new_range
 }
 
+/// Moves `count` lines up or down from `line_idx`. Lines hidden by a fold are skipped.
+fn move_line_idx(
+    slice: RopeSlice,
+    line_idx: usize,
+    dir: Direction,
+    count: usize,
+    annotations: &TextAnnotations,
+) -> usize {
+    let last_line = slice.len_lines() - 1;
+    let mut new_line_idx = line_idx;
+    for _ in 0..count {
+        match dir {
+            Direction::Forward if new_line_idx < last_line => {
+                let mut next_line_idx = new_line_idx + 1;
+                if let Some(fold) = annotations.fold_hiding_line(slice.line_to_char(next_line_idx))
+                {
+                    next_line_idx = slice.char_to_line(fold.end) + 1;
+                }
+                if next_line_idx > last_line {
+                    break;
+                }
+                new_line_idx = next_line_idx;
+            }
+            Direction::Backward if new_line_idx > 0 => {
+                new_line_idx -= 1;
+                if let Some(fold) = annotations.fold_hiding_line(slice.line_to_char(new_line_idx)) {
+                    new_line_idx = slice.char_to_line(fold.start);
+                }
+            }
+            _ => break,
+        }
+    }
+    new_line_idx
+}
+
 pub fn move_next_word_start(slice: RopeSlice, range: Range, count: usize) -> Range {
     word_move(slice, range, count, WordMotionTarget::NextWordStart)
 }
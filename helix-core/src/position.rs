@@ -186,7 +186,8 @@ pub enum VisualOffsetError {
 }
 
 /// Returns the visual offset from the start of the visual line
-/// that contains anchor.
+/// that contains anchor. The virtual lines above the first line of
+/// the document are part of the first visual line.
 pub fn visual_offset_from_anchor(
     text: RopeSlice,
     anchor: usize,
@@ -220,13 +221,13 @@ pub fn visual_offset_from_anchor(
         if formatter.next_char_pos() > anchor && anchor_line.is_none() {
             if let Some(mut found_pos) = found_pos {
                 return if found_pos.row == last_pos.row {
-                    found_pos.row = 0;
+                    found_pos.row -= formatter.visual_line_start(last_pos.row);
                     Ok((found_pos, block_start))
                 } else {
                     Err(VisualOffsetError::PosBeforeAnchorRow)
                 };
             } else {
-                anchor_line = Some(last_pos.row);
+                anchor_line = Some(formatter.visual_line_start(last_pos.row));
             }
         }
 
@@ -237,7 +238,7 @@ pub fn visual_offset_from_anchor(
         }
     }
 
-    let anchor_line = anchor_line.unwrap_or(last_pos.row);
+    let anchor_line = anchor_line.unwrap_or(formatter.visual_line_start(last_pos.row));
     last_pos.row -= anchor_line;
 
     Ok((last_pos, block_start))
@@ -358,19 +359,66 @@ pub fn pos_at_visual_coords(text: RopeSlice, coords: Position, tab_width: usize)
 /// If the visual line at the specified offset is a virtual line generated by a `LineAnnotation`
 /// the previous char_index is returned, together with the remaining vertical offset (`virtual_lines`)
 pub fn char_idx_at_visual_offset(
+    text: RopeSlice,
+    anchor: usize,
+    row_offset: isize,
+    column: usize,
+    text_fmt: &TextFormat,
+    annotations: &TextAnnotations,
+) -> (usize, usize) {
+    let (block_start, row) = visual_block_offset(text, anchor, row_offset, text_fmt, annotations);
+    let (char_idx, char_row, _) =
+        char_at_visual_block_offset(text, block_start, row, column, text_fmt, annotations);
+    (char_idx, row - char_row)
+}
+
+/// Returns the anchor and vertical offset of a view that displays the visual line at
+/// `row_offset` from the visual line that contains `anchor` at its top.
+///
+/// This behaves the same as `char_idx_at_visual_offset` with a column of 0, except that
+/// the vertical offset is relative to the start of the visual line of the returned
+/// anchor. These only differ for the first visual line of the document which also
+/// contains the virtual lines above it: the vertical offset of the first line is the
+/// number of virtual lines above it.
+pub fn view_offset_at_visual_offset(
+    text: RopeSlice,
+    anchor: usize,
+    row_offset: isize,
+    text_fmt: &TextFormat,
+    annotations: &TextAnnotations,
+) -> (usize, usize) {
+    let (block_start, row) = visual_block_offset(text, anchor, row_offset, text_fmt, annotations);
+    let (char_idx, _, line_start) =
+        char_at_visual_block_offset(text, block_start, row, 0, text_fmt, annotations);
+    (char_idx, row - line_start)
+}
+
+/// Converts a row relative to the visual line containing `anchor` to a row relative to
+/// a block at or before `anchor`.
+///
+/// # Returns
+///
+/// `(block_start, row)`
+fn visual_block_offset(
     text: RopeSlice,
     mut anchor: usize,
     mut row_offset: isize,
-    column: usize,
     text_fmt: &TextFormat,
     annotations: &TextAnnotations,
 ) -> (usize, usize) {
     let mut pos = anchor;
+    let mut first_block = true;
     // convert row relative to visual line containing anchor to row relative to a block containing anchor (anchor may change)
     loop {
         let (visual_pos_in_block, block_char_offset) =
             visual_offset_from_block(text, anchor, pos, text_fmt, annotations);
-        row_offset += visual_pos_in_block.row as isize;
+        let mut row = visual_pos_in_block.row;
+        // the first visual line of the document starts above its virtual lines
+        if first_block && block_char_offset == 0 && row == annotations.virtual_lines_above_text() {
+            row = 0;
+        }
+        first_block = false;
+        row_offset += row as isize;
         anchor = block_char_offset;
         if row_offset >= 0 {
             break;
@@ -387,14 +435,7 @@ pub fn char_idx_at_visual_offset(
         anchor -= 1;
     }
 
-    char_idx_at_visual_block_offset(
-        text,
-        anchor,
-        row_offset as usize,
-        column,
-        text_fmt,
-        annotations,
-    )
+    (anchor, row_offset as usize)
 }
 
 /// This function behaves the same as `char_idx_at_visual_offset`, except that
@@ -416,6 +457,26 @@ pub fn char_idx_at_visual_block_offset(
     text_fmt: &TextFormat,
     annotations: &TextAnnotations,
 ) -> (usize, usize) {
+    let (char_idx, char_row, _) =
+        char_at_visual_block_offset(text, anchor, row, column, text_fmt, annotations);
+    (char_idx, row - char_row)
+}
+
+/// See `char_idx_at_visual_block_offset`.
+///
+/// # Returns
+///
+/// `(real_char_idx, char_row, line_start)` where `char_row` is the row of the returned
+/// char and `line_start` the row where its visual line starts, see
+/// [`DocumentFormatter::visual_line_start`].
+fn char_at_visual_block_offset(
+    text: RopeSlice,
+    anchor: usize,
+    row: usize,
+    column: usize,
+    text_fmt: &TextFormat,
+    annotations: &TextAnnotations,
+) -> (usize, usize, usize) {
     let mut formatter =
         DocumentFormatter::new_at_prev_checkpoint(text, text_fmt, annotations, anchor);
     let mut last_char_idx = formatter.next_char_pos();
@@ -426,17 +487,25 @@ pub fn char_idx_at_visual_block_offset(
             Ordering::Equal => {
                 if grapheme.visual_pos.col + grapheme.width() > column {
                     if !grapheme.is_virtual() {
-                        return (grapheme.char_idx, 0);
+                        return (grapheme.char_idx, row, formatter.visual_line_start(row));
                     } else if found_non_virtual_on_row {
-                        return (last_char_idx, 0);
+                        return (last_char_idx, row, formatter.visual_line_start(row));
                     }
                 } else if !grapheme.is_virtual() {
                     found_non_virtual_on_row = true;
                     last_char_idx = grapheme.char_idx;
                 }
             }
-            Ordering::Greater if found_non_virtual_on_row => return (last_char_idx, 0),
-            Ordering::Greater => return (last_char_idx, row - last_row),
+            Ordering::Greater if found_non_virtual_on_row => {
+                return (last_char_idx, row, formatter.visual_line_start(row))
+            }
+            Ordering::Greater => {
+                return (
+                    last_char_idx,
+                    last_row,
+                    formatter.visual_line_start(last_row),
+                )
+            }
             Ordering::Less => {
                 if !grapheme.is_virtual() {
                     last_row = grapheme.visual_pos.row;
@@ -446,13 +515,17 @@ pub fn char_idx_at_visual_block_offset(
         }
     }
 
-    (formatter.next_char_pos(), 0)
+    (
+        formatter.next_char_pos(),
+        row,
+        formatter.visual_line_start(row),
+    )
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::text_annotations::InlineAnnotation;
+    use crate::text_annotations::{InlineAnnotation, LineAnnotation};
     use crate::Rope;
 
     #[test]
@@ -837,6 +910,85 @@ fn test_char_idx_at_visual_row_offset_inline_annotation() {
         );
     }
 
+    struct LinesAboveText(usize);
+
+    impl LineAnnotation for LinesAboveText {
+        fn insert_virtual_lines(&mut self, _: usize, _: Position, _: usize) -> Position {
+            Position::new(0, 0)
+        }
+
+        fn insert_virtual_lines_above_text(&mut self) -> usize {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_virtual_lines_above_text() {
+        let text = Rope::from("foo\nbar\nbaz");
+        let slice = text.slice(..);
+        let text_fmt = TextFormat::default();
+        let mut annotations = TextAnnotations::default();
+        annotations.add_line_annotation(Box::new(LinesAboveText(2)));
+
+        // the virtual lines precede the first line within its block
+        assert_eq!(
+            visual_offset_from_block(slice, 0, 0, &text_fmt, &annotations).0,
+            Position::new(2, 0)
+        );
+        assert_eq!(
+            visual_offset_from_block(slice, 4, 5, &text_fmt, &annotations).0,
+            Position::new(0, 1)
+        );
+
+        // but they are part of the first visual line
+        assert_eq!(
+            visual_offset_from_anchor(slice, 0, 5, &text_fmt, &annotations, usize::MAX),
+            Ok((Position::new(3, 1), 0))
+        );
+        assert_eq!(
+            visual_offset_from_anchor(slice, 2, 2, &text_fmt, &annotations, usize::MAX),
+            Ok((Position::new(2, 2), 0))
+        );
+        assert_eq!(
+            visual_offset_from_anchor(slice, 4, 5, &text_fmt, &annotations, usize::MAX),
+            Ok((Position::new(0, 1), 4))
+        );
+
+        // the virtual lines are above the first char
+        assert_eq!(
+            char_idx_at_visual_offset(slice, 0, 1, 0, &text_fmt, &annotations),
+            (0, 1)
+        );
+        assert_eq!(
+            char_idx_at_visual_offset(slice, 4, -1, 1, &text_fmt, &annotations),
+            (1, 0)
+        );
+        assert_eq!(
+            char_idx_at_visual_offset(slice, 4, -2, 1, &text_fmt, &annotations),
+            (0, 1)
+        );
+
+        // a view scrolls through the virtual lines with the vertical offset
+        for (row_offset, view_offset) in [(-5, (0, 0)), (-3, (0, 0)), (-2, (0, 1)), (-1, (0, 2))] {
+            assert_eq!(
+                view_offset_at_visual_offset(slice, 4, row_offset, &text_fmt, &annotations),
+                view_offset
+            );
+        }
+        assert_eq!(
+            view_offset_at_visual_offset(slice, 0, 3, &text_fmt, &annotations),
+            (4, 0)
+        );
+        assert_eq!(
+            view_offset_at_visual_offset(slice, 0, 4, &text_fmt, &annotations),
+            (8, 0)
+        );
+        assert_eq!(
+            view_offset_at_visual_offset(slice, 4, -1, &text_fmt, &TextAnnotations::default()),
+            (0, 0)
+        );
+    }
+
     #[test]
     fn test_char_idx_at_visual_row_offset() {
         let text = Rope::from("ḧëḷḷö\nẅöṛḷḋ\nfoo");
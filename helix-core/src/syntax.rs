@@ -44,6 +44,7 @@ pub struct LanguageData {
     textobject_query: OnceCell<Option<TextObjectQuery>>,
     tag_query: OnceCell<Option<TagQuery>>,
     rainbow_query: OnceCell<Option<RainbowQuery>>,
+    fold_query: OnceCell<Option<FoldQuery>>,
 }
 
 impl LanguageData {
@@ -55,6 +56,7 @@ fn new(config: LanguageConfiguration) -> Self {
             textobject_query: OnceCell::new(),
             tag_query: OnceCell::new(),
             rainbow_query: OnceCell::new(),
+            fold_query: OnceCell::new(),
         }
     }
 
@@ -231,6 +233,36 @@ fn rainbow_query(&self, loader: &Loader) -> Option<&RainbowQuery> {
             .as_ref()
     }
 
+    /// Compiles the folds.scm query for a language.
+    /// This function should only be used by this module or the xtask crate.
+    pub fn compile_fold_query(
+        grammar: Grammar,
+        config: &LanguageConfiguration,
+    ) -> Result<Option<FoldQuery>> {
+        let name = &config.language_id;
+        let text = read_query(name, "folds.scm");
+        if text.is_empty() {
+            return Ok(None);
+        }
+        let fold_query = FoldQuery::new(grammar, &text)
+            .with_context(|| format!("Failed to compile folds.scm query for '{name}'"))?;
+        Ok(Some(fold_query))
+    }
+
+    fn fold_query(&self, loader: &Loader) -> Option<&FoldQuery> {
+        self.fold_query
+            .get_or_init(|| {
+                let grammar = self.syntax_config(loader)?.grammar;
+                Self::compile_fold_query(grammar, &self.config)
+                    .map_err(|err| {
+                        log::error!("{err}");
+                    })
+                    .ok()
+                    .flatten()
+            })
+            .as_ref()
+    }
+
     fn reconfigure(&self, scopes: &[String]) {
         if let Some(Some(config)) = self.syntax.get() {
             reconfigure_highlights(config, scopes);
@@ -424,6 +456,10 @@ fn rainbow_query(&self, lang: Language) -> Option<&RainbowQuery> {
         self.language(lang).rainbow_query(self)
     }
 
+    fn fold_query(&self, lang: Language) -> Option<&FoldQuery> {
+        self.language(lang).fold_query(self)
+    }
+
     pub fn language_server_configs(&self) -> &HashMap<String, LanguageServerConfiguration> {
         &self.language_server_configs
     }
@@ -699,6 +735,48 @@ struct RainbowScope<'tree> {
 
         OverlayHighlights::Heterogenous { highlights }
     }
+
+    /// Returns the line ranges of all nodes captured by the folds.scm queries
+    /// of the document's languages. Ranges spanning a single line are skipped.
+    /// The ranges are sorted by their start line and larger ranges come first.
+    pub fn fold_ranges(
+        &self,
+        source: RopeSlice,
+        loader: &Loader,
+        range: impl RangeBounds<u32>,
+    ) -> Vec<ops::RangeInclusive<usize>> {
+        let mut ranges = Vec::new();
+        let mut query_iter = self.query_iter::<_, (), _>(
+            source,
+            |lang| loader.fold_query(lang).map(|q| &q.query),
+            range,
+        );
+
+        while let Some(event) = query_iter.next() {
+            let QueryIterEvent::Match(mat) = event else {
+                continue;
+            };
+            let fold_query = loader
+                .fold_query(query_iter.current_language())
+                .expect("language must have a fold query to emit matches");
+            if Some(mat.capture) != fold_query.fold_capture {
+                continue;
+            }
+
+            let byte_range = mat.node.byte_range();
+            let start = source.byte_to_line(byte_range.start as usize);
+            // a node ending at the start of a line (including the line break) does not
+            // extend onto that line
+            let end = source.byte_to_line((byte_range.end as usize).saturating_sub(1));
+            if start < end {
+                ranges.push(start..=end);
+            }
+        }
+
+        ranges.sort_by_key(|range| (*range.start(), std::cmp::Reverse(*range.end())));
+        ranges.dedup();
+        ranges
+    }
 }
 
 pub type Highlighter<'a> = highlighter::Highlighter<'a, 'a, Loader>;
@@ -1196,6 +1274,25 @@ fn new(grammar: Grammar, source: &str) -> Result<Self, tree_sitter::query::Parse
     }
 }
 
+#[derive(Debug)]
+pub struct FoldQuery {
+    query: Query,
+    fold_capture: Option<Capture>,
+}
+
+impl FoldQuery {
+    fn new(grammar: Grammar, source: &str) -> Result<Self, tree_sitter::query::ParseError> {
+        let query = Query::new(grammar, source, |_pattern, predicate| {
+            Err(InvalidPredicateError::unknown(predicate))
+        })?;
+
+        Ok(Self {
+            fold_capture: query.get_capture("fold"),
+            query,
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use once_cell::sync::Lazy;
@@ -59,6 +59,10 @@ pub struct LanguageConfiguration {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub formatter: Option<FormatterConfiguration>,
 
+    /// External commands whose output is shown as diagnostics.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub linters: Vec<LinterConfiguration>,
+
     /// If set, overrides `editor.path-completion`.
     pub path_completion: Option<bool>,
     /// If set, overrides `editor.word-completion`.
@@ -103,6 +107,9 @@ pub struct LanguageConfiguration {
     pub persistent_diagnostic_sources: Vec<String>,
     /// Overrides the `editor.rainbow-brackets` config key for the language.
     pub rainbow_brackets: Option<bool>,
+    /// Key bindings of the documents of the language by mode, read by the editor's config.
+    #[serde(default, skip_serializing)]
+    pub keys: Option<toml::Value>,
 }
 
 impl LanguageConfiguration {
@@ -321,6 +328,10 @@ pub enum LanguageServerFeature {
     InlayHints,
     DocumentColors,
     CallHierarchy,
+    TypeHierarchy,
+    SemanticTokens,
+    FoldingRange,
+    CodeLens,
 }
 
 impl Display for LanguageServerFeature {
@@ -348,6 +359,10 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             InlayHints => "inlay-hints",
             DocumentColors => "document-colors",
             CallHierarchy => "call-hierarchy",
+            TypeHierarchy => "type-hierarchy",
+            SemanticTokens => "semantic-tokens",
+            FoldingRange => "folding-range",
+            CodeLens => "code-lens",
         };
         write!(f, "{feature}",)
     }
@@ -456,6 +471,104 @@ pub struct FormatterConfiguration {
     pub args: Vec<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct LinterConfiguration {
+    /// The name of the linter, shown as the source of its diagnostics.
+    pub name: String,
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub input: LinterInput,
+    #[serde(default)]
+    pub trigger: LinterTrigger,
+    pub format: LinterFormat,
+    /// Maps the severities printed by the linter to diagnostic severities. Severities not
+    /// in the map are matched against the names of the diagnostic severities.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub severities: HashMap<String, Severity>,
+    #[serde(default = "default_timeout")]
+    pub timeout: u64,
+}
+
+/// How the document is passed to a linter.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinterInput {
+    /// The contents of the document are written to stdin.
+    #[default]
+    Stdin,
+    /// The path of the document is passed as the last argument. The linter only runs
+    /// when the document has no unsaved changes.
+    File,
+}
+
+/// When a linter runs. Linters always run when a document is opened.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinterTrigger {
+    /// When the document is saved.
+    #[default]
+    Save,
+    /// When the document is saved and shortly after it was changed.
+    Idle,
+}
+
+/// How the output of a linter is parsed. Lines and columns start at 1 and end positions
+/// are exclusive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", tag = "type", deny_unknown_fields)]
+pub enum LinterFormat {
+    /// Each line of the output matching `pattern` is a diagnostic. The named capture
+    /// groups `line` and `message` are required, `column`, `end_line`, `end_column`,
+    /// `severity` and `code` are optional.
+    Regex {
+        #[serde(
+            serialize_with = "serialize_linter_pattern",
+            deserialize_with = "deserialize_linter_pattern"
+        )]
+        pattern: regex::Regex,
+    },
+    /// The output is JSON. `diagnostics` is a JSON pointer to the array of diagnostics and
+    /// the other fields are JSON pointers into a diagnostic.
+    #[serde(rename_all = "kebab-case")]
+    Json {
+        #[serde(default)]
+        diagnostics: String,
+        line: String,
+        column: Option<String>,
+        end_line: Option<String>,
+        end_column: Option<String>,
+        severity: Option<String>,
+        message: String,
+        code: Option<String>,
+    },
+}
+
+fn serialize_linter_pattern<S>(pattern: &regex::Regex, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(pattern.as_str())
+}
+
+fn deserialize_linter_pattern<'de, D>(deserializer: D) -> Result<regex::Regex, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let pattern = String::deserialize(deserializer)?;
+    let regex = regex::Regex::new(&pattern).map_err(serde::de::Error::custom)?;
+    for group in ["line", "message"] {
+        if !regex.capture_names().any(|name| name == Some(group)) {
+            return Err(serde::de::Error::custom(format!(
+                "linter pattern is missing the `{group}` capture group"
+            )));
+        }
+    }
+    Ok(regex)
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct AdvancedCompletion {
@@ -79,6 +79,30 @@ pub fn new(char_idx: usize, grapheme: impl Into<Tendril>) -> Self {
     }
 }
 
+/// A folded region of the document. The text in `start..end` (char indices)
+/// is concealed and a single placeholder grapheme is shown in its place.
+/// Vertical positioning treats the whole region as one grapheme, so lines
+/// inside the fold are skipped when moving between visual lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fold {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Fold {
+    pub fn new(start: usize, end: usize) -> Self {
+        debug_assert!(start < end);
+        Self { start, end }
+    }
+
+    /// Returns whether `char_idx` is concealed by this fold. The first char
+    /// of the fold is not considered concealed because the placeholder is
+    /// shown there.
+    pub fn conceals(&self, char_idx: usize) -> bool {
+        self.start < char_idx && char_idx < self.end
+    }
+}
+
 /// Line annotations allow inserting virtual text lines between normal text
 /// lines.  These lines can be filled with text in the rendering code as their
 /// contents have no effect beyond visual appearance.
@@ -171,6 +195,17 @@ fn insert_virtual_lines(
         line_end_visual_pos: Position,
         doc_line: usize,
     ) -> Position;
+
+    /// This function is called when a traversal starts at the start of the document
+    /// to insert virtual lines above the first line, which has no previous line whose
+    /// end `insert_virtual_lines` is called for.
+    ///
+    /// # Returns
+    ///
+    /// The number of virtual lines to reserve above the first line
+    fn insert_virtual_lines_above_text(&mut self) -> usize {
+        0
+    }
 }
 
 #[derive(Debug)]
@@ -278,6 +313,7 @@ fn drop(&mut self) {
 pub struct TextAnnotations<'a> {
     inline_annotations: Vec<Layer<'a, InlineAnnotation, Option<Highlight>>>,
     overlays: Vec<Layer<'a, Overlay, Option<Highlight>>>,
+    folds: Vec<Layer<'a, Fold, Option<Highlight>>>,
     line_annotations: Vec<(Cell<usize>, RawBox<dyn LineAnnotation + 'a>)>,
 }
 
@@ -286,6 +322,7 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("TextAnnotations")
             .field("inline_annotations", &self.inline_annotations)
             .field("overlays", &self.overlays)
+            .field("folds", &self.folds)
             .finish_non_exhaustive()
     }
 }
@@ -295,6 +332,7 @@ impl<'a> TextAnnotations<'a> {
     pub fn reset_pos(&self, char_idx: usize) {
         reset_pos(&self.inline_annotations, char_idx, |annot| annot.char_idx);
         reset_pos(&self.overlays, char_idx, |annot| annot.char_idx);
+        reset_pos(&self.folds, char_idx, |fold| fold.start);
         for (next_anchor, layer) in &self.line_annotations {
             next_anchor.set(unsafe { layer.get().reset_pos(char_idx) });
         }
@@ -354,6 +392,19 @@ pub fn add_overlay(&mut self, layer: &'a [Overlay], highlight: Option<Highlight>
         self
     }
 
+    /// Add new folds.
+    ///
+    /// The placeholder shown for each fold is rendered with `highlight`
+    /// patched on top of `ui.text`.
+    ///
+    /// The folds **must be sorted** by their `start` and **must not overlap**.
+    pub fn add_folds(&mut self, layer: &'a [Fold], highlight: Option<Highlight>) -> &mut Self {
+        if !layer.is_empty() {
+            self.folds.push((layer, highlight).into());
+        }
+        self
+    }
+
     /// Add new annotation lines.
     ///
     /// The line annotations **must be sorted** by their `char_idx`.
@@ -390,6 +441,34 @@ pub(crate) fn overlay_at(&self, char_idx: usize) -> Option<(&Overlay, Option<Hig
         overlay
     }
 
+    pub(crate) fn fold_at(&self, char_idx: usize) -> Option<(&Fold, Option<Highlight>)> {
+        self.folds.iter().find_map(|layer| {
+            let fold = layer.consume(char_idx, |fold| fold.start)?;
+            Some((fold, layer.metadata))
+        })
+    }
+
+    /// Returns the fold that hides the line starting at `line_start`, if any.
+    /// The line that contains the end of a fold is hidden even if it is empty.
+    pub fn fold_hiding_line(&self, line_start: usize) -> Option<&Fold> {
+        self.folds.iter().find_map(|layer| {
+            let i = layer
+                .annotations
+                .partition_point(|fold| fold.start < line_start);
+            layer.annotations[..i]
+                .last()
+                .filter(|fold| line_start <= fold.end)
+        })
+    }
+
+    /// Skips all inline annotations and overlays that are concealed by a fold
+    /// ending at `char_idx`.
+    pub(crate) fn skip_concealed(&self, char_idx: usize) {
+        reset_pos(&self.inline_annotations, char_idx, |annot| annot.char_idx);
+        reset_pos(&self.overlays, char_idx, |annot| annot.char_idx);
+        reset_pos(&self.folds, char_idx, |fold| fold.start);
+    }
+
     pub(crate) fn process_virtual_text_anchors(&self, grapheme: &FormattedGrapheme) {
         for (next_anchor, layer) in &self.line_annotations {
             loop {
@@ -421,4 +500,11 @@ pub(crate) fn virtual_lines_at(
         }
         virt_off.row
     }
+
+    pub(crate) fn virtual_lines_above_text(&self) -> usize {
+        self.line_annotations
+            .iter()
+            .map(|(_, layer)| unsafe { layer.get().insert_virtual_lines_above_text() })
+            .sum()
+    }
 }
@@ -371,6 +371,18 @@ pub fn is_empty(&self) -> bool {
         self.changes.is_empty() || self.changes == [Operation::Retain(self.len)]
     }
 
+    /// The length of the document the changes apply to.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// The length of the document after applying the changes.
+    #[inline]
+    pub fn len_after(&self) -> usize {
+        self.len_after
+    }
+
     /// Map a (mostly) *sorted* list of positions through the changes.
     ///
     /// This is equivalent to updating each position with `map_pos`:
@@ -453,6 +453,42 @@ pub async fn set_breakpoints(
         Ok(response.breakpoints)
     }
 
+    pub async fn set_function_breakpoints(
+        &self,
+        breakpoints: Vec<FunctionBreakpoint>,
+    ) -> Result<Vec<Breakpoint>> {
+        let args = requests::SetFunctionBreakpointsArguments { breakpoints };
+
+        let response = self
+            .request::<requests::SetFunctionBreakpoints>(args)
+            .await?;
+        Ok(response.breakpoints)
+    }
+
+    pub fn data_breakpoint_info(
+        &self,
+        name: String,
+        frame_id: Option<usize>,
+    ) -> impl Future<Output = Result<Value>> {
+        let args = requests::DataBreakpointInfoArguments {
+            variables_reference: None,
+            name,
+            frame_id,
+        };
+
+        self.call::<requests::DataBreakpointInfo>(args)
+    }
+
+    pub async fn set_data_breakpoints(
+        &self,
+        breakpoints: Vec<DataBreakpoint>,
+    ) -> Result<Vec<Breakpoint>> {
+        let args = requests::SetDataBreakpointsArguments { breakpoints };
+
+        let response = self.request::<requests::SetDataBreakpoints>(args).await?;
+        Ok(response.breakpoints)
+    }
+
     pub async fn configuration_done(&self) -> Result<()> {
         if !self
             .caps
@@ -550,17 +586,59 @@ pub async fn eval(
         &self,
         expression: String,
         frame_id: Option<usize>,
+        context: Option<&str>,
     ) -> Result<requests::EvaluateResponse> {
         let args = requests::EvaluateArguments {
             expression,
             frame_id,
-            context: None,
+            context: context.map(ToOwned::to_owned),
             format: None,
         };
 
         self.request::<requests::Evaluate>(args).await
     }
 
+    /// Evaluates `expression` as if it was entered into a debug console.
+    ///
+    /// Unlike [`Client::eval`] the returned future doesn't borrow the client so the
+    /// expression can be evaluated in the background.
+    pub fn repl_eval(
+        &self,
+        expression: String,
+        frame_id: Option<usize>,
+    ) -> impl Future<Output = Result<requests::EvaluateResponse>> {
+        let args = requests::EvaluateArguments {
+            expression,
+            frame_id,
+            context: Some("repl".to_owned()),
+            format: None,
+        };
+
+        let response = self.call::<requests::Evaluate>(args);
+        async move { Ok(serde_json::from_value(response.await?)?) }
+    }
+
+    /// Requests completions for `text` at the 1-based UTF-16 `column`.
+    pub fn completions(
+        &self,
+        text: String,
+        column: usize,
+        frame_id: Option<usize>,
+    ) -> impl Future<Output = Result<Vec<CompletionItem>>> {
+        let args = requests::CompletionsArguments {
+            frame_id,
+            text,
+            column,
+            line: None,
+        };
+
+        let response = self.call::<requests::Completions>(args);
+        async move {
+            let response: requests::CompletionsResponse = serde_json::from_value(response.await?)?;
+            Ok(response.targets)
+        }
+    }
+
     pub fn set_exception_breakpoints(
         &self,
         filters: Vec<String>,
@@ -16,6 +16,9 @@
 
 static LOG_FILE: once_cell::sync::OnceCell<PathBuf> = once_cell::sync::OnceCell::new();
 
+static LISTENING_REMOTE_SOCKET: once_cell::sync::OnceCell<PathBuf> =
+    once_cell::sync::OnceCell::new();
+
 pub fn initialize_config_file(specified_file: Option<PathBuf>) {
     let config_file = specified_file.unwrap_or_else(default_config_file);
     ensure_parent_dir(&config_file);
@@ -172,6 +175,45 @@ pub fn workspace_exclude_file() -> PathBuf {
     data_dir().join("excluded_workspaces")
 }
 
+pub fn undo_dir() -> PathBuf {
+    data_dir().join("undo")
+}
+
+pub fn session_dir() -> PathBuf {
+    data_dir().join("sessions")
+}
+
+pub fn history_file() -> PathBuf {
+    data_dir().join("history.json")
+}
+
+/// A hex encoded hash of `path` used to name the files kept per path in the data directory,
+/// like undo histories. The hash is stable across runs and versions of Helix.
+pub fn path_hash(path: &Path) -> String {
+    use sha1::{Digest, Sha1};
+
+    let hash = Sha1::digest(path.as_os_str().as_encoded_bytes());
+    hash.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// The socket used for remote control, `$HELIX_REMOTE_SOCKET` if set.
+pub fn remote_socket() -> PathBuf {
+    std::env::var_os("HELIX_REMOTE_SOCKET")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| cache_dir().join("remote.sock"))
+}
+
+/// Records that this instance listens for remote control requests on `path`.
+pub fn initialize_listening_remote_socket(path: PathBuf) {
+    LISTENING_REMOTE_SOCKET.set(path).ok();
+}
+
+/// The socket this instance listens on for remote control requests, if any. Shell commands
+/// started by the editor receive it as `$HELIX_REMOTE_SOCKET`.
+pub fn listening_remote_socket() -> Option<&'static Path> {
+    LISTENING_REMOTE_SOCKET.get().map(PathBuf::as_path)
+}
+
 /// Merge two TOML documents, merging values from `right` onto `left`
 ///
 /// `merge_depth` sets the nesting depth up to which values are merged instead
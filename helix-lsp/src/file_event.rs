@@ -8,6 +8,7 @@
 enum Event {
     FileChanged {
         path: PathBuf,
+        typ: lsp::FileChangeType,
     },
     Register {
         client_id: LanguageServerId,
@@ -80,7 +81,11 @@ pub fn unregister(&self, client_id: LanguageServerId, registration_id: String) {
     }
 
     pub fn file_changed(&self, path: PathBuf) {
-        let _ = self.tx.send(Event::FileChanged { path });
+        self.file_event(path, lsp::FileChangeType::CHANGED);
+    }
+
+    pub fn file_event(&self, path: PathBuf, typ: lsp::FileChangeType) {
+        let _ = self.tx.send(Event::FileChanged { path, typ });
     }
 
     pub fn remove_client(&self, client_id: LanguageServerId) {
@@ -91,7 +96,7 @@ async fn run(mut rx: mpsc::UnboundedReceiver<Event>) {
         let mut state: HashMap<LanguageServerId, ClientState> = HashMap::new();
         while let Some(event) = rx.recv().await {
             match event {
-                Event::FileChanged { path } => {
+                Event::FileChanged { path, typ } => {
                     log::debug!("Received file event for {:?}", &path);
 
                     state.retain(|id, client_state| {
@@ -113,13 +118,7 @@ async fn run(mut rx: mpsc::UnboundedReceiver<Event>) {
                             "Sending didChangeWatchedFiles notification to client '{}'",
                             client.name()
                         );
-                        client.did_change_watched_files(vec![lsp::FileEvent {
-                            uri,
-                            // We currently always send the CHANGED state
-                            // since we don't actually have more context at
-                            // the moment.
-                            typ: lsp::FileChangeType::CHANGED,
-                        }]);
+                        client.did_change_watched_files(vec![lsp::FileEvent { uri, typ }]);
                         true
                     });
                 }
@@ -12,6 +12,7 @@
     document::{DocumentOpenError, DocumentSavedEventResult},
     editor::{ConfigEvent, EditorEvent},
     graphics::Rect,
+    session::{self, Session},
     theme,
     tree::Layout,
     Align, Editor,
@@ -21,20 +22,23 @@
 
 use crate::{
     args::Args,
+    commands, compositor,
     compositor::{Compositor, Event},
     config::Config,
     handlers,
     job::Jobs,
     keymap::Keymaps,
+    remote::{self, Request, Response},
     ui::{self, overlay::overlaid},
 };
 
 use log::{debug, error, info, warn};
 use std::{
     io::{stdin, IsTerminal},
-    path::Path,
+    path::{Path, PathBuf},
     sync::Arc,
 };
+use tokio::sync::oneshot;
 
 #[cfg_attr(windows, allow(unused_imports))]
 use anyhow::{Context, Error};
@@ -79,6 +83,10 @@ pub struct Application {
     lsp_progress: LspProgressMap,
 
     theme_mode: Option<theme::Mode>,
+    /// The server receiving remote control requests if `remote-control` is enabled.
+    remote: Option<remote::Server>,
+    /// The replies to [Request::Wait] requests with the paths that are still open.
+    remote_waits: Vec<(Vec<PathBuf>, oneshot::Sender<Response>)>,
 }
 
 #[cfg(feature = "integration")]
@@ -129,6 +137,10 @@ pub fn new(args: Args, config: Config, lang_loader: syntax::Loader) -> Result<Se
         let mut compositor = Compositor::new(area);
         let config = Arc::new(ArcSwap::from_pointee(config));
         let handlers = handlers::setup(config.clone());
+        commands::typed::init_custom_commands(Box::new(Map::new(
+            Arc::clone(&config),
+            |config: &Config| &config.commands,
+        )));
         let mut editor = Editor::new(
             area,
             Arc::new(theme_loader),
@@ -138,12 +150,26 @@ pub fn new(args: Args, config: Config, lang_loader: syntax::Loader) -> Result<Se
             })),
             handlers,
         );
+        let persistent_history = editor.config().persistent_history.clone();
+        if persistent_history.enable {
+            if let Err(err) = editor
+                .registers
+                .load_persisted(&helix_loader::history_file(), &persistent_history.registers)
+            {
+                log::error!("Failed to load history: {err:#}");
+            }
+        }
         Self::load_configured_theme(&mut editor, &config.load(), &mut terminal, theme_mode);
 
         let keys = Box::new(Map::new(Arc::clone(&config), |config: &Config| {
             &config.keys
         }));
-        let editor_view = Box::new(ui::EditorView::new(Keymaps::new(keys)));
+        let language_keys = Box::new(Map::new(Arc::clone(&config), |config: &Config| {
+            &config.language_keys
+        }));
+        let editor_view = Box::new(ui::EditorView::new(
+            Keymaps::new(keys).with_language_map(language_keys),
+        ));
         compositor.push(editor_view);
 
         let jobs = Jobs::new();
@@ -233,6 +259,15 @@ pub fn new(args: Args, config: Config, lang_loader: syntax::Loader) -> Result<Se
             }
         } else if stdin().is_terminal() || cfg!(feature = "integration") {
             editor.new_file(Action::VerticalSplit);
+            let session_file = session::workspace_session_file();
+            if editor.config().auto_session && session_file.exists() {
+                // The empty scratch buffer is closed once all views are replaced.
+                if let Err(err) = Session::load(&session_file)
+                    .and_then(|session| editor.restore_session(&session))
+                {
+                    editor.set_error(err.to_string());
+                }
+            }
         } else {
             editor
                 .new_file_from_stdin(Action::VerticalSplit)
@@ -251,6 +286,23 @@ pub fn new(args: Args, config: Config, lang_loader: syntax::Loader) -> Result<Se
         ])
         .context("build signal handler")?;
 
+        let remote = if editor.config().remote_control {
+            let path = helix_loader::remote_socket();
+            match remote::Server::bind(path.clone()) {
+                Ok(server) => {
+                    // Lets shell commands started from the editor find the socket.
+                    helix_loader::initialize_listening_remote_socket(path);
+                    Some(server)
+                }
+                Err(err) => {
+                    warn!("Not listening for remote control requests: {err:#}");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
         let app = Self {
             compositor,
             terminal,
@@ -260,6 +312,8 @@ pub fn new(args: Args, config: Config, lang_loader: syntax::Loader) -> Result<Se
             jobs,
             lsp_progress: LspProgressMap::new(),
             theme_mode,
+            remote,
+            remote_waits: Vec::new(),
         };
 
         Ok(app)
@@ -319,6 +373,7 @@ pub async fn event_loop_until_idle<S>(&mut self, input_stream: &mut S) -> bool
             if self.editor.should_close() {
                 return false;
             }
+            self.reply_to_remote_waits(false);
 
             use futures_util::StreamExt;
 
@@ -352,6 +407,10 @@ pub async fn event_loop_until_idle<S>(&mut self, input_stream: &mut S) -> bool
                     self.jobs.handle_callback(&mut self.editor, &mut self.compositor, callback);
                     self.render().await;
                 }
+                Some((request, reply)) = remote::recv(&mut self.remote) => {
+                    self.handle_remote_request(request, reply);
+                    self.render().await;
+                }
                 event = self.editor.wait_event() => {
                     let _idle_handled = self.handle_editor_event(event).await;
 
@@ -373,6 +432,73 @@ pub async fn event_loop_until_idle<S>(&mut self, input_stream: &mut S) -> bool
         }
     }
 
+    fn handle_remote_request(&mut self, request: Request, reply: oneshot::Sender<Response>) {
+        let result = match request {
+            Request::Open { path, position } => self.remote_open(path, position),
+            Request::Command { command } => {
+                let mut cx = compositor::Context {
+                    editor: &mut self.editor,
+                    scroll: None,
+                    jobs: &mut self.jobs,
+                };
+                let command = command.strip_prefix(':').unwrap_or(&command);
+                commands::typed::execute_command_line(&mut cx, command, ui::PromptEvent::Validate)
+                    .map(|_| None)
+            }
+            Request::CurrentPath => Ok(doc!(self.editor)
+                .path()
+                .map(|path| path.to_string_lossy().into_owned())),
+            Request::Wait { paths } => {
+                let paths = paths
+                    .into_iter()
+                    .map(helix_stdx::path::canonicalize)
+                    .collect();
+                self.remote_waits.push((paths, reply));
+                return;
+            }
+        };
+        let _ = reply.send(Response::from_result(result));
+    }
+
+    fn remote_open(
+        &mut self,
+        path: PathBuf,
+        position: Option<remote::Position>,
+    ) -> anyhow::Result<Option<String>> {
+        let path = helix_stdx::path::canonicalize(path);
+        self.editor
+            .open(&path, helix_view::editor::Action::Replace)?;
+        if let Some(position) = position {
+            let (view, doc) = current!(self.editor);
+            let pos = helix_core::Position::new(
+                position.line.saturating_sub(1),
+                position.column.saturating_sub(1),
+            );
+            let pos = pos_at_coords(doc.text().slice(..), pos, true);
+            doc.set_selection(view.id, Selection::point(pos));
+            align_view(doc, view, Align::Center);
+        }
+        Ok(None)
+    }
+
+    /// Replies to the [Request::Wait] requests whose documents are all closed, or to all of
+    /// them if `exiting`.
+    fn reply_to_remote_waits(&mut self, exiting: bool) {
+        if self.remote_waits.is_empty() {
+            return;
+        }
+        for (paths, _) in &mut self.remote_waits {
+            paths.retain(|path| self.editor.document_by_path(path).is_some());
+        }
+        let (done, waiting) = std::mem::take(&mut self.remote_waits)
+            .into_iter()
+            .partition(|(paths, _)| paths.is_empty() || exiting);
+        self.remote_waits = waiting;
+        for (_, reply) in done {
+            let _ = reply.send(Response::Ok { value: None });
+        }
+    }
+
     pub fn handle_config_events(&mut self, config_event: ConfigEvent) {
         let old_editor_config = self.editor.config();
 
@@ -591,6 +717,7 @@ pub fn handle_document_write(&mut self, doc_save_event: DocumentSavedEventResult
             }
         };
 
+        let persistent_undo = self.editor.config().persistent_undo;
         let doc = match self.editor.document_mut(doc_save_event.doc_id) {
             None => {
                 warn!(
@@ -611,6 +738,14 @@ pub fn handle_document_write(&mut self, doc_save_event: DocumentSavedEventResult
 
         doc.set_last_saved_revision(doc_save_event.revision, doc_save_event.save_time);
 
+        if persistent_undo {
+            doc.save_history(
+                &doc_save_event.path,
+                doc_save_event.revision,
+                &doc_save_event.text,
+            );
+        }
+
         let lines = doc_save_event.text.len_lines();
         let size = doc_save_event.text.len_bytes();
 
@@ -648,6 +783,11 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             "'{}' written, {lines}L {size}",
             get_relative_path(&doc_save_event.path).to_string_lossy(),
         ));
+
+        helix_event::dispatch(helix_view::events::DocumentDidSave {
+            editor: &mut self.editor,
+            doc: doc_save_event.doc_id,
+        });
     }
 
     #[inline(always)]
@@ -1133,6 +1273,43 @@ macro_rules! language_server {
 
                         Ok(serde_json::Value::Null)
                     }
+                    Ok(MethodCall::SemanticTokensRefresh) => {
+                        let language_server = language_server!().id();
+
+                        let documents: Vec<_> = self
+                            .editor
+                            .documents
+                            .values()
+                            .filter(|x| x.supports_language_server(language_server))
+                            .map(|x| x.id())
+                            .collect();
+
+                        for document in documents {
+                            handlers::semantic_tokens::request_semantic_tokens(
+                                &mut self.editor,
+                                document,
+                            );
+                        }
+
+                        Ok(serde_json::Value::Null)
+                    }
+                    Ok(MethodCall::CodeLensRefresh) => {
+                        let language_server = language_server!().id();
+
+                        let documents: Vec<_> = self
+                            .editor
+                            .documents
+                            .values()
+                            .filter(|x| x.supports_language_server(language_server))
+                            .map(|x| x.id())
+                            .collect();
+
+                        for document in documents {
+                            handlers::code_lens::request_code_lenses(&mut self.editor, document);
+                        }
+
+                        Ok(serde_json::Value::Null)
+                    }
                     Ok(MethodCall::ShowMessageRequest(params)) => {
                         if let Some(actions) = params.actions.filter(|a| !a.is_empty()) {
                             let id = id.clone();
@@ -1353,6 +1530,20 @@ pub async fn close(&mut self) -> Vec<anyhow::Error> {
             log::error!("Error writing: {}", err);
             errs.push(err);
         }
+        // The documents are written, programs waiting for them to be closed can continue.
+        self.reply_to_remote_waits(true);
+
+        let persistent_history = &self.editor.config().persistent_history;
+        if persistent_history.enable {
+            if let Err(err) = self.editor.registers.save_persisted(
+                &helix_loader::history_file(),
+                &persistent_history.registers,
+                persistent_history.max_entries,
+            ) {
+                log::error!("Error saving history: {:#}", err);
+                errs.push(err);
+            }
+        }
 
         if self.editor.close_language_servers(None).await.is_err() {
             log::error!("Timed out waiting for language servers to shutdown");
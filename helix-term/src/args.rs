@@ -20,6 +20,17 @@ pub struct Args {
     pub config_file: Option<PathBuf>,
     pub files: IndexMap<PathBuf, Vec<Position>>,
     pub working_directory: Option<PathBuf>,
+    pub remote: bool,
+    pub remote_wait: bool,
+    pub remote_command: Option<String>,
+    pub remote_current_path: bool,
+}
+
+impl Args {
+    /// Whether to send requests to a running instance instead of starting the editor.
+    pub fn is_remote(&self) -> bool {
+        self.remote || self.remote_wait || self.remote_command.is_some() || self.remote_current_path
+    }
 }
 
 impl Args {
@@ -77,6 +88,13 @@ pub fn parse_args() -> Result<Args> {
                     Some(path) => args.log_file = Some(path.into()),
                     None => anyhow::bail!("--log must specify a path to write"),
                 },
+                "--remote" => args.remote = true,
+                "--remote-wait" => args.remote_wait = true,
+                "--remote-command" => match argv.next() {
+                    Some(command) => args.remote_command = Some(command),
+                    None => anyhow::bail!("--remote-command must specify a command to run"),
+                },
+                "--remote-current-path" => args.remote_current_path = true,
                 "-w" | "--working-dir" => match argv.next().as_deref() {
                     Some(path) => {
                         args.working_directory = if Path::new(path).is_dir() {
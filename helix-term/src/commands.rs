@@ -32,6 +32,7 @@
     indent::{self, IndentStyle},
     line_ending::{get_line_ending_of_str, line_end_char_index},
     match_brackets,
+    merge_conflict::{self, Resolution},
     movement::{self, move_vertically_visual, Direction},
     object, pos_at_coords,
     regex::{self, Regex},
@@ -41,11 +42,11 @@
     text_annotations::{Overlay, TextAnnotations},
     textobject,
     unicode::width::UnicodeWidthChar,
-    visual_offset_from_block, Deletion, LineEnding, Position, Range, Rope, RopeReader, RopeSlice,
-    Selection, SmallVec, Syntax, Tendril, Transaction,
+    view_offset_at_visual_offset, visual_offset_from_block, Deletion, LineEnding, Position, Range,
+    Rope, RopeReader, RopeSlice, Selection, SmallVec, Syntax, Tendril, Transaction,
 };
 use helix_view::{
-    document::{FormatterError, Mode, SCRATCH_BUFFER_NAME},
+    document::{FormatterError, LineBlame, Mode, SCRATCH_BUFFER_NAME},
     editor::{Action, Motion},
     expansion,
     info::Info,
@@ -79,6 +80,8 @@
     future::Future,
     io::Read,
     num::NonZeroUsize,
+    ops,
+    sync::{atomic, Arc},
 };
 
 use std::{
@@ -90,8 +93,9 @@
 use serde::de::{self, Deserialize, Deserializer};
 use url::Url;
 
-use grep_regex::RegexMatcherBuilder;
-use grep_searcher::{sinks, BinaryDetection, SearcherBuilder};
+use grep_matcher::Matcher;
+use grep_regex::{RegexMatcher, RegexMatcherBuilder};
+use grep_searcher::{sinks, BinaryDetection, Searcher, SearcherBuilder};
 use ignore::{DirEntry, WalkBuilder, WalkState};
 
 pub type OnKeyCallback = Box<dyn FnOnce(&mut Context, KeyEvent)>;
@@ -249,19 +253,18 @@ impl MappableCommand {
     pub fn execute(&self, cx: &mut Context) {
         match &self {
             Self::Typable { name, args, doc: _ } => {
-                if let Some(command) = typed::TYPABLE_COMMAND_MAP.get(name.as_str()) {
-                    let mut cx = compositor::Context {
-                        editor: cx.editor,
-                        jobs: cx.jobs,
-                        scroll: None,
-                    };
-                    if let Err(e) =
-                        typed::execute_command(&mut cx, command, args, PromptEvent::Validate)
-                    {
-                        cx.editor.set_error(format!("{}", e));
-                    }
-                } else {
-                    cx.editor.set_error(format!("no such command: '{name}'"));
+                let mut ccx = compositor::Context {
+                    editor: cx.editor,
+                    jobs: cx.jobs,
+                    scroll: None,
+                };
+                match typed::execute_typable(&mut ccx, name, args, None) {
+                    // Like a macro, the commands of a custom command run in a callback.
+                    Ok(Some(call)) => cx
+                        .callback
+                        .push(Box::new(move |compositor, cx| call.execute(cx, compositor))),
+                    Ok(None) => (),
+                    Err(e) => ccx.editor.set_error(format!("{}", e)),
                 }
             }
             Self::Static { fun, .. } => (fun)(cx),
@@ -383,6 +386,7 @@ pub fn doc(&self) -> &str {
         search_selection_detect_word_boundaries, "Use current selection as the search pattern, automatically wrapping with `\\b` on word boundaries",
         make_search_word_bounded, "Modify current search to make it word bounded",
         global_search, "Global search in workspace folder",
+        global_replace, "Global search and replace in workspace folder",
         extend_line, "Select current line, if already selected, extend to another line based on the anchor",
         extend_line_below, "Select current line, if already selected, extend to next line",
         extend_line_above, "Select current line, if already selected, extend to previous line",
@@ -407,12 +411,17 @@ pub fn doc(&self) -> &str {
         file_explorer_in_current_buffer_directory, "Open file explorer at current buffer's directory",
         file_explorer_in_current_directory, "Open file explorer at current working directory",
         code_action, "Perform code action",
+        code_lens, "Perform code lens on the current line",
         buffer_picker, "Open buffer picker",
         jumplist_picker, "Open jumplist picker",
         symbol_picker, "Open symbol picker",
         syntax_symbol_picker, "Open symbol picker from syntax information",
         lsp_or_syntax_symbol_picker, "Open symbol picker from LSP or syntax information",
         changed_file_picker, "Open changed file picker",
+        blame_line, "Show the commit that last changed the current line",
+        stage_diff_change, "Stage the diff changes under the selections",
+        unstage_diff_change, "Unstage the staged diff changes under the selections",
+        show_diff_change_base, "Show the base text of the diff change at the cursor",
         select_references_to_symbol_under_cursor, "Select symbol references",
         workspace_symbol_picker, "Open workspace symbol picker",
         syntax_workspace_symbol_picker, "Open workspace symbol picker from syntax information",
@@ -441,6 +450,10 @@ pub fn doc(&self) -> &str {
         goto_file_hsplit, "Goto files in selections (hsplit)",
         goto_file_vsplit, "Goto files in selections (vsplit)",
         goto_reference, "Goto references",
+        incoming_calls, "Open picker of incoming calls",
+        outgoing_calls, "Open picker of outgoing calls",
+        supertypes, "Open picker of supertypes",
+        subtypes, "Open picker of subtypes",
         goto_window_top, "Goto window top",
         goto_window_center, "Goto window center",
         goto_window_bottom, "Goto window bottom",
@@ -458,6 +471,12 @@ pub fn doc(&self) -> &str {
         goto_prev_change, "Goto previous change",
         goto_first_change, "Goto first change",
         goto_last_change, "Goto last change",
+        goto_next_conflict, "Goto next merge conflict",
+        goto_prev_conflict, "Goto previous merge conflict",
+        accept_conflict_ours, "Resolve merge conflicts by accepting our changes",
+        accept_conflict_theirs, "Resolve merge conflicts by accepting their changes",
+        accept_conflict_both, "Resolve merge conflicts by accepting both changes",
+        accept_conflict_base, "Resolve merge conflicts by restoring the common ancestor",
         goto_line_start, "Goto line start",
         goto_line_end, "Goto line end",
         goto_column, "Goto column",
@@ -543,6 +562,12 @@ pub fn doc(&self) -> &str {
         swap_view_up, "Swap with split above",
         swap_view_down, "Swap with split below",
         transpose_view, "Transpose splits",
+        grow_view_width, "Increase width of split",
+        shrink_view_width, "Decrease width of split",
+        grow_view_height, "Increase height of split",
+        shrink_view_height, "Decrease height of split",
+        equalize_views, "Make all splits the same size",
+        zoom_view, "Toggle maximizing the current split",
         rotate_view, "Goto next window",
         rotate_view_reverse, "Goto previous window",
         hsplit, "Horizontal bottom split",
@@ -560,6 +585,11 @@ pub fn doc(&self) -> &str {
         align_view_bottom, "Align view bottom",
         scroll_up, "Scroll view up",
         scroll_down, "Scroll view down",
+        fold, "Fold the range under the cursor",
+        unfold, "Unfold the range under the cursor",
+        toggle_fold, "Toggle the fold under the cursor",
+        fold_all, "Fold all ranges",
+        unfold_all, "Unfold all ranges",
         match_brackets, "Goto matching bracket",
         surround_add, "Surround add",
         surround_replace, "Surround replace",
@@ -585,6 +615,11 @@ pub fn doc(&self) -> &str {
         dap_launch, "Launch debug target",
         dap_restart, "Restart debugging session",
         dap_toggle_breakpoint, "Toggle breakpoint",
+        dap_toggle_function_breakpoint, "Toggle function breakpoint",
+        dap_toggle_data_breakpoint, "Toggle data breakpoint",
+        dap_toggle_watch, "Toggle watch expression",
+        dap_watches, "List watch expressions",
+        dap_console, "Open debug console",
         dap_continue, "Continue program execution",
         dap_pause, "Pause program execution",
         dap_step_in, "Step in",
@@ -652,21 +687,22 @@ fn from_str(s: &str) -> Result<Self, Self::Err> {
         if let Some(suffix) = s.strip_prefix(':') {
             let (name, args, _) = command_line::split(suffix);
             ensure!(!name.is_empty(), "Expected typable command name");
-            typed::TYPABLE_COMMAND_MAP
-                .get(name)
-                .map(|cmd| {
-                    let doc = if args.is_empty() {
-                        cmd.doc.to_string()
-                    } else {
-                        format!(":{} {:?}", cmd.name, args)
-                    };
-                    MappableCommand::Typable {
-                        name: cmd.name.to_owned(),
-                        doc,
-                        args: args.to_string(),
-                    }
-                })
-                .ok_or_else(|| anyhow!("No TypableCommand named '{}'", s))
+            // Commands of the `[commands]` table aren't known while the config is parsed,
+            // the config checks that they exist once it's loaded.
+            let (name, cmd_doc) = match typed::TYPABLE_COMMAND_MAP.get(name) {
+                Some(cmd) => (cmd.name, cmd.doc),
+                None => (name, ""),
+            };
+            let doc = if args.is_empty() {
+                cmd_doc.to_string()
+            } else {
+                format!(":{} {:?}", name, args)
+            };
+            Ok(MappableCommand::Typable {
+                name: name.to_owned(),
+                doc,
+                args: args.to_string(),
+            })
         } else if let Some(suffix) = s.strip_prefix('@') {
             helix_view::input::parse_macro(suffix).map(|keys| Self::Macro {
                 name: s.to_string(),
@@ -1949,11 +1985,10 @@ pub fn scroll(cx: &mut Context, offset: usize, direction: Direction, sync_cursor
     let doc_text = doc.text().slice(..);
     let viewport = view.inner_area(doc);
     let text_fmt = doc.text_format(viewport.width, None);
-    (view_offset.anchor, view_offset.vertical_offset) = char_idx_at_visual_offset(
+    (view_offset.anchor, view_offset.vertical_offset) = view_offset_at_visual_offset(
         doc_text,
         view_offset.anchor,
         view_offset.vertical_offset as isize + offset,
-        0,
         &text_fmt,
         // &annotations,
         &view.text_annotations(&*doc, None),
@@ -2561,6 +2596,16 @@ fn make_search_word_bounded(cx: &mut Context) {
     }
 }
 
+/// The searcher used to search the files of the working directory with `matcher`. Binary
+/// files are skipped.
+fn global_searcher(matcher: &RegexMatcher) -> Searcher {
+    SearcherBuilder::new()
+        .binary_detection(BinaryDetection::quit(b'\x00'))
+        .multi_line(true)
+        .line_terminator(matcher.line_terminator().unwrap_or_default())
+        .build()
+}
+
 fn global_search(cx: &mut Context) {
     #[derive(Debug)]
     struct FileResult<'a> {
@@ -2645,10 +2690,7 @@ struct GlobalSearchConfig {
 
         let injector = injector.clone();
         async move {
-            let searcher = SearcherBuilder::new()
-                .binary_detection(BinaryDetection::quit(b'\x00'))
-                .multi_line(true)
-                .build();
+            let searcher = global_searcher(&matcher);
             WalkBuilder::new(search_root)
                 .hidden(config.file_picker_config.hidden)
                 .parents(config.file_picker_config.parents)
@@ -2789,6 +2831,397 @@ struct GlobalSearchConfig {
     cx.push_layer(Box::new(overlaid(picker)));
 }
 
+/// A match of a global replace with the replacement for it.
+struct ReplaceMatch {
+    path: PathBuf,
+    /// Path relative to the working directory for display.
+    relative_path: PathBuf,
+    /// 0 indexed line of the start of the match
+    line: usize,
+    /// Byte range of the match in the contents searched.
+    range: ops::Range<usize>,
+    matched: String,
+    replacement: String,
+    /// The text around the match on its first line, used to preview the change.
+    prefix: String,
+    suffix: String,
+    selected: atomic::AtomicBool,
+}
+
+impl ReplaceMatch {
+    fn is_selected(&self) -> bool {
+        self.selected.load(atomic::Ordering::Relaxed)
+    }
+}
+
+struct GlobalReplaceConfig {
+    style: PathStyleConfig,
+    removed_style: Style,
+    added_style: Style,
+}
+
+fn global_replace(cx: &mut Context) {
+    let reg = cx.register.unwrap_or('/');
+    ui::prompt(
+        cx,
+        "global-replace:".into(),
+        Some(reg),
+        ui::completers::none,
+        move |cx, input, event| {
+            if event != PromptEvent::Validate {
+                return;
+            }
+            // An empty input searches for the last query, e.g. of `global_search`.
+            let Some(pattern) = (if input.is_empty() {
+                cx.editor
+                    .registers
+                    .first(reg, cx.editor)
+                    .map(Cow::into_owned)
+            } else {
+                Some(input.to_string())
+            }) else {
+                cx.editor.set_error("no search pattern");
+                return;
+            };
+            let smart_case = cx.editor.config().search.smart_case;
+            let case_insensitive = smart_case && !pattern.chars().any(char::is_uppercase);
+            let regex = match global_replace_regex(&pattern, case_insensitive) {
+                Ok(regex) => regex,
+                Err(err) => {
+                    cx.editor.set_error(format!("Invalid regex: {err}"));
+                    return;
+                }
+            };
+            // Finds the files containing matches, like in global search.
+            let matcher = match RegexMatcherBuilder::new()
+                .case_insensitive(case_insensitive)
+                .multi_line(true)
+                .crlf(true)
+                .build(&pattern)
+            {
+                Ok(matcher) => matcher,
+                Err(err) => {
+                    cx.editor.set_error(format!("Invalid regex: {err}"));
+                    return;
+                }
+            };
+
+            let callback = async move {
+                let call = move |_editor: &mut Editor, compositor: &mut Compositor| {
+                    let prompt = Prompt::new(
+                        format!("replace '{pattern}' with:").into(),
+                        None,
+                        ui::completers::none,
+                        move |cx, input, event| {
+                            if event == PromptEvent::Validate {
+                                global_replace_search(
+                                    cx,
+                                    regex.clone(),
+                                    matcher.clone(),
+                                    input.to_string(),
+                                );
+                            }
+                        },
+                    );
+                    compositor.push(Box::new(prompt));
+                };
+                Ok(Callback::EditorCompositor(Box::new(call)))
+            };
+            cx.jobs.callback(callback);
+        },
+    );
+}
+
+/// Builds the regex of a global replace. `^` and `$` match at the start and end of lines
+/// ending with `\r\n` too.
+fn global_replace_regex(pattern: &str, case_insensitive: bool) -> Result<Regex, regex::Error> {
+    regex::RegexBuilder::new(pattern)
+        .case_insensitive(case_insensitive)
+        .multi_line(true)
+        .crlf(true)
+        .build()
+}
+
+/// Searches the working directory for `regex` in the background and opens a picker
+/// previewing the replacements.
+fn global_replace_search(
+    cx: &mut compositor::Context,
+    regex: Regex,
+    matcher: RegexMatcher,
+    replacement: String,
+) {
+    let search_root = helix_stdx::env::current_working_dir();
+    if !search_root.exists() {
+        cx.editor
+            .set_error("Current working directory does not exist");
+        return;
+    }
+    let file_picker_config = cx.editor.config().file_picker.clone();
+    // Search open documents instead of the files so unsaved edits are replaced too.
+    let documents: Vec<_> = cx
+        .editor
+        .documents()
+        .filter_map(|doc| Some((doc.path()?.to_owned(), doc.text().to_string())))
+        .collect();
+
+    let callback = async move {
+        let matches = tokio::task::spawn_blocking(move || {
+            let absolute_root = search_root
+                .canonicalize()
+                .unwrap_or_else(|_| search_root.clone());
+            let dedup_symlinks = file_picker_config.deduplicate_links;
+            let mut matches = Vec::new();
+            let walker = WalkBuilder::new(&search_root)
+                .hidden(file_picker_config.hidden)
+                .parents(file_picker_config.parents)
+                .ignore(file_picker_config.ignore)
+                .follow_links(file_picker_config.follow_symlinks)
+                .git_ignore(file_picker_config.git_ignore)
+                .git_global(file_picker_config.git_global)
+                .git_exclude(file_picker_config.git_exclude)
+                .max_depth(file_picker_config.max_depth)
+                .filter_entry(move |entry| {
+                    filter_picker_entry(entry, &absolute_root, dedup_symlinks)
+                })
+                .add_custom_ignore_filename(helix_loader::config_dir().join("ignore"))
+                .add_custom_ignore_filename(".helix/ignore")
+                .sort_by_file_name(|a, b| a.cmp(b))
+                .build();
+            let mut searcher = global_searcher(&matcher);
+            for entry in walker.flatten() {
+                let path = entry.path();
+                if !path.is_file() {
+                    continue;
+                }
+                let contents = match documents.iter().find(|(doc_path, _)| doc_path == path) {
+                    Some((_, text)) => Cow::Borrowed(text.as_str()),
+                    None => {
+                        // Only files with matches are read entirely.
+                        let mut found = false;
+                        let sink = sinks::UTF8(|_, _| {
+                            found = true;
+                            Ok(false)
+                        });
+                        if let Err(err) = searcher.search_path(&matcher, path, sink) {
+                            log::error!("Global replace error: {}, {}", path.display(), err);
+                        }
+                        if !found {
+                            continue;
+                        }
+                        match std::fs::read(path) {
+                            // Binary files are skipped like in global search.
+                            Ok(bytes) if !bytes.contains(&0) => match String::from_utf8(bytes) {
+                                Ok(text) => Cow::Owned(text),
+                                Err(_) => continue,
+                            },
+                            _ => continue,
+                        }
+                    }
+                };
+                collect_replace_matches(&regex, &replacement, path, &contents, &mut matches);
+            }
+            matches
+        })
+        .await?;
+
+        let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+            if matches.is_empty() {
+                editor.set_status("No matches found");
+                return;
+            }
+            compositor.push(Box::new(overlaid(global_replace_picker(editor, matches))));
+        };
+        Ok(Callback::EditorCompositor(Box::new(call)))
+    };
+    cx.jobs.callback(callback);
+}
+
+fn collect_replace_matches(
+    regex: &Regex,
+    replacement: &str,
+    path: &Path,
+    contents: &str,
+    matches: &mut Vec<ReplaceMatch>,
+) {
+    let relative_path = helix_stdx::path::get_relative_path(path).into_owned();
+    let mut line = 0;
+    let mut searched = 0;
+    for captures in regex.captures_iter(contents) {
+        let range = captures.get(0).unwrap().range();
+        // Replacing empty matches like `^` is rarely intended.
+        if range.is_empty() {
+            continue;
+        }
+        line += contents[searched..range.start].matches('\n').count();
+        searched = range.start;
+
+        let line_start = contents[..range.start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = contents[range.start..]
+            .find('\n')
+            .map_or(contents.len(), |i| range.start + i);
+        let matched = &contents[range.clone()];
+        let suffix = if range.end <= line_end {
+            contents[range.end..line_end].trim_end()
+        } else {
+            ""
+        };
+        let mut expanded = String::new();
+        captures.expand(replacement, &mut expanded);
+        matches.push(ReplaceMatch {
+            path: path.to_path_buf(),
+            relative_path: relative_path.clone(),
+            line,
+            range: range.clone(),
+            matched: matched.to_string(),
+            replacement: expanded,
+            prefix: contents[line_start..range.start].trim_start().to_string(),
+            suffix: suffix.to_string(),
+            selected: true.into(),
+        });
+    }
+}
+
+fn global_replace_picker(
+    editor: &Editor,
+    matches: Vec<ReplaceMatch>,
+) -> Picker<Arc<ReplaceMatch>, GlobalReplaceConfig> {
+    let matches: Arc<[Arc<ReplaceMatch>]> = matches.into_iter().map(Arc::new).collect();
+    let config = GlobalReplaceConfig {
+        style: PathStyleConfig::new(&editor.theme),
+        removed_style: editor.theme.get("diff.minus"),
+        added_style: editor.theme.get("diff.plus"),
+    };
+    let columns = [
+        PickerColumn::new("", |item: &Arc<ReplaceMatch>, _| {
+            if item.is_selected() { "[x]" } else { "[ ]" }.into()
+        })
+        .without_filtering(),
+        PickerColumn::new(
+            "path",
+            |item: &Arc<ReplaceMatch>, config: &GlobalReplaceConfig| {
+                config
+                    .style
+                    .stylize(Some(&item.relative_path), Some(item.line))
+            },
+        ),
+        PickerColumn::new(
+            "change",
+            |item: &Arc<ReplaceMatch>, config: &GlobalReplaceConfig| {
+                let matched = item.matched.lines().next().unwrap_or_default();
+                let replacement = item.replacement.lines().next().unwrap_or_default();
+                Spans::from(vec![
+                    Span::raw(item.prefix.as_str()),
+                    Span::styled(matched, config.removed_style),
+                    Span::styled(replacement, config.added_style),
+                    Span::raw(item.suffix.as_str()),
+                ])
+                .into()
+            },
+        ),
+    ];
+
+    let all_matches = matches.clone();
+    Picker::new(
+        columns,
+        1, // path
+        matches.iter().cloned(),
+        config,
+        move |cx, _item, _action| apply_global_replace(cx, &all_matches),
+    )
+    .with_preview(|_editor, item| {
+        let end_line = item.line + item.matched.matches('\n').count();
+        Some((item.path.as_path().into(), Some((item.line, end_line))))
+    })
+    .with_toggle(|item| {
+        item.selected.fetch_xor(true, atomic::Ordering::Relaxed);
+    })
+}
+
+/// Applies the selected matches with one transaction per document. Files which weren't
+/// open are saved and closed again, open documents are left modified so the changes can be
+/// reviewed.
+fn apply_global_replace(cx: &mut compositor::Context, matches: &[Arc<ReplaceMatch>]) {
+    let editor = &mut *cx.editor;
+    let mut files: Vec<(&Path, Vec<&ReplaceMatch>)> = Vec::new();
+    for item in matches.iter().filter(|item| item.is_selected()) {
+        match files.last_mut() {
+            Some((path, items)) if *path == item.path => items.push(item),
+            _ => files.push((&item.path, vec![item])),
+        }
+    }
+
+    let mut replaced = 0;
+    let mut errors = Vec::new();
+    let mut opened = Vec::new();
+    for (path, items) in &files {
+        let was_open = editor.document_by_path(path).is_some();
+        let doc_id = match editor.open(path, Action::Load) {
+            Ok(doc_id) => doc_id,
+            Err(err) => {
+                errors.push(format!("failed to open {}: {err}", path.display()));
+                continue;
+            }
+        };
+        if !was_open {
+            opened.push(doc_id);
+        }
+        let doc = doc!(editor, &doc_id);
+        let text = doc.text();
+        let unchanged = items.iter().all(|item| {
+            item.range.end <= text.len_bytes()
+                && text.byte_slice(item.range.clone()) == item.matched.as_str()
+        });
+        if !unchanged {
+            errors.push(format!("{} changed since the search", path.display()));
+            continue;
+        }
+        let transaction = Transaction::change(
+            text,
+            items.iter().map(|item| {
+                (
+                    text.byte_to_char(item.range.start),
+                    text.byte_to_char(item.range.end),
+                    Some(Tendril::from(item.replacement.as_str())),
+                )
+            }),
+        );
+
+        let view_id = editor.get_synced_view_id(doc_id);
+        let doc = doc_mut!(editor, &doc_id);
+        let view = view_mut!(editor, view_id);
+        doc.apply(&transaction, view.id);
+        doc.append_changes_to_history(view);
+        replaced += items.len();
+
+        if !was_open {
+            if let Err(err) = editor.save(doc_id, None::<PathBuf>, false) {
+                errors.push(format!("failed to save {}: {err}", path.display()));
+            }
+        }
+    }
+
+    // Failed saves keep their documents modified and open.
+    if let Err(err) = cx.block_try_flush_writes() {
+        errors.push(err.to_string());
+    }
+    for doc_id in opened {
+        let _ = cx.editor.close_document(doc_id, false);
+    }
+
+    let editor = &mut *cx.editor;
+    if errors.is_empty() {
+        editor.set_status(format!(
+            "Replaced {replaced} matches in {} files",
+            files.len()
+        ));
+    } else {
+        editor.set_error(format!(
+            "Replaced {replaced} matches, {}",
+            errors.join(", ")
+        ));
+    }
+}
+
 enum Extend {
     Above,
     Below,
@@ -3479,6 +3912,65 @@ struct JumpMeta<'a> {
     cx.push_layer(Box::new(overlaid(picker)));
 }
 
+fn stage_diff_change(cx: &mut Context) {
+    if let Err(err) = typed::stage_diff_change_impl(cx.editor) {
+        cx.editor.set_error(err.to_string());
+    }
+}
+
+fn unstage_diff_change(cx: &mut Context) {
+    if let Err(err) = typed::unstage_diff_change_impl(cx.editor) {
+        cx.editor.set_error(err.to_string());
+    }
+}
+
+fn show_diff_change_base(cx: &mut Context) {
+    if let Err(err) = typed::show_diff_change_base_impl(cx.editor, cx.jobs) {
+        cx.editor.set_error(err.to_string());
+    }
+}
+
+fn blame_line(cx: &mut Context) {
+    let doc = doc!(cx.editor);
+    if doc.path().is_none() {
+        cx.editor.set_error("Buffer has no path");
+        return;
+    }
+
+    let doc_id = doc.id();
+    crate::handlers::blame::request_blame(cx.editor, doc_id, move |editor, compositor| {
+        let (view, doc) = current_ref!(editor);
+        if doc.id() != doc_id {
+            return;
+        }
+        let text = doc.text().slice(..);
+        let line = text.char_to_line(doc.selection(view.id).primary().cursor(text));
+        let commit = match doc.line_blame(line) {
+            Some(LineBlame::Committed(commit)) => commit,
+            Some(LineBlame::Uncommitted) => {
+                editor.set_status("Not committed yet");
+                return;
+            }
+            None => {
+                editor.set_error("No blame information for the current line");
+                return;
+            }
+        };
+
+        let contents = format!(
+            "**{}**\n\n`{}` {} <{}> {}",
+            commit.summary,
+            commit.short_id(),
+            commit.author,
+            commit.email,
+            commit.date
+        );
+        let contents = ui::Markdown::new(contents, editor.syn_loader.clone());
+        let popup = Popup::new("blame", contents).auto_close(true);
+        compositor.replace_or_push("blame", popup);
+    });
+}
+
 fn changed_file_picker(cx: &mut Context) {
     pub struct FileChangeData {
         cwd: PathBuf,
@@ -3579,19 +4071,34 @@ pub fn command_palette(cx: &mut Context) {
 
     cx.callback.push(Box::new(
         move |compositor: &mut Compositor, cx: &mut compositor::Context| {
-            let keymap = compositor.find::<ui::EditorView>().unwrap().keymaps.map()
-                [&cx.editor.mode]
-                .reverse_map();
-
-            let commands = MappableCommand::STATIC_COMMAND_LIST.iter().cloned().chain(
-                typed::TYPABLE_COMMAND_LIST
-                    .iter()
-                    .map(|cmd| MappableCommand::Typable {
-                        name: cmd.name.to_owned(),
-                        args: String::new(),
-                        doc: cmd.doc.to_owned(),
-                    }),
-            );
+            let keymap = compositor
+                .find::<ui::EditorView>()
+                .unwrap()
+                .keymaps
+                .reverse_map(cx.editor.mode, doc!(cx.editor).language_name());
+
+            let custom_commands: Vec<_> = typed::custom_commands()
+                .iter()
+                .flat_map(|commands| commands.iter())
+                .map(|(name, command)| MappableCommand::Typable {
+                    name: name.clone(),
+                    args: String::new(),
+                    doc: command.doc.clone(),
+                })
+                .collect();
+            let commands = MappableCommand::STATIC_COMMAND_LIST
+                .iter()
+                .cloned()
+                .chain(
+                    typed::TYPABLE_COMMAND_LIST
+                        .iter()
+                        .map(|cmd| MappableCommand::Typable {
+                            name: cmd.name.to_owned(),
+                            args: String::new(),
+                            doc: cmd.doc.to_owned(),
+                        }),
+                )
+                .chain(custom_commands);
 
             let columns = [
                 ui::PickerColumn::new("name", |item, _| match item {
@@ -3655,6 +4162,64 @@ pub fn command_palette(cx: &mut Context) {
     ));
 }
 
+/// Executes `commands` in the focused view like a key binding would and runs the callbacks
+/// they push, and the commands of custom commands, before the next command.
+/// `%{arg}` and `%{args}` in typable commands expand to `arguments` if given.
+pub fn execute_commands(
+    cx: &mut compositor::Context,
+    compositor: &mut Compositor,
+    commands: &[MappableCommand],
+    arguments: Option<&[String]>,
+) {
+    for command in commands {
+        let mut ctx = Context {
+            register: None,
+            count: None,
+            editor: cx.editor,
+            callback: Vec::new(),
+            on_next_key_callback: None,
+            jobs: cx.jobs,
+        };
+        let focus = ctx.editor.tree.focus;
+
+        let mut call = None;
+        match command {
+            MappableCommand::Typable { name, args, .. } => {
+                let mut cx = compositor::Context {
+                    editor: &mut *ctx.editor,
+                    jobs: &mut *ctx.jobs,
+                    scroll: None,
+                };
+                match typed::execute_typable(&mut cx, name, args, arguments) {
+                    Ok(custom) => call = custom,
+                    Err(err) => cx.editor.set_error(err.to_string()),
+                }
+            }
+            _ => command.execute(&mut ctx),
+        }
+
+        let callbacks = std::mem::take(&mut ctx.callback);
+        if ctx.editor.tree.contains(focus) {
+            let config = ctx.editor.config();
+            let mode = ctx.editor.mode();
+            let view = view_mut!(ctx.editor, focus);
+            let doc = doc_mut!(ctx.editor, &view.doc);
+
+            view.ensure_cursor_in_view(doc, config.scrolloff);
+
+            if mode != Mode::Insert {
+                doc.append_changes_to_history(view);
+            }
+        }
+        for callback in callbacks {
+            callback(compositor, cx);
+        }
+        if let Some(call) = call {
+            call.execute(cx, compositor);
+        }
+    }
+}
+
 fn last_picker(cx: &mut Context) {
     // TODO: last picker does not seem to work well with buffer_picker
     cx.callback.push(Box::new(|compositor, cx| {
@@ -4289,6 +4854,105 @@ fn hunk_range(hunk: Hunk, text: RopeSlice) -> Range {
     Range::new(anchor, head)
 }
 
+fn goto_next_conflict(cx: &mut Context) {
+    goto_next_conflict_impl(cx, Direction::Forward)
+}
+
+fn goto_prev_conflict(cx: &mut Context) {
+    goto_next_conflict_impl(cx, Direction::Backward)
+}
+
+fn goto_next_conflict_impl(cx: &mut Context, direction: Direction) {
+    let count = cx.count() - 1;
+    let motion = move |editor: &mut Editor| {
+        let (view, doc) = current!(editor);
+        if doc.conflicts().is_empty() {
+            editor.set_status("No merge conflicts in current buffer");
+            return;
+        }
+
+        let doc_text = doc.text().slice(..);
+        let conflicts = doc.conflicts();
+        let selection = doc.selection(view.id).clone().transform(|range| {
+            let cursor_line = range.cursor_line(doc_text);
+            let idx = match direction {
+                Direction::Forward => {
+                    let idx = conflicts.partition_point(|conflict| conflict.start <= cursor_line);
+                    (idx < conflicts.len()).then(|| (idx + count).min(conflicts.len() - 1))
+                }
+                Direction::Backward => conflicts
+                    .partition_point(|conflict| conflict.end < cursor_line)
+                    .checked_sub(1)
+                    .map(|idx| idx.saturating_sub(count)),
+            };
+            let Some(idx) = idx else {
+                return range;
+            };
+            let lines = conflicts[idx].lines();
+            let new_range = Range::new(
+                doc_text.line_to_char(lines.start),
+                doc_text.line_to_char(lines.end),
+            );
+            if editor.mode == Mode::Select {
+                let head = if new_range.head < range.anchor {
+                    new_range.anchor
+                } else {
+                    new_range.head
+                };
+
+                Range::new(range.anchor, head)
+            } else {
+                new_range.with_direction(direction)
+            }
+        });
+
+        push_jump(view, doc);
+        doc.set_selection(view.id, selection)
+    };
+    cx.editor.apply_motion(motion);
+}
+
+fn accept_conflict_ours(cx: &mut Context) {
+    resolve_conflicts(cx, Resolution::Ours)
+}
+
+fn accept_conflict_theirs(cx: &mut Context) {
+    resolve_conflicts(cx, Resolution::Theirs)
+}
+
+fn accept_conflict_both(cx: &mut Context) {
+    resolve_conflicts(cx, Resolution::Both)
+}
+
+fn accept_conflict_base(cx: &mut Context) {
+    resolve_conflicts(cx, Resolution::Base)
+}
+
+/// Resolves the merge conflicts under the cursors in a single transaction.
+fn resolve_conflicts(cx: &mut Context, resolution: Resolution) {
+    let (view, doc) = current!(cx.editor);
+    let text = doc.text().slice(..);
+    let mut conflicts: Vec<_> = doc
+        .selection(view.id)
+        .iter()
+        .filter_map(|range| doc.conflict_at_line(range.cursor_line(text)))
+        .copied()
+        .collect();
+    conflicts.dedup();
+    if conflicts.is_empty() {
+        cx.editor.set_error("No merge conflict under any cursor");
+        return;
+    }
+
+    let Some(transaction) = merge_conflict::resolve(doc.text(), &conflicts, resolution) else {
+        cx.editor
+            .set_error("Merge conflict does not include the common ancestor");
+        return;
+    };
+    doc.apply(&transaction, view.id);
+    exit_select_mode(cx);
+}
+
 pub mod insert {
     use crate::{events::PostInsertChar, key};
 
@@ -4311,6 +4975,11 @@ fn exclude_cursor(text: RopeSlice, range: Range, cursor: Range) -> Range {
     use helix_view::editor::SmartTabConfig;
 
     pub fn insert_char(cx: &mut Context, c: char) {
+        insert_char_with_inverse(cx, c);
+    }
+
+    /// Inserts `c` like [insert_char] and returns the transaction that removes it again.
+    pub fn insert_char_with_inverse(cx: &mut Context, c: char) -> Transaction {
         let (view, doc) = current_ref!(cx.editor);
         let text = doc.text();
         let selection = doc.selection(view.id);
@@ -4334,11 +5003,13 @@ pub fn insert_char(cx: &mut Context, c: char) {
                 })
                 .unwrap_or_else(|| insert_char(*range, c))
         });
+        let inverse = transaction.invert(text);
 
         let doc = doc_mut!(cx.editor, &doc.id());
         doc.apply(&transaction, view.id);
 
         helix_event::dispatch(PostInsertChar { c, cx });
+        inverse
     }
 
     pub fn smart_tab(cx: &mut Context) {
@@ -5901,6 +6572,37 @@ fn transpose_view(cx: &mut Context) {
     cx.editor.transpose_view()
 }
 
+fn resize_view(cx: &mut Context, layout: tree::Layout, delta: i32) {
+    let count = cx.count() as i32;
+    let view_id = cx.editor.tree.focus;
+    cx.editor.tree.resize_view(view_id, layout, count * delta);
+}
+
+fn grow_view_width(cx: &mut Context) {
+    resize_view(cx, tree::Layout::Vertical, 1)
+}
+
+fn shrink_view_width(cx: &mut Context) {
+    resize_view(cx, tree::Layout::Vertical, -1)
+}
+
+fn grow_view_height(cx: &mut Context) {
+    resize_view(cx, tree::Layout::Horizontal, 1)
+}
+
+fn shrink_view_height(cx: &mut Context) {
+    resize_view(cx, tree::Layout::Horizontal, -1)
+}
+
+fn equalize_views(cx: &mut Context) {
+    cx.editor.tree.equalize()
+}
+
+fn zoom_view(cx: &mut Context) {
+    let zoomed = cx.editor.tree.zoomed().is_some();
+    cx.editor.tree.set_zoom(!zoomed)
+}
+
 /// Open a new split in the given direction specified by the action.
 ///
 /// Maintain the current view (both the cursor's position and view in document).
@@ -5942,6 +6644,7 @@ fn wclose(cx: &mut Context) {
             cx.editor.set_error(err.to_string());
             return;
         }
+        typed::save_auto_session(cx.editor);
     }
     let view_id = view!(cx.editor).id;
     // close current split
@@ -6088,6 +6791,166 @@ fn scroll_down(cx: &mut Context) {
     scroll(cx, cx.count(), Direction::Forward, false);
 }
 
+/// Computes the foldable line ranges of the current document and passes them to `f`.
+/// Folding ranges from a language server are preferred over the `folds.scm` query.
+fn with_fold_ranges<F>(cx: &mut Context, f: F)
+where
+    F: FnOnce(&mut Editor, ViewId, DocumentId, Vec<ops::RangeInclusive<usize>>) + Send + 'static,
+{
+    let (view, doc) = current_ref!(cx.editor);
+    let (view_id, doc_id) = (view.id, doc.id());
+
+    if let Some(language_server) = doc
+        .language_servers_with_feature(LanguageServerFeature::FoldingRange)
+        .next()
+    {
+        let future = language_server
+            .text_document_folding_range(doc.identifier())
+            .unwrap();
+        cx.jobs.callback(async move {
+            let folding_ranges = future.await?.unwrap_or_default();
+            let call = move |editor: &mut Editor| {
+                let mut ranges: Vec<_> = folding_ranges
+                    .into_iter()
+                    .filter(|range| range.start_line < range.end_line)
+                    .map(|range| range.start_line as usize..=range.end_line as usize)
+                    .collect();
+                ranges.sort_by_key(|range| (*range.start(), std::cmp::Reverse(*range.end())));
+                ranges.dedup();
+                f(editor, view_id, doc_id, ranges);
+            };
+            Ok(Callback::Editor(Box::new(call)))
+        });
+        return;
+    }
+
+    let ranges = match doc.syntax() {
+        Some(syntax) => syntax.fold_ranges(doc.text().slice(..), &cx.editor.syn_loader.load(), ..),
+        None => Vec::new(),
+    };
+    f(cx.editor, view_id, doc_id, ranges);
+}
+
+/// Moves all cursors that are hidden by a fold to the start of that fold.
+fn reveal_folded_cursors(doc: &mut Document, view_id: ViewId) {
+    let folds = doc.folds(view_id);
+    let text = doc.text().slice(..);
+    let selection = doc.selection(view_id).clone().transform(|range| {
+        let cursor = range.cursor(text);
+        match folds.iter().find(|fold| fold.conceals(cursor)) {
+            Some(fold) => Range::point(fold.start),
+            None => range,
+        }
+    });
+    doc.set_selection(view_id, selection);
+}
+
+fn fold_selections(
+    editor: &mut Editor,
+    view_id: ViewId,
+    doc_id: DocumentId,
+    ranges: &[ops::RangeInclusive<usize>],
+) -> usize {
+    let Some(doc) = editor.documents.get_mut(&doc_id) else {
+        return 0;
+    };
+    if !doc.selections().contains_key(&view_id) {
+        return 0;
+    }
+    let text = doc.text().slice(..);
+    let lines: Vec<_> = doc
+        .selection(view_id)
+        .iter()
+        .map(|range| range.line_range(text))
+        .collect();
+
+    let mut folded = 0;
+    for (start_line, end_line) in lines {
+        if start_line < end_line {
+            // fold the selected lines
+            if doc.fold_lines(view_id, start_line, end_line) {
+                folded += 1;
+            }
+            continue;
+        }
+        // fold the innermost range around the cursor that is not folded yet
+        let mut candidates: Vec<_> = ranges
+            .iter()
+            .filter(|range| range.contains(&start_line))
+            .collect();
+        candidates.sort_by_key(|range| range.end() - range.start());
+        if candidates
+            .into_iter()
+            .any(|range| doc.fold_lines(view_id, *range.start(), *range.end()))
+        {
+            folded += 1;
+        }
+    }
+
+    reveal_folded_cursors(doc, view_id);
+    folded
+}
+
+fn unfold_selections(doc: &mut Document, view_id: ViewId) -> usize {
+    let text = doc.text().slice(..);
+    let lines: Vec<_> = doc
+        .selection(view_id)
+        .iter()
+        .map(|range| range.line_range(text))
+        .collect();
+    lines
+        .into_iter()
+        .map(|(start_line, end_line)| doc.unfold_lines(view_id, start_line, end_line))
+        .sum()
+}
+
+fn fold(cx: &mut Context) {
+    with_fold_ranges(cx, |editor, view_id, doc_id, ranges| {
+        if fold_selections(editor, view_id, doc_id, &ranges) == 0 {
+            editor.set_error("No foldable range found");
+        }
+    });
+}
+
+fn unfold(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    if unfold_selections(doc, view.id) == 0 {
+        cx.editor.set_error("No fold found");
+    }
+}
+
+fn toggle_fold(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    if unfold_selections(doc, view.id) == 0 {
+        fold(cx);
+    }
+}
+
+fn fold_all(cx: &mut Context) {
+    with_fold_ranges(cx, |editor, view_id, doc_id, ranges| {
+        let Some(doc) = editor.documents.get_mut(&doc_id) else {
+            return;
+        };
+        if !doc.selections().contains_key(&view_id) {
+            return;
+        }
+        // the ranges are sorted so that outer ranges are folded first
+        let folded = ranges
+            .iter()
+            .filter(|range| doc.fold_lines(view_id, *range.start(), *range.end()))
+            .count();
+        reveal_folded_cursors(doc, view_id);
+        if folded == 0 {
+            editor.set_error("No foldable range found");
+        }
+    });
+}
+
+fn unfold_all(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    doc.clear_folds(view.id);
+}
+
 fn goto_ts_object_impl(cx: &mut Context, object: &'static str, direction: Direction) {
     let count = cx.count();
     let motion = move |editor: &mut Editor| {
@@ -6529,6 +7392,9 @@ async fn shell_impl_async(
         .arg(cmd)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
+    if let Some(socket) = helix_loader::listening_remote_socket() {
+        process.env("HELIX_REMOTE_SOCKET", socket);
+    }
 
     if input.is_some() || cfg!(windows) {
         process.stdin(Stdio::piped());
@@ -7155,3 +8021,98 @@ fn lsp_or_syntax_workspace_symbol_picker(cx: &mut Context) {
         syntax_workspace_symbol_picker(cx);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn replace_matches(pattern: &str, replacement: &str, contents: &str) -> Vec<ReplaceMatch> {
+        let regex = global_replace_regex(pattern, false).unwrap();
+        let mut matches = Vec::new();
+        collect_replace_matches(
+            &regex,
+            replacement,
+            Path::new("/tmp/file.txt"),
+            contents,
+            &mut matches,
+        );
+        matches
+    }
+
+    /// The line, matched text, replacement and preview of each match.
+    fn summary(matches: &[ReplaceMatch]) -> Vec<(usize, &str, &str, &str, &str)> {
+        matches
+            .iter()
+            .map(|item| {
+                (
+                    item.line,
+                    item.matched.as_str(),
+                    item.replacement.as_str(),
+                    item.prefix.as_str(),
+                    item.suffix.as_str(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn replace_matches_expand_captures() {
+        let matches = replace_matches(r"(?<name>\w+)=(\d+)", "${name}:$2", "a=1 b=2\nc=3\n");
+        assert_eq!(
+            summary(&matches),
+            [
+                (0, "a=1", "a:1", "", " b=2"),
+                (0, "b=2", "b:2", "a=1 ", ""),
+                (1, "c=3", "c:3", "", ""),
+            ]
+        );
+        assert_eq!(matches[1].range, 4..7);
+        assert_eq!(matches[2].range, 8..11);
+    }
+
+    #[test]
+    fn replace_matches_multiple_lines() {
+        let contents = "x\nfoo\nbar baz\n  foo\nbar\n";
+        let matches = replace_matches(r"foo\nbar", "qux", contents);
+        // The suffix is only shown for matches within their first line.
+        assert_eq!(
+            summary(&matches),
+            [
+                (1, "foo\nbar", "qux", "", ""),
+                (3, "foo\nbar", "qux", "", ""),
+            ]
+        );
+        assert_eq!(&contents[matches[1].range.clone()], "foo\nbar");
+    }
+
+    #[test]
+    fn replace_matches_skip_empty_matches() {
+        let matches = replace_matches("x*", "y", "axb\nxx\n");
+        assert_eq!(
+            summary(&matches),
+            [(0, "x", "y", "a", "b"), (1, "xx", "y", "", "")]
+        );
+        assert!(replace_matches("^", "y", "a\nb\n").is_empty());
+    }
+
+    #[test]
+    fn replace_matches_crlf() {
+        let contents = "a foo\r\nb foo bar\r\nfoo\r\n";
+        let matches = replace_matches("foo", "qux", contents);
+        assert_eq!(
+            summary(&matches),
+            [
+                (0, "foo", "qux", "a ", ""),
+                (1, "foo", "qux", "b ", " bar"),
+                (2, "foo", "qux", "", ""),
+            ]
+        );
+        // `$` matches before `\r\n`.
+        let matches = replace_matches("foo$", "qux", contents);
+        assert_eq!(
+            summary(&matches),
+            [(0, "foo", "qux", "a ", ""), (2, "foo", "qux", "", "")]
+        );
+        assert_eq!(&contents[matches[1].range.clone()], "foo");
+    }
+}
@@ -6,9 +6,11 @@
 };
 use dap::{StackFrame, Thread, ThreadStates};
 use helix_core::syntax::config::{DebugConfigCompletion, DebugTemplate};
+use helix_core::Selection;
 use helix_dap::{self as dap, requests::TerminateArguments};
 use helix_lsp::block_on;
-use helix_view::editor::Breakpoint;
+use helix_view::editor::{Action, Breakpoint, DataBreakpoint, FunctionBreakpoint, WatchExpression};
+use helix_view::{align_view, Align};
 
 use serde_json::{to_value, Value};
 use tui::text::Spans;
@@ -19,7 +21,10 @@
 
 use anyhow::{anyhow, bail};
 
-use helix_view::handlers::dap::{breakpoints_changed, jump_to_stack_frame, select_thread_id};
+use helix_view::handlers::dap::{
+    breakpoints_changed, data_breakpoints_changed, evaluate_watch_expressions,
+    function_breakpoints_changed, jump_to_stack_frame, select_thread_id,
+};
 
 fn thread_picker(
     cx: &mut Context,
@@ -427,6 +432,197 @@ pub fn dap_toggle_breakpoint_impl(cx: &mut Context, path: PathBuf, line: usize)
     }
 }
 
+/// Text of the primary selection to prefill prompts with, if more than a single char is selected.
+fn primary_selection_text(editor: &Editor) -> Option<String> {
+    let (view, doc) = current_ref!(editor);
+    let range = doc.selection(view.id).primary();
+    (range.len() > 1).then(|| range.fragment(doc.text().slice(..)).into())
+}
+
+fn dap_prompt(
+    cx: &mut Context,
+    prompt: &'static str,
+    callback_fn: impl FnMut(&mut compositor::Context, &str, PromptEvent) + 'static,
+) {
+    let mut prompt = Prompt::new(prompt.into(), None, ui::completers::none, callback_fn);
+    if let Some(text) = primary_selection_text(cx.editor) {
+        prompt.insert_str(&text, cx.editor);
+    }
+    cx.push_layer(Box::new(prompt));
+}
+
+pub fn dap_toggle_function_breakpoint(cx: &mut Context) {
+    dap_prompt(cx, "function:", |cx, input, event| {
+        if event != PromptEvent::Validate || input.is_empty() {
+            return;
+        }
+
+        let breakpoints = &mut cx.editor.function_breakpoints;
+        if let Some(pos) = breakpoints.iter().position(|b| b.name == input) {
+            breakpoints.remove(pos);
+        } else {
+            breakpoints.push(FunctionBreakpoint {
+                name: input.to_owned(),
+                ..Default::default()
+            });
+        }
+
+        let debugger = debugger!(cx.editor);
+        if let Err(e) = function_breakpoints_changed(debugger, &mut cx.editor.function_breakpoints)
+        {
+            cx.editor.set_error(e.to_string());
+        }
+    })
+}
+
+pub fn dap_toggle_data_breakpoint(cx: &mut Context) {
+    dap_prompt(cx, "data:", |cx, input, event| {
+        if event != PromptEvent::Validate || input.is_empty() {
+            return;
+        }
+
+        if let Some(pos) = cx
+            .editor
+            .data_breakpoints
+            .iter()
+            .position(|b| b.expression == input)
+        {
+            cx.editor.data_breakpoints.remove(pos);
+            let debugger = debugger!(cx.editor);
+            if let Err(e) = data_breakpoints_changed(debugger, &mut cx.editor.data_breakpoints) {
+                cx.editor.set_error(e.to_string());
+            }
+            return;
+        }
+
+        let debugger = debugger!(cx.editor);
+        if !debugger
+            .caps
+            .as_ref()
+            .and_then(|caps| caps.supports_data_breakpoints)
+            .unwrap_or_default()
+        {
+            cx.editor
+                .set_error("Debugger does not support data breakpoints");
+            return;
+        }
+
+        let frame_id = debugger.current_stack_frame().map(|frame| frame.id);
+        let request = debugger.data_breakpoint_info(input.to_owned(), frame_id);
+        let expression = input.to_owned();
+        dap_callback(
+            cx.jobs,
+            request,
+            move |editor, _compositor, info: dap::requests::DataBreakpointInfoResponse| {
+                let Some(data_id) = info.data_id else {
+                    editor.set_error(format!("Can't set data breakpoint: {}", info.description));
+                    return;
+                };
+                editor.data_breakpoints.push(DataBreakpoint {
+                    id: None,
+                    verified: false,
+                    message: None,
+                    expression,
+                    data_id,
+                    description: info.description,
+                    can_persist: info.can_persist.unwrap_or_default(),
+                });
+
+                let debugger = debugger!(editor);
+                if let Err(e) = data_breakpoints_changed(debugger, &mut editor.data_breakpoints) {
+                    editor.set_error(e.to_string());
+                }
+            },
+        );
+    })
+}
+
+pub fn dap_toggle_watch(cx: &mut Context) {
+    dap_prompt(cx, "watch:", |cx, input, event| {
+        if event != PromptEvent::Validate || input.is_empty() {
+            return;
+        }
+
+        let watch_expressions = &mut cx.editor.watch_expressions;
+        if let Some(pos) = watch_expressions
+            .iter()
+            .position(|watch| watch.expression == input)
+        {
+            watch_expressions.remove(pos);
+            return;
+        }
+        watch_expressions.push(WatchExpression {
+            expression: input.to_owned(),
+            value: None,
+        });
+
+        let debugger = debugger!(cx.editor);
+        if debugger.thread_id.is_some() {
+            let watch = cx.editor.watch_expressions.last_mut().unwrap();
+            block_on(evaluate_watch_expressions(
+                debugger,
+                std::slice::from_mut(watch),
+            ));
+        }
+    })
+}
+
+pub fn dap_watches(cx: &mut Context) {
+    if cx.editor.watch_expressions.is_empty() {
+        cx.editor.set_status("No watch expressions");
+        return;
+    }
+
+    let theme = &cx.editor.theme;
+    let text_style = theme.get("ui.text.focus");
+    let value_style = theme.get("ui.text");
+    let error_style = theme.get("error");
+
+    let lines: Vec<_> = cx
+        .editor
+        .watch_expressions
+        .iter()
+        .map(|watch| {
+            use tui::text::Span;
+            let value = match &watch.value {
+                Some(Ok(value)) => Span::styled(value.to_owned(), value_style),
+                Some(Err(err)) => Span::styled(err.to_owned(), error_style),
+                None => Span::styled("<not evaluated>", value_style),
+            };
+            Spans::from(vec![
+                Span::styled(watch.expression.to_owned(), text_style),
+                Span::raw(" = "),
+                value,
+            ])
+        })
+        .collect();
+
+    let contents = Text::from(tui::text::Text::from(lines));
+    let popup = Popup::new("dap-watches", contents);
+    cx.replace_or_push_layer("dap-watches", popup);
+}
+
+/// Shows the debug console in a split and moves the cursor to its input line.
+pub fn dap_console(cx: &mut Context) {
+    let doc_id = cx.editor.debug_console();
+    let view_id = cx
+        .editor
+        .tree
+        .views()
+        .find(|(view, _)| view.doc == doc_id)
+        .map(|(view, _)| view.id);
+    match view_id {
+        Some(view_id) => cx.editor.focus(view_id),
+        None => cx.editor.switch(doc_id, Action::HorizontalSplit),
+    }
+    cx.editor.flush_debug_console();
+
+    let (view, doc) = current!(cx.editor);
+    let end = doc.text().len_chars();
+    doc.set_selection(view.id, Selection::point(end));
+    align_view(doc, view, Align::Bottom);
+}
+
 pub fn dap_continue(cx: &mut Context) {
     let debugger = debugger!(cx.editor);
 
@@ -1,4 +1,4 @@
-use futures_util::{stream::FuturesUnordered, FutureExt};
+use futures_util::{future::BoxFuture, stream::FuturesUnordered, FutureExt};
 use helix_lsp::{
     block_on,
     lsp::{
@@ -19,7 +19,7 @@
 };
 use helix_stdx::path;
 use helix_view::{
-    document::{DocumentInlayHints, DocumentInlayHintsId},
+    document::{DocumentCodeLens, DocumentInlayHints, DocumentInlayHintsId},
     editor::Action,
     handlers::lsp::SignatureHelpInvoked,
     theme::Style,
@@ -28,11 +28,14 @@
 
 use crate::{
     compositor::{self, Compositor},
+    handlers,
     job::Callback,
     ui::{self, overlay::overlaid, FileLocation, Picker, Popup, PromptEvent},
 };
 
-use std::{cmp::Ordering, collections::HashSet, fmt::Display, future::Future, path::Path};
+use std::{
+    cmp::Ordering, collections::HashSet, fmt::Display, future::Future, path::Path, sync::Arc,
+};
 
 /// Gets the first language server that is attached to a document which supports a specific feature.
 /// If there is no configured language server that supports the feature, this displays a status message.
@@ -215,19 +218,24 @@ fn diag_picker(
         flat_diag.reserve(diags.len());
 
         for (diag, provider) in diags {
-            if let Some(ls) = provider
-                .language_server_id()
-                .and_then(|id| cx.editor.language_server_by_id(id))
-            {
-                flat_diag.push(PickerDiagnostic {
-                    location: Location {
-                        uri: uri.clone(),
-                        range: diag.range,
-                        offset_encoding: ls.offset_encoding(),
-                    },
-                    diag,
-                });
-            }
+            let offset_encoding = match provider {
+                DiagnosticProvider::Lsp { server_id, .. } => {
+                    match cx.editor.language_server_by_id(server_id) {
+                        Some(ls) => ls.offset_encoding(),
+                        None => continue,
+                    }
+                }
+                // Linter diagnostics are positioned in characters.
+                DiagnosticProvider::Linter { .. } => OffsetEncoding::Utf32,
+            };
+            flat_diag.push(PickerDiagnostic {
+                location: Location {
+                    uri: uri.clone(),
+                    range: diag.range,
+                    offset_encoding,
+                },
+                diag,
+            });
         }
     }
 
@@ -825,6 +833,81 @@ pub fn code_action(cx: &mut Context) {
     });
 }
 
+impl ui::menu::Item for DocumentCodeLens {
+    type Data = ();
+    fn format(&self, _data: &Self::Data) -> Row<'_> {
+        self.lens
+            .command
+            .as_ref()
+            .map_or("", |command| command.title.as_str())
+            .into()
+    }
+}
+
+pub fn code_lens(cx: &mut Context) {
+    let (view, doc) = current_ref!(cx.editor);
+    let text = doc.text().slice(..);
+    let line = text.char_to_line(doc.selection(view.id).primary().cursor(text));
+    let doc_id = doc.id();
+
+    let mut futures = handlers::code_lens::code_lens_requests(cx.editor, doc_id);
+    if futures.is_empty() {
+        cx.editor
+            .set_error("No configured language server supports code lenses");
+        return;
+    }
+
+    cx.jobs.callback(async move {
+        let mut lenses = Vec::new();
+
+        while let Some(output) = futures.next().await {
+            match output {
+                Ok(mut items) => lenses.append(&mut items),
+                Err(err) => log::error!("while gathering code lenses: {err}"),
+            }
+        }
+
+        let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+            let Some(doc) = editor.document(doc_id) else {
+                return;
+            };
+            let text = doc.text().slice(..);
+            lenses.retain(|lens| {
+                lens.lens.command.is_some()
+                    && lens.start <= text.len_chars()
+                    && text.char_to_line(lens.start) == line
+            });
+            if lenses.is_empty() {
+                editor.set_error("No code lenses available");
+                return;
+            }
+            lenses.sort_by_key(|lens| lens.start);
+
+            let mut picker = ui::Menu::new(lenses, (), move |editor, lens, event| {
+                if event != PromptEvent::Validate {
+                    return;
+                }
+
+                // always present here
+                let lens = lens.unwrap();
+                if let Some(command) = &lens.lens.command {
+                    log::debug!("code lens command: {:?}", command);
+                    editor.execute_lsp_command(command.clone(), lens.language_server_id);
+                }
+            });
+            picker.move_down(); // pre-select the first item
+
+            let popup = Popup::new("code-lens", picker)
+                .with_scrollbar(false)
+                .auto_close(true);
+
+            compositor.replace_or_push("code-lens", popup);
+        };
+
+        Ok(Callback::EditorCompositor(Box::new(call)))
+    });
+}
+
 #[derive(Debug)]
 pub struct ApplyEditError {
     pub kind: ApplyEditErrorKind,
@@ -1027,6 +1110,323 @@ pub fn goto_reference(cx: &mut Context) {
     });
 }
 
+/// An item of a call or type hierarchy.
+trait HierarchyItem: Clone + Send + Sync + 'static {
+    fn name(&self) -> &str;
+    fn kind(&self) -> lsp::SymbolKind;
+    fn detail(&self) -> Option<&str>;
+    fn uri(&self) -> &lsp::Url;
+}
+
+impl HierarchyItem for lsp::CallHierarchyItem {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn kind(&self) -> lsp::SymbolKind {
+        self.kind
+    }
+
+    fn detail(&self) -> Option<&str> {
+        self.detail.as_deref()
+    }
+
+    fn uri(&self) -> &lsp::Url {
+        &self.uri
+    }
+}
+
+impl HierarchyItem for lsp::TypeHierarchyItem {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn kind(&self) -> lsp::SymbolKind {
+        self.kind
+    }
+
+    fn detail(&self) -> Option<&str> {
+        self.detail.as_deref()
+    }
+
+    fn uri(&self) -> &lsp::Url {
+        &self.uri
+    }
+}
+
+type HierarchyRequest<T> = Option<BoxFuture<'static, anyhow::Result<Vec<T>>>>;
+
+/// A direction in which a hierarchy picker expands items, like the incoming calls of a call
+/// hierarchy or the supertypes of a type hierarchy.
+trait HierarchyDirection: Copy + Send + Sync + 'static {
+    type Item: HierarchyItem;
+
+    const FEATURE: LanguageServerFeature;
+    const NAME: &'static str;
+
+    /// Requests the items at the position, the first one is the most specific.
+    fn prepare(
+        language_server: &Client,
+        text_document: lsp::TextDocumentIdentifier,
+        position: lsp::Position,
+    ) -> HierarchyRequest<Self::Item>;
+
+    /// Requests the next level of the hierarchy of `item` along with the range to jump to for
+    /// each of the returned items.
+    fn expand(
+        self,
+        language_server: &Client,
+        item: Self::Item,
+    ) -> HierarchyRequest<(Self::Item, lsp::Range)>;
+
+    fn empty_message(self, name: &str) -> String;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CallHierarchyDirection {
+    Incoming,
+    Outgoing,
+}
+
+impl HierarchyDirection for CallHierarchyDirection {
+    type Item = lsp::CallHierarchyItem;
+
+    const FEATURE: LanguageServerFeature = LanguageServerFeature::CallHierarchy;
+    const NAME: &'static str = "call hierarchy";
+
+    fn prepare(
+        language_server: &Client,
+        text_document: lsp::TextDocumentIdentifier,
+        position: lsp::Position,
+    ) -> HierarchyRequest<Self::Item> {
+        let request = language_server.prepare_call_hierarchy(text_document, position)?;
+        Some(async move { Ok(request.await?.unwrap_or_default()) }.boxed())
+    }
+
+    fn expand(
+        self,
+        language_server: &Client,
+        item: Self::Item,
+    ) -> HierarchyRequest<(Self::Item, lsp::Range)> {
+        match self {
+            Self::Incoming => {
+                let request = language_server.call_hierarchy_incoming(item)?;
+                let calls = async move {
+                    let calls = request.await?.unwrap_or_default().into_iter().map(|call| {
+                        // Jump to the call site rather than to the caller.
+                        let range = call
+                            .from_ranges
+                            .first()
+                            .copied()
+                            .unwrap_or(call.from.selection_range);
+                        (call.from, range)
+                    });
+                    Ok(calls.collect())
+                };
+                Some(calls.boxed())
+            }
+            Self::Outgoing => {
+                let request = language_server.call_hierarchy_outgoing(item)?;
+                let calls = async move {
+                    let calls = request.await?.unwrap_or_default().into_iter().map(|call| {
+                        let range = call.to.selection_range;
+                        (call.to, range)
+                    });
+                    Ok(calls.collect())
+                };
+                Some(calls.boxed())
+            }
+        }
+    }
+
+    fn empty_message(self, name: &str) -> String {
+        match self {
+            Self::Incoming => format!("No incoming calls to {name}"),
+            Self::Outgoing => format!("No outgoing calls from {name}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TypeHierarchyDirection {
+    Supertypes,
+    Subtypes,
+}
+
+impl HierarchyDirection for TypeHierarchyDirection {
+    type Item = lsp::TypeHierarchyItem;
+
+    const FEATURE: LanguageServerFeature = LanguageServerFeature::TypeHierarchy;
+    const NAME: &'static str = "type hierarchy";
+
+    fn prepare(
+        language_server: &Client,
+        text_document: lsp::TextDocumentIdentifier,
+        position: lsp::Position,
+    ) -> HierarchyRequest<Self::Item> {
+        let request = language_server.prepare_type_hierarchy(text_document, position)?;
+        Some(async move { Ok(request.await?.unwrap_or_default()) }.boxed())
+    }
+
+    fn expand(
+        self,
+        language_server: &Client,
+        item: Self::Item,
+    ) -> HierarchyRequest<(Self::Item, lsp::Range)> {
+        let request = match self {
+            Self::Supertypes => language_server.type_hierarchy_supertypes(item)?.boxed(),
+            Self::Subtypes => language_server.type_hierarchy_subtypes(item)?.boxed(),
+        };
+        let types = async move {
+            let types = request.await?.unwrap_or_default().into_iter().map(|item| {
+                let range = item.selection_range;
+                (item, range)
+            });
+            Ok(types.collect())
+        };
+        Some(types.boxed())
+    }
+
+    fn empty_message(self, name: &str) -> String {
+        match self {
+            Self::Supertypes => format!("No supertypes of {name}"),
+            Self::Subtypes => format!("No subtypes of {name}"),
+        }
+    }
+}
+
+/// An entry of a hierarchy picker, like a caller for incoming calls or a subtype for subtypes.
+struct HierarchyEntry<T> {
+    item: T,
+    location: Location,
+    language_server_id: LanguageServerId,
+}
+
+/// Requests the next level of the hierarchy of `item` and opens it in a picker. Selecting an
+/// entry with `Ctrl-l` expands the next level of the hierarchy.
+fn hierarchy_picker<D: HierarchyDirection>(
+    language_server: Arc<Client>,
+    item: D::Item,
+    direction: D,
+) -> BoxFuture<'static, anyhow::Result<Callback>> {
+    async move {
+        let name = item.name().to_owned();
+        let offset_encoding = language_server.offset_encoding();
+        let language_server_id = language_server.id();
+        let items = match direction.expand(&language_server, item) {
+            Some(request) => request.await?,
+            None => Vec::new(),
+        };
+        let entries: Vec<_> = items
+            .into_iter()
+            .filter_map(|(item, range)| {
+                let location = lsp_location_to_location(
+                    lsp::Location::new(item.uri().clone(), range),
+                    offset_encoding,
+                )?;
+                Some(HierarchyEntry {
+                    item,
+                    location,
+                    language_server_id,
+                })
+            })
+            .collect();
+
+        let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+            if entries.is_empty() {
+                editor.set_status(direction.empty_message(&name));
+                return;
+            }
+
+            let columns = [
+                ui::PickerColumn::new("kind", |entry: &HierarchyEntry<D::Item>, _| {
+                    display_symbol_kind(entry.item.kind()).into()
+                }),
+                ui::PickerColumn::new("name", |entry: &HierarchyEntry<D::Item>, _| {
+                    entry.item.name().into()
+                }),
+                ui::PickerColumn::new("detail", |entry: &HierarchyEntry<D::Item>, _| {
+                    entry.item.detail().unwrap_or_default().into()
+                }),
+                ui::PickerColumn::new("path", |entry: &HierarchyEntry<D::Item>, _| {
+                    let path = if let Some(path) = entry.location.uri.as_path() {
+                        path::get_relative_path(path).to_string_lossy().to_string()
+                    } else {
+                        entry.location.uri.to_string()
+                    };
+                    format!("{path}:{}", entry.location.range.start.line + 1).into()
+                }),
+            ];
+
+            let picker = Picker::new(columns, 1, entries, (), |cx, entry, action| {
+                jump_to_location(cx.editor, &entry.location, action)
+            })
+            .with_preview(|_editor, entry| location_to_file_location(&entry.location))
+            .with_expand(move |cx, entry| {
+                let Some(language_server) = cx
+                    .editor
+                    .language_servers
+                    .get_by_id(entry.language_server_id)
+                    .cloned()
+                else {
+                    cx.editor.set_error("Language Server disappeared");
+                    return;
+                };
+                cx.jobs.callback(hierarchy_picker(
+                    language_server,
+                    entry.item.clone(),
+                    direction,
+                ));
+            })
+            .truncate_start(false);
+
+            compositor.push(Box::new(overlaid(picker)));
+        };
+
+        Ok(Callback::EditorCompositor(Box::new(call)))
+    }
+    .boxed()
+}
+
+fn hierarchy<D: HierarchyDirection>(cx: &mut Context, direction: D) {
+    let (view, doc) = current_ref!(cx.editor);
+    let language_server = language_server_with_feature!(cx.editor, doc, D::FEATURE);
+    let pos = doc.position(view.id, language_server.offset_encoding());
+    let future = D::prepare(language_server, doc.identifier(), pos).unwrap();
+    let language_server = cx
+        .editor
+        .language_servers
+        .get_by_id(language_server.id())
+        .cloned()
+        .expect("language server attached to the document must be registered");
+
+    cx.jobs.callback(async move {
+        let Some(item) = future.await?.into_iter().next() else {
+            let call = |editor: &mut Editor| {
+                editor.set_error(format!("No {} item found.", D::NAME));
+            };
+            return Ok(Callback::Editor(Box::new(call)));
+        };
+        hierarchy_picker(language_server, item, direction).await
+    });
+}
+
+pub fn incoming_calls(cx: &mut Context) {
+    hierarchy(cx, CallHierarchyDirection::Incoming);
+}
+
+pub fn outgoing_calls(cx: &mut Context) {
+    hierarchy(cx, CallHierarchyDirection::Outgoing);
+}
+
+pub fn supertypes(cx: &mut Context) {
+    hierarchy(cx, TypeHierarchyDirection::Supertypes);
+}
+
+pub fn subtypes(cx: &mut Context) {
+    hierarchy(cx, TypeHierarchyDirection::Subtypes);
+}
+
 pub fn signature_help(cx: &mut Context) {
     cx.editor
         .handlers
@@ -1,19 +1,26 @@
+use std::collections::BTreeMap;
 use std::fmt::Write;
 use std::io::BufReader;
 use std::ops::{self, Deref};
 
+use crate::config::CustomCommand;
 use crate::job::Job;
 
 use super::*;
 
+use arc_swap::access::{DynAccess, DynGuard};
 use helix_core::command_line::{Args, Flag, Signature, Token, TokenKind};
 use helix_core::fuzzy::fuzzy_match;
 use helix_core::indent::MAX_INDENT;
 use helix_core::line_ending;
+use helix_event::runtime_local;
 use helix_stdx::path::home_dir;
 use helix_view::document::{read_to_string, DEFAULT_LANGUAGE_NAME};
 use helix_view::editor::{CloseError, ConfigEvent};
+use helix_view::events::DocumentDidChangeLanguage;
 use helix_view::expansion;
+use helix_view::session::{self, Session};
+use once_cell::sync::OnceCell;
 use serde_json::Value;
 use ui::completers::{self, Completer};
 
@@ -115,7 +122,8 @@ fn quit(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> anyhow
 
     // last view and we have unsaved changes
     if cx.editor.tree.views().count() == 1 {
-        buffers_remaining_impl(cx.editor)?
+        buffers_remaining_impl(cx.editor)?;
+        save_auto_session(cx.editor);
     }
 
     cx.block_try_flush_writes()?;
@@ -130,11 +138,25 @@ fn force_quit(cx: &mut compositor::Context, _args: Args, event: PromptEvent) ->
     }
 
     cx.block_try_flush_writes()?;
+    if cx.editor.tree.views().count() == 1 {
+        save_auto_session(cx.editor);
+    }
     cx.editor.close(view!(cx.editor).id);
 
     Ok(())
 }
 
+/// Saves the session of the workspace if `editor.auto-session` is enabled. Called before
+/// the last view is closed.
+pub(super) fn save_auto_session(editor: &Editor) {
+    if !editor.config().auto_session {
+        return;
+    }
+    if let Err(err) = editor.session().save(&session::workspace_session_file()) {
+        log::error!("failed to save session: {err}");
+    }
+}
+
 fn open(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
     if event != PromptEvent::Validate {
         return Ok(());
@@ -775,7 +797,7 @@ fn force_write_quit(
 pub(super) fn buffers_remaining_impl(editor: &mut Editor) -> anyhow::Result<()> {
     let modified_ids: Vec<_> = editor
         .documents()
-        .filter(|doc| doc.is_modified())
+        .filter(|doc| doc.is_modified() && editor.debug_console != Some(doc.id()))
         .map(|doc| doc.id())
         .collect();
 
@@ -963,6 +985,7 @@ fn quit_all_impl(cx: &mut compositor::Context, force: bool) -> anyhow::Result<()
     if !force {
         buffers_remaining_impl(cx.editor)?;
     }
+    save_auto_session(cx.editor);
 
     // close all views
     let views: Vec<_> = cx.editor.tree.views().map(|(view, _)| view.id).collect();
@@ -1511,12 +1534,14 @@ fn reload(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> anyh
     doc.reload(view, &cx.editor.diff_providers).map(|_| {
         view.ensure_cursor_in_view(doc, scrolloff);
     })?;
+    let doc_id = doc.id();
     if let Some(path) = doc.path().map(ToOwned::to_owned) {
         cx.editor
             .language_servers
             .file_event_handler
             .file_changed(path);
     }
+    crate::handlers::blame::refresh_blame(cx.editor, doc_id);
     Ok(())
 }
 
@@ -1525,60 +1550,59 @@ fn reload_all(cx: &mut compositor::Context, _args: Args, event: PromptEvent) ->
         return Ok(());
     }
 
-    let scrolloff = cx.editor.config().scrolloff;
-    let view_id = view!(cx.editor).id;
-
-    let docs_view_ids: Vec<(DocumentId, Vec<ViewId>)> = cx
-        .editor
-        .documents_mut()
-        .map(|doc| {
-            let mut view_ids: Vec<_> = doc.selections().keys().cloned().collect();
+    let doc_ids: Vec<DocumentId> = cx.editor.documents.keys().copied().collect();
+    for doc_id in doc_ids {
+        if let Err(error) = reload_document(cx.editor, doc_id) {
+            cx.editor.set_error(format!("{}", error));
+        }
+    }
 
-            if view_ids.is_empty() {
-                doc.ensure_view_init(view_id);
-                view_ids.push(view_id);
-            };
+    Ok(())
+}
 
-            (doc.id(), view_ids)
-        })
-        .collect();
+/// Reloads the document from its file on disk, syncing all views of the document.
+pub(crate) fn reload_document(editor: &mut Editor, doc_id: DocumentId) -> anyhow::Result<()> {
+    let scrolloff = editor.config().scrolloff;
+    let view_id = view!(editor).id;
 
-    for (doc_id, view_ids) in docs_view_ids {
-        let doc = doc_mut!(cx.editor, &doc_id);
+    let doc = doc_mut!(editor, &doc_id);
+    let mut view_ids: Vec<_> = doc.selections().keys().cloned().collect();
+    if view_ids.is_empty() {
+        doc.ensure_view_init(view_id);
+        view_ids.push(view_id);
+    };
 
-        // Every doc is guaranteed to have at least 1 view at this point.
-        let view = view_mut!(cx.editor, view_ids[0]);
+    // Every doc is guaranteed to have at least 1 view at this point.
+    let view = view_mut!(editor, view_ids[0]);
 
-        // Ensure that the view is synced with the document's history.
-        view.sync_changes(doc);
+    // Ensure that the view is synced with the document's history.
+    view.sync_changes(doc);
 
-        if let Err(error) = doc.reload(view, &cx.editor.diff_providers) {
-            cx.editor.set_error(format!("{}", error));
-            continue;
-        }
+    doc.reload(view, &editor.diff_providers)?;
 
-        if let Some(path) = doc.path().map(ToOwned::to_owned) {
-            cx.editor
-                .language_servers
-                .file_event_handler
-                .file_changed(path);
-        }
+    if let Some(path) = doc.path().map(ToOwned::to_owned) {
+        editor
+            .language_servers
+            .file_event_handler
+            .file_changed(path);
+    }
 
-        for view_id in view_ids {
-            let view = view_mut!(cx.editor, view_id);
-            if view.doc.eq(&doc_id) {
-                // Reloading commits the diff against disk through the first view
-                // only (above). Any other view onto this document is left
-                // pointing at the pre-reload revision, so sync it now; otherwise
-                // its jumplist entries keep referencing the old (e.g. larger)
-                // text and a later commit panics when mapping them through a
-                // changeset whose pre-image no longer contains them.
-                view.sync_changes(doc);
-                view.ensure_cursor_in_view(doc, scrolloff);
-            }
+    for view_id in view_ids {
+        let view = view_mut!(editor, view_id);
+        if view.doc.eq(&doc_id) {
+            // Reloading commits the diff against disk through the first view
+            // only (above). Any other view onto this document is left
+            // pointing at the pre-reload revision, so sync it now; otherwise
+            // its jumplist entries keep referencing the old (e.g. larger)
+            // text and a later commit panics when mapping them through a
+            // changeset whose pre-image no longer contains them.
+            view.sync_changes(doc);
+            view.ensure_cursor_in_view(doc, scrolloff);
         }
     }
 
+    crate::handlers::blame::refresh_blame(editor, doc_id);
+
     Ok(())
 }
 
@@ -2042,7 +2066,7 @@ fn debug_eval(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> a
         // TODO: support no frame_id
 
         let frame_id = debugger.stack_frames[&thread_id][frame].id;
-        let response = helix_lsp::block_on(debugger.eval(args.join(" "), Some(frame_id)))?;
+        let response = helix_lsp::block_on(debugger.eval(args.join(" "), Some(frame_id), None))?;
         cx.editor.set_status(response.result);
     }
     Ok(())
@@ -2334,6 +2358,10 @@ fn language(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> any
     let diagnostics =
         Editor::doc_diagnostics(&cx.editor.language_servers, &cx.editor.diagnostics, doc);
     doc.replace_diagnostics(diagnostics, &[], None);
+    helix_event::dispatch(DocumentDidChangeLanguage {
+        editor: cx.editor,
+        doc: id,
+    });
     Ok(())
 }
 
@@ -2641,6 +2669,280 @@ fn reset_diff_change(
     Ok(())
 }
 
+/// Returns the path of the current document and the ranges of the vcs diff hunks under the
+/// selections, using `lines` to pick the document or the base side of each hunk.
+fn selected_vcs_hunks(
+    editor: &Editor,
+    lines: impl Fn(&Hunk) -> ops::Range<u32>,
+) -> anyhow::Result<(PathBuf, Vec<ops::Range<u32>>)> {
+    let (view, doc) = current_ref!(editor);
+    let Some(handle) = doc.vcs_diff_handle() else {
+        bail!("Diff is not available in the current buffer")
+    };
+    let Some(path) = doc.path() else {
+        bail!("Buffer has no path")
+    };
+    // The line ranges are applied to the raw bytes of the file.
+    if doc.encoding() != encoding::UTF_8 {
+        bail!("Staging changes is only supported for UTF-8 documents")
+    }
+
+    let diff = handle.load();
+    let doc_text = doc.text().slice(..);
+    let hunks: Vec<_> = diff
+        .hunks_intersecting_line_ranges(doc.selection(view.id).line_ranges(doc_text))
+        .map(|hunk| lines(&hunk))
+        .collect();
+    if hunks.is_empty() {
+        bail!("There are no changes under any selection");
+    }
+    Ok((path.to_path_buf(), hunks))
+}
+
+pub(super) fn stage_diff_change_impl(editor: &mut Editor) -> anyhow::Result<()> {
+    let (path, lines) = selected_vcs_hunks(editor, |hunk| hunk.after.clone())?;
+    let contents = doc!(editor).text().to_string();
+    let changes = editor
+        .diff_providers
+        .stage_lines(&path, contents.as_bytes(), &lines)?;
+    let diff_providers = editor.diff_providers.clone();
+    doc_mut!(editor).update_diff_bases(&diff_providers);
+    editor.set_status(format!(
+        "Staged {changes} change{}",
+        if changes == 1 { "" } else { "s" }
+    ));
+    Ok(())
+}
+
+fn stage_diff_change(
+    cx: &mut compositor::Context,
+    _args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    stage_diff_change_impl(cx.editor)
+}
+
+pub(super) fn unstage_diff_change_impl(editor: &mut Editor) -> anyhow::Result<()> {
+    let (path, lines) = selected_vcs_hunks(editor, |hunk| hunk.before.clone())?;
+    let changes = editor.diff_providers.unstage_lines(&path, &lines)?;
+    let diff_providers = editor.diff_providers.clone();
+    doc_mut!(editor).update_diff_bases(&diff_providers);
+    editor.set_status(format!(
+        "Unstaged {changes} change{}",
+        if changes == 1 { "" } else { "s" }
+    ));
+    Ok(())
+}
+
+fn unstage_diff_change(
+    cx: &mut compositor::Context,
+    _args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    unstage_diff_change_impl(cx.editor)
+}
+
+pub(super) fn show_diff_change_base_impl(
+    editor: &mut Editor,
+    jobs: &mut Jobs,
+) -> anyhow::Result<()> {
+    let (view, doc) = current_ref!(editor);
+    let Some(handle) = doc.diff_handle() else {
+        bail!("Diff is not available in the current buffer")
+    };
+
+    let diff = handle.load();
+    let doc_text = doc.text().slice(..);
+    let line = doc_text.char_to_line(doc.selection(view.id).primary().cursor(doc_text));
+    let Some(hunk) = diff
+        .hunks_intersecting_line_ranges(std::iter::once((line, line)))
+        .next()
+    else {
+        bail!("There is no change at the cursor");
+    };
+    if hunk.before.is_empty() {
+        bail!("The change at the cursor only adds lines");
+    }
+
+    let diff_base = diff.diff_base();
+    let start = diff_base.line_to_char(hunk.before.start as usize);
+    let end = diff_base.line_to_char(hunk.before.end as usize);
+    let contents = format!(
+        "```{}\n{}\n```",
+        doc.language_name().unwrap_or_default(),
+        diff_base.slice(start..end).to_string().trim_end()
+    );
+
+    let callback = async move {
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut Compositor| {
+                let contents = ui::Markdown::new(contents, editor.syn_loader.clone());
+                let popup = Popup::new("diff-base", contents).auto_close(true);
+                compositor.replace_or_push("diff-base", popup);
+            },
+        ));
+        Ok(call)
+    };
+    jobs.callback(callback);
+
+    Ok(())
+}
+
+fn show_diff_change_base(
+    cx: &mut compositor::Context,
+    _args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    show_diff_change_base_impl(cx.editor, cx.jobs)
+}
+
+fn diff(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let (view, doc) = current_ref!(cx.editor);
+    let view_id = view.id;
+    let doc_id = doc.id();
+    let partner = match args.first() {
+        Some(path) => {
+            let path = helix_stdx::path::expand_tilde(Path::new(path));
+            cx.editor.open(&path, Action::VerticalSplit)?
+        }
+        None => {
+            let Some(handle) = doc.vcs_diff_handle() else {
+                bail!("Diff is not available in the current buffer")
+            };
+            let diff_base = handle.load().diff_base().clone();
+            let mut base = Document::from(
+                diff_base,
+                Some((doc.encoding(), false)),
+                cx.editor.config.clone(),
+                cx.editor.syn_loader.clone(),
+            );
+            if let Some(language) = doc.language_name() {
+                base.set_language_by_language_id(language, &cx.editor.syn_loader.load())?;
+            }
+            cx.editor
+                .new_file_from_document(Action::VerticalSplit, base)
+        }
+    };
+    if partner == doc_id {
+        bail!("Cannot compare a buffer with itself");
+    }
+
+    cx.editor.diff_documents(doc_id, partner);
+    cx.editor.focus(view_id);
+    Ok(())
+}
+
+fn diff_off(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let doc_id = doc!(cx.editor).id();
+    cx.editor.end_diff(doc_id);
+    Ok(())
+}
+
+fn diff_put(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let (view, doc) = current_ref!(cx.editor);
+    let Some(diff_mode) = doc.diff_mode() else {
+        bail!("The current buffer is not compared with another buffer")
+    };
+    let partner_id = diff_mode.partner;
+
+    let diff = diff_mode.handle.load();
+    let doc_text = doc.text().slice(..);
+    let partner_text = diff.diff_base().slice(..);
+    let changes: Vec<_> = diff
+        .hunks_intersecting_line_ranges(doc.selection(view.id).line_ranges(doc_text))
+        .map(|hunk| {
+            let start = doc_text.line_to_char(hunk.after.start as usize);
+            let end = doc_text.line_to_char(hunk.after.end as usize);
+            let text: Tendril = doc_text.slice(start..end).chunks().collect();
+            (
+                partner_text.line_to_char(hunk.before.start as usize),
+                partner_text.line_to_char(hunk.before.end as usize),
+                (!text.is_empty()).then_some(text),
+            )
+        })
+        .collect();
+    if changes.is_empty() {
+        bail!("There are no changes under any selection");
+    }
+    drop(diff);
+
+    let count = changes.len();
+    let view_id = cx.editor.get_synced_view_id(partner_id);
+    let partner = doc_mut!(cx.editor, &partner_id);
+    let transaction = Transaction::change(partner.text(), changes.into_iter());
+    let view = view_mut!(cx.editor, view_id);
+    partner.apply(&transaction, view.id);
+    partner.append_changes_to_history(view);
+    cx.editor.set_status(format!(
+        "Put {count} change{}",
+        if count == 1 { "" } else { "s" }
+    ));
+    Ok(())
+}
+
+fn session_save(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let path = match args.first() {
+        Some(path) => helix_stdx::path::expand_tilde(Path::new(path)).into_owned(),
+        None => session::workspace_session_file(),
+    };
+    cx.editor.session().save(&path)?;
+    cx.editor
+        .set_status(format!("Saved session to {}", path.display()));
+    Ok(())
+}
+
+fn session_load(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let path = match args.first() {
+        Some(path) => helix_stdx::path::expand_tilde(Path::new(path)).into_owned(),
+        None => session::workspace_session_file(),
+    };
+    let session = Session::load(&path)?;
+    cx.editor.restore_session(&session)?;
+    cx.editor
+        .set_status(format!("Loaded session from {}", path.display()));
+    Ok(())
+}
+
 fn clear_register(
     cx: &mut compositor::Context,
     args: Args,
@@ -3875,7 +4177,7 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
     TypableCommand {
         name: "reset-diff-change",
         aliases: &["diffget", "diffg"],
-        doc: "Reset the diff change at the cursor position.",
+        doc: "Reset the diff change at the cursor position. In diff mode this copies the change from the other buffer.",
         fun: reset_diff_change,
         completer: CommandCompleter::none(),
         signature: Signature {
@@ -3883,6 +4185,94 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
             ..Signature::DEFAULT
         },
     },
+    TypableCommand {
+        name: "diff",
+        aliases: &[],
+        doc: "Compare the current buffer side by side with the given file, or with its version control base if no file is given.",
+        fun: diff,
+        completer: CommandCompleter::positional(&[completers::filename]),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "diff-off",
+        aliases: &[],
+        doc: "Stop comparing the current buffer with another buffer.",
+        fun: diff_off,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "diff-put",
+        aliases: &["diffput", "diffp"],
+        doc: "Copy the diff change at the cursor position to the other buffer in diff mode.",
+        fun: diff_put,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "session-save",
+        aliases: &[],
+        doc: "Save the split layout and sizes, the files of the views, their cursor positions and jumplists and the working directory to a session file. Without an argument the session of the current workspace is saved.",
+        fun: session_save,
+        completer: CommandCompleter::positional(&[completers::filename]),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "session-load",
+        aliases: &[],
+        doc: "Restore a session saved with `:session-save`, replacing the current views. Without an argument the session of the current workspace is loaded.",
+        fun: session_load,
+        completer: CommandCompleter::positional(&[completers::filename]),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "stage-diff-change",
+        aliases: &[],
+        doc: "Stage the diff changes under the selections into the git index.",
+        fun: stage_diff_change,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "unstage-diff-change",
+        aliases: &[],
+        doc: "Unstage the staged diff changes under the selections from the git index.",
+        fun: unstage_diff_change,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "show-diff-change-base",
+        aliases: &[],
+        doc: "Show the original text of the diff change at the cursor position.",
+        fun: show_diff_change_base,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
     TypableCommand {
         name: "clear-register",
         aliases: &[],
@@ -4012,7 +4402,29 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
             .collect()
     });
 
-fn execute_command_line(
+type CustomCommands = Box<dyn DynAccess<BTreeMap<String, CustomCommand>> + Send + Sync>;
+
+runtime_local! {
+    static CUSTOM_COMMANDS: OnceCell<CustomCommands> = OnceCell::new();
+}
+
+/// Sets the commands of the `[commands]` table of the config that run like typable
+/// commands.
+pub fn init_custom_commands(commands: CustomCommands) {
+    let _ = CUSTOM_COMMANDS.set(commands);
+}
+
+/// Returns the commands of the `[commands]` table of the config, `None` before the
+/// application set them up.
+pub fn custom_commands() -> Option<DynGuard<BTreeMap<String, CustomCommand>>> {
+    CUSTOM_COMMANDS.get().map(|commands| commands.load())
+}
+
+fn custom_command(name: &str) -> Option<CustomCommand> {
+    custom_commands()?.get(name).cloned()
+}
+
+pub(crate) fn execute_command_line(
     cx: &mut compositor::Context,
     input: &str,
     event: PromptEvent,
@@ -4030,7 +4442,15 @@ fn execute_command_line(
 
     match typed::TYPABLE_COMMAND_MAP.get(command) {
         Some(cmd) => execute_command(cx, cmd, rest, event),
-        None if event == PromptEvent::Validate => Err(anyhow!("no such command: '{command}'")),
+        None if event == PromptEvent::Validate => match custom_command(command) {
+            Some(custom) => {
+                // The commands run after the command line was handled, like a key binding.
+                let call = custom_command_call(cx, command, &custom, rest, None)?;
+                job::dispatch_context_blocking(move |cx, compositor| call.execute(cx, compositor));
+                Ok(())
+            }
+            None => Err(anyhow!("no such command: '{command}'")),
+        },
         None => Ok(()),
     }
 }
@@ -4040,10 +4460,22 @@ pub(super) fn execute_command(
     cmd: &TypableCommand,
     args: &str,
     event: PromptEvent,
+) -> anyhow::Result<()> {
+    execute_command_with_arguments(cx, cmd, args, event, None)
+}
+
+/// Like [execute_command] but `%{arg}` and `%{args}` expand to the `arguments` of the
+/// custom command running `cmd`.
+pub(super) fn execute_command_with_arguments(
+    cx: &mut compositor::Context,
+    cmd: &TypableCommand,
+    args: &str,
+    event: PromptEvent,
+    arguments: Option<&[String]>,
 ) -> anyhow::Result<()> {
     let args = if event == PromptEvent::Validate {
         Args::parse(args, cmd.signature, true, |token| {
-            expansion::expand(cx.editor, token).map_err(|err| err.into())
+            expansion::expand_with_arguments(cx.editor, token, arguments).map_err(|err| err.into())
         })
         .map_err(|err| anyhow!("'{}': {err}", cmd.name))?
     } else {
@@ -4054,6 +4486,69 @@ pub(super) fn execute_command(
     (cmd.fun)(cx, args, event).map_err(|err| anyhow!("'{}': {err}", cmd.name))
 }
 
+/// The commands of a custom command together with the arguments it was called with.
+pub(super) struct CustomCommandCall {
+    commands: Vec<MappableCommand>,
+    arguments: Vec<String>,
+}
+
+impl CustomCommandCall {
+    /// Runs the commands in order like [execute_commands].
+    pub fn execute(self, cx: &mut compositor::Context, compositor: &mut Compositor) {
+        execute_commands(cx, compositor, &self.commands, Some(&self.arguments))
+    }
+}
+
+/// Executes the typable command `name`, builtin or defined in the `[commands]` table of the
+/// config, with the command line `args`. `%{arg}` and `%{args}` in `args` expand to the
+/// `arguments` of the custom command running it, if any.
+///
+/// A custom command is not run here: its commands are returned to be run by the caller,
+/// which can run them before any command that follows it.
+pub(super) fn execute_typable(
+    cx: &mut compositor::Context,
+    name: &str,
+    args: &str,
+    arguments: Option<&[String]>,
+) -> anyhow::Result<Option<CustomCommandCall>> {
+    if let Some(cmd) = TYPABLE_COMMAND_MAP.get(name) {
+        execute_command_with_arguments(cx, cmd, args, PromptEvent::Validate, arguments)?;
+        Ok(None)
+    } else if let Some(custom) = custom_command(name) {
+        custom_command_call(cx, name, &custom, args, arguments).map(Some)
+    } else {
+        Err(anyhow!("no such command: '{name}'"))
+    }
+}
+
+/// Expands the arguments in `args` of a call to a command defined in the `[commands]` table
+/// of the config.
+fn custom_command_call(
+    cx: &mut compositor::Context,
+    name: &str,
+    command: &CustomCommand,
+    args: &str,
+    arguments: Option<&[String]>,
+) -> anyhow::Result<CustomCommandCall> {
+    const SIGNATURE: Signature = Signature {
+        positionals: (0, None),
+        ..Signature::DEFAULT
+    };
+
+    let arguments = Args::parse(args, SIGNATURE, true, |token| {
+        expansion::expand_with_arguments(cx.editor, token, arguments).map_err(|err| err.into())
+    })
+    .map_err(|err| anyhow!("'{name}': {err}"))?
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+
+    Ok(CustomCommandCall {
+        commands: command.commands.clone(),
+        arguments,
+    })
+}
+
 #[allow(clippy::unnecessary_unwrap)]
 pub(super) fn command_mode(cx: &mut Context) {
     let mut prompt = Prompt::new(
@@ -4066,7 +4561,13 @@ pub(super) fn command_mode(cx: &mut Context) {
             }
         },
     );
-    prompt.doc_fn = Box::new(command_line_doc);
+    prompt.doc_fn = Box::new(|input| {
+        let (command, _, _) = command_line::split(input);
+        match custom_command(command) {
+            Some(custom) => Some(Cow::Owned(custom.doc)),
+            None => command_line_doc(input),
+        }
+    });
 
     // Calculate initial completion
     prompt.recalculate_completion(cx.editor);
@@ -4149,13 +4650,21 @@ fn complete_command_line(editor: &Editor, input: &str) -> Vec<ui::prompt::Comple
     let (command, rest, complete_command) = command_line::split(input);
 
     if complete_command {
+        let custom_commands = custom_commands();
         fuzzy_match(
             input,
-            TYPABLE_COMMAND_LIST.iter().map(|command| command.name),
+            TYPABLE_COMMAND_LIST
+                .iter()
+                .map(|command| command.name)
+                .chain(
+                    custom_commands
+                        .iter()
+                        .flat_map(|commands| commands.keys().map(String::as_str)),
+                ),
             false,
         )
         .into_iter()
-        .map(|(name, _)| (0.., name.into()))
+        .map(|(name, _)| (0.., name.to_owned().into()))
         .collect()
     } else {
         TYPABLE_COMMAND_MAP
@@ -1,9 +1,12 @@
+use crate::commands::typed::TYPABLE_COMMAND_MAP;
 use crate::keymap;
-use crate::keymap::{merge_keys, KeyTrie};
+use crate::keymap::{merge_keys, KeyTrie, MappableCommand};
+use globset::Glob;
 use helix_loader::merge_toml_values;
 use helix_view::{document::Mode, theme};
-use serde::Deserialize;
-use std::collections::HashMap;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Display;
 use std::fs;
 use std::io::Error as IOError;
@@ -13,15 +16,284 @@
 pub struct Config {
     pub theme: Option<theme::Config>,
     pub keys: HashMap<Mode, KeyTrie>,
+    /// Keymaps of documents with a language, by language name, merged over [Self::keys].
+    pub language_keys: HashMap<String, HashMap<Mode, KeyTrie>>,
     pub editor: helix_view::editor::Config,
+    pub hooks: Vec<HookConfig>,
+    /// Typable commands defined in the `[commands]` table, by name.
+    pub commands: BTreeMap<String, CustomCommand>,
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ConfigRaw {
     pub theme: Option<theme::Config>,
-    pub keys: Option<HashMap<Mode, KeyTrie>>,
+    pub keys: Option<KeysConfig>,
     pub editor: Option<toml::Value>,
+    pub hooks: Option<Vec<HookConfig>>,
+    #[serde(default, deserialize_with = "deserialize_commands")]
+    pub commands: Option<BTreeMap<String, CustomCommand>>,
+}
+
+/// A typable command defined in the config that runs a list of other commands.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct CustomCommand {
+    /// Help text shown in the command line and the command palette.
+    #[serde(default)]
+    pub doc: String,
+    /// The commands executed in order. Typable commands may use `%{arg}` and `%{args}` to
+    /// refer to the arguments of the command.
+    pub commands: Vec<MappableCommand>,
+}
+
+/// Deserializes the `[commands]` table, checking that the names don't shadow builtin
+/// typable commands.
+fn deserialize_commands<'de, D>(
+    deserializer: D,
+) -> Result<Option<BTreeMap<String, CustomCommand>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let commands = BTreeMap::<String, CustomCommand>::deserialize(deserializer)?;
+    for name in commands.keys() {
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(serde::de::Error::custom(format!(
+                "invalid command name '{name}'"
+            )));
+        }
+        if TYPABLE_COMMAND_MAP.contains_key(name.as_str()) {
+            return Err(serde::de::Error::custom(format!(
+                "command '{name}' shadows a builtin command"
+            )));
+        }
+    }
+    Ok(Some(commands))
+}
+
+/// Returns the command of `commands` run by `command`, or an error if it runs a typable
+/// command that is neither builtin nor one of `commands`.
+fn custom_command<'a>(
+    command: &MappableCommand,
+    commands: &'a BTreeMap<String, CustomCommand>,
+) -> Result<Option<&'a CustomCommand>, TomlError> {
+    match command {
+        MappableCommand::Typable { name, .. }
+            if !TYPABLE_COMMAND_MAP.contains_key(name.as_str()) =>
+        {
+            commands
+                .get(name)
+                .map(Some)
+                .ok_or_else(|| TomlError::custom(format!("No TypableCommand named ':{name}'")))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Checks that `command` exists and uses the documentation of the command of `commands` it
+/// runs, if any.
+fn resolve_command(
+    command: &mut MappableCommand,
+    commands: &BTreeMap<String, CustomCommand>,
+) -> Result<(), TomlError> {
+    if let Some(custom) = custom_command(command, commands)? {
+        if let MappableCommand::Typable { args, doc, .. } = command {
+            if args.is_empty() {
+                doc.clone_from(&custom.doc);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn resolve_key_trie(
+    trie: &mut KeyTrie,
+    commands: &BTreeMap<String, CustomCommand>,
+) -> Result<(), TomlError> {
+    match trie {
+        KeyTrie::MappableCommand(command) => resolve_command(command, commands),
+        KeyTrie::Sequence(sequence) => sequence
+            .iter_mut()
+            .try_for_each(|command| resolve_command(command, commands)),
+        KeyTrie::Node(node) => node
+            .values_mut()
+            .try_for_each(|trie| resolve_key_trie(trie, commands)),
+    }
+}
+
+/// Returns an error if the command `name` of `commands` runs itself, directly or through
+/// other commands. `stack` holds the commands running `name`.
+fn check_recursion<'a>(
+    commands: &'a BTreeMap<String, CustomCommand>,
+    name: &'a str,
+    stack: &mut Vec<&'a str>,
+) -> Result<(), TomlError> {
+    if stack.contains(&name) {
+        return Err(TomlError::custom(format!(
+            "command '{}' runs itself",
+            stack[0]
+        )));
+    }
+    stack.push(name);
+    for command in &commands[name].commands {
+        if let MappableCommand::Typable { name, .. } = command {
+            if commands.contains_key(name) {
+                check_recursion(commands, name, stack)?;
+            }
+        }
+    }
+    stack.pop();
+    Ok(())
+}
+
+/// The `[keys]` table with the keymaps of each mode, like `[keys.normal]`, and keymaps that
+/// only apply to documents of a language, like `[keys.rust.normal]`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct KeysConfig {
+    pub modes: HashMap<Mode, KeyTrie>,
+    pub languages: HashMap<String, HashMap<Mode, KeyTrie>>,
+}
+
+impl<'de> Deserialize<'de> for KeysConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct KeysConfigVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for KeysConfigVisitor {
+            type Value = KeysConfig;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(formatter, "a table of keymaps by mode or language")
+            }
+
+            fn visit_map<M>(self, mut map: M) -> Result<Self::Value, M::Error>
+            where
+                M: serde::de::MapAccess<'de>,
+            {
+                let mut keys = KeysConfig::default();
+                while let Some(name) = map.next_key::<String>()? {
+                    match name.parse::<Mode>() {
+                        Ok(mode) => {
+                            keys.modes.insert(mode, map.next_value()?);
+                        }
+                        Err(_) => {
+                            // A table of modes is the keymap of a language, anything else is
+                            // likely a misspelled mode.
+                            let language = map
+                                .next_value::<HashMap<String, KeyTrie>>()?
+                                .into_iter()
+                                .map(|(mode, keys)| Ok((mode.parse::<Mode>()?, keys)))
+                                .collect::<anyhow::Result<_>>()
+                                .map_err(|_| {
+                                    serde::de::Error::custom(format!("unknown mode '{name}'"))
+                                })?;
+                            keys.languages.insert(name, language);
+                        }
+                    }
+                }
+                Ok(keys)
+            }
+        }
+
+        deserializer.deserialize_map(KeysConfigVisitor)
+    }
+}
+
+impl KeysConfig {
+    /// Checks that the keymaps of languages are for languages of `languages`, so that a
+    /// misspelled mode isn't taken for a language.
+    fn check_languages(
+        &self,
+        languages: &HashMap<String, HashMap<Mode, KeyTrie>>,
+    ) -> Result<(), ConfigLoadError> {
+        match self
+            .languages
+            .keys()
+            .find(|language| !languages.contains_key(*language))
+        {
+            Some(language) => Err(ConfigLoadError::BadConfig(TomlError::custom(format!(
+                "unknown mode or language '{language}' in [keys]"
+            )))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns the keymaps in the `keys` tables of the languages of a `languages.toml`, by
+/// language name. Languages without a `keys` table have empty keymaps.
+fn languages_keys(
+    languages: &toml::Value,
+) -> Result<HashMap<String, HashMap<Mode, KeyTrie>>, TomlError> {
+    let mut keys = HashMap::new();
+    let languages = languages.get("language").and_then(toml::Value::as_array);
+    for language in languages.into_iter().flatten() {
+        let Some(name) = language.get("name").and_then(toml::Value::as_str) else {
+            continue;
+        };
+        let language_keys = match language.get("keys") {
+            Some(value) => value
+                .clone()
+                .try_into()
+                .map_err(|err| TomlError::custom(format!("keys of language '{name}': {err}")))?,
+            None => HashMap::new(),
+        };
+        keys.insert(name.to_owned(), language_keys);
+    }
+    Ok(keys)
+}
+
+/// Merges the keymaps of each language in `deltas` over `keys`, in order.
+fn merge_language_keys(
+    keys: &HashMap<Mode, KeyTrie>,
+    deltas: impl IntoIterator<Item = HashMap<String, HashMap<Mode, KeyTrie>>>,
+) -> HashMap<String, HashMap<Mode, KeyTrie>> {
+    let mut language_keys: HashMap<String, HashMap<Mode, KeyTrie>> = HashMap::new();
+    for (language, delta) in deltas.into_iter().flatten() {
+        let keys = language_keys
+            .entry(language)
+            .or_insert_with(|| keys.clone());
+        merge_keys(keys, delta);
+    }
+    language_keys
+}
+
+/// Commands run when an event happens, configured with `[[hooks]]`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct HookConfig {
+    pub event: HookEvent,
+    /// Only runs the commands for documents of this language.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Only runs the commands for documents with a path matching this glob.
+    #[serde(default, deserialize_with = "deserialize_glob")]
+    pub glob: Option<Glob>,
+    pub commands: Vec<MappableCommand>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookEvent {
+    DocumentOpen,
+    DocumentSave,
+    DocumentClose,
+    /// A document's view lost focus to another view.
+    DocumentFocusLost,
+    LanguageChange,
+    ModeSwitch,
+    /// The terminal lost focus.
+    FocusLost,
+}
+
+fn deserialize_glob<'de, D>(deserializer: D) -> Result<Option<Glob>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|glob| Glob::new(&glob).map_err(serde::de::Error::custom))
+        .transpose()
 }
 
 impl Default for Config {
@@ -29,7 +301,10 @@ fn default() -> Config {
         Config {
             theme: None,
             keys: keymap::default(),
+            language_keys: HashMap::new(),
             editor: helix_view::editor::Config::default(),
+            hooks: Vec::new(),
+            commands: BTreeMap::new(),
         }
     }
 }
@@ -60,19 +335,35 @@ pub fn load(
         global: Result<&String, ConfigLoadError>,
         local: Result<String, ConfigLoadError>,
     ) -> Result<Config, ConfigLoadError> {
+        Self::load_with_languages(global, local, &helix_loader::config::default_lang_config())
+    }
+
+    /// Loads the config with the keys of the `languages`, the contents of a `languages.toml`.
+    /// The keys of a language in the config are merged over the keys in `languages`.
+    fn load_with_languages(
+        global: Result<&String, ConfigLoadError>,
+        local: Result<String, ConfigLoadError>,
+        languages: &toml::Value,
+    ) -> Result<Config, ConfigLoadError> {
+        let mut languages_keys = languages_keys(languages).map_err(ConfigLoadError::BadConfig)?;
         let global_config: Result<ConfigRaw, ConfigLoadError> =
             global.and_then(|file| toml::from_str(file).map_err(ConfigLoadError::BadConfig));
         let local_config: Result<ConfigRaw, ConfigLoadError> =
             local.and_then(|file| toml::from_str(&file).map_err(ConfigLoadError::BadConfig));
-        let res = match (global_config, local_config) {
+        let mut res = match (global_config, local_config) {
             (Ok(global), Ok(local)) => {
+                let global_keys = global.keys.unwrap_or_default();
+                let local_keys = local.keys.unwrap_or_default();
+                global_keys.check_languages(&languages_keys)?;
+                local_keys.check_languages(&languages_keys)?;
+                languages_keys.retain(|_, keys| !keys.is_empty());
                 let mut keys = keymap::default();
-                if let Some(global_keys) = global.keys {
-                    merge_keys(&mut keys, global_keys)
-                }
-                if let Some(local_keys) = local.keys {
-                    merge_keys(&mut keys, local_keys)
-                }
+                merge_keys(&mut keys, global_keys.modes);
+                merge_keys(&mut keys, local_keys.modes);
+                let language_keys = merge_language_keys(
+                    &keys,
+                    [languages_keys, global_keys.languages, local_keys.languages],
+                );
 
                 let editor = match (global.editor, local.editor) {
                     (None, None) => helix_view::editor::Config::default(),
@@ -84,10 +375,29 @@ pub fn load(
                         .map_err(ConfigLoadError::BadConfig)?,
                 };
 
+                // Commands of the workspace replace global commands with the same name.
+                let commands = global
+                    .commands
+                    .into_iter()
+                    .chain(local.commands)
+                    .flatten()
+                    .collect();
+
+                // Hooks of the workspace run after the global hooks.
+                let hooks = global
+                    .hooks
+                    .into_iter()
+                    .chain(local.hooks)
+                    .flatten()
+                    .collect();
+
                 Config {
                     theme: local.theme.or(global.theme),
                     keys,
+                    language_keys,
                     editor,
+                    hooks,
+                    commands,
                 }
             }
             // if any configs are invalid return that first
@@ -96,17 +406,23 @@ pub fn load(
                 return Err(ConfigLoadError::BadConfig(err))
             }
             (Ok(config), Err(_)) | (Err(_), Ok(config)) => {
+                let config_keys = config.keys.unwrap_or_default();
+                config_keys.check_languages(&languages_keys)?;
+                languages_keys.retain(|_, keys| !keys.is_empty());
                 let mut keys = keymap::default();
-                if let Some(keymap) = config.keys {
-                    merge_keys(&mut keys, keymap);
-                }
+                merge_keys(&mut keys, config_keys.modes);
+                let language_keys =
+                    merge_language_keys(&keys, [languages_keys, config_keys.languages]);
                 Config {
                     theme: config.theme,
                     keys,
+                    language_keys,
                     editor: config.editor.map_or_else(
                         || Ok(helix_view::editor::Config::default()),
                         |val| val.try_into().map_err(ConfigLoadError::BadConfig),
                     )?,
+                    hooks: config.hooks.unwrap_or_default(),
+                    commands: config.commands.unwrap_or_default(),
                 }
             }
 
@@ -114,9 +430,37 @@ pub fn load(
             (Err(err), Err(_)) => return Err(err),
         };
 
+        res.resolve_commands().map_err(ConfigLoadError::BadConfig)?;
         Ok(res)
     }
 
+    /// Checks that the typable commands run by key bindings, hooks and the `[commands]`
+    /// exist and that the `[commands]` don't run themselves. Key bindings of `[commands]`
+    /// show the documentation of the command.
+    fn resolve_commands(&mut self) -> Result<(), TomlError> {
+        let commands = &self.commands;
+        for trie in self.keys.values_mut().chain(
+            self.language_keys
+                .values_mut()
+                .flat_map(|keys| keys.values_mut()),
+        ) {
+            resolve_key_trie(trie, commands)?;
+        }
+        for hook in &mut self.hooks {
+            for command in &mut hook.commands {
+                resolve_command(command, commands)?;
+            }
+        }
+        for (name, custom) in commands {
+            for command in &custom.commands {
+                custom_command(command, commands)
+                    .map_err(|err| TomlError::custom(format!("command '{name}': {err}")))?;
+            }
+            check_recursion(commands, name, &mut Vec::new())?;
+        }
+        Ok(())
+    }
+
     pub fn load_default() -> Result<Config, ConfigLoadError> {
         let global_config =
             fs::read_to_string(helix_loader::config_file()).map_err(ConfigLoadError::Error)?;
@@ -124,20 +468,51 @@ pub fn load_default() -> Result<Config, ConfigLoadError> {
             .map_err(ConfigLoadError::Error);
 
         let phony_config = ConfigLoadError::Error(IOError::other("hacky placeholder"));
-        let global_parsed = Config::load(Ok(&global_config), Err(phony_config))?;
+        let global_parsed = Config::load_with_languages(
+            Ok(&global_config),
+            Err(phony_config),
+            &user_languages(false),
+        )?;
         if let helix_loader::workspace_trust::TrustStatus::Trusted =
             helix_loader::workspace_trust::quick_query_workspace(global_parsed.editor.insecure)
         {
-            Config::load(Ok(&global_config), local_config)
+            Config::load_with_languages(
+                Ok(&global_config),
+                local_config,
+                &user_languages(global_parsed.editor.insecure),
+            )
         } else {
             Ok(global_parsed)
         }
     }
+
+    /// Loads the config used when there is no `config.toml`: the default config with the
+    /// keys of `languages.toml`.
+    pub fn load_without_config_file() -> Result<Config, ConfigLoadError> {
+        Self::load_languages(&user_languages(false))
+    }
+
+    fn load_languages(languages: &toml::Value) -> Result<Config, ConfigLoadError> {
+        Self::load_with_languages(
+            Ok(&String::new()),
+            Err(ConfigLoadError::default()),
+            languages,
+        )
+    }
+}
+
+/// The `languages.toml` of the user, or the default one if it is invalid. The language
+/// loader reports errors in `languages.toml`.
+fn user_languages(insecure: bool) -> toml::Value {
+    helix_loader::config::user_lang_config(insecure)
+        .unwrap_or_else(|_| helix_loader::config::default_lang_config())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use helix_view::input::KeyEvent;
+    use std::str::FromStr;
 
     impl Config {
         fn load_test(config: &str) -> Config {
@@ -183,6 +558,207 @@ fn parsing_keymaps_config_file() {
         );
     }
 
+    #[test]
+    fn parsing_language_keymaps() {
+        use crate::key;
+
+        let config = Config::load_test(
+            r#"
+            [keys.normal]
+            q = "move_next_word_end"
+
+            [keys.rust.normal.space.r]
+            name = "Cargo"
+            sticky = true
+            b = ":sh cargo build"
+            "#,
+        );
+        assert_eq!(
+            config.keys[&Mode::Normal].search(&[key!('q')]),
+            Some(&KeyTrie::MappableCommand(
+                MappableCommand::move_next_word_end
+            ))
+        );
+        assert_eq!(
+            config.keys[&Mode::Normal].search(&[key!(' '), key!('r')]),
+            Some(&KeyTrie::MappableCommand(MappableCommand::rename_symbol))
+        );
+
+        let rust = &config.language_keys["rust"][&Mode::Normal];
+        let node = rust
+            .search(&[key!(' '), key!('r')])
+            .unwrap()
+            .node()
+            .unwrap();
+        assert_eq!(node.name(), "Cargo");
+        assert!(node.is_sticky());
+        assert!(node.contains_key(&key!('b')));
+        // The keys of the language are merged over the global keys.
+        assert!(rust.search(&[key!('q')]).is_some());
+        assert!(rust.search(&[key!(' '), key!('f')]).is_some());
+        assert_eq!(config.language_keys["rust"].len(), 3);
+
+        for invalid in [
+            "[keys.nromal]\nq = \"move_next_word_end\"",
+            "[keys.rutz.normal]\nq = \"move_next_word_end\"",
+        ] {
+            let invalid = invalid.to_owned();
+            assert!(Config::load(Ok(&invalid), Err(ConfigLoadError::default())).is_err());
+        }
+    }
+
+    #[test]
+    fn parsing_languages_keymaps() {
+        use crate::key;
+
+        let languages = toml::toml! {
+            [[language]]
+            name = "rust"
+
+            [language.keys.normal]
+            q = "move_next_word_end"
+            w = "move_prev_word_end"
+
+            [[language]]
+            name = "python"
+        };
+        let config = r#"
+            [keys.rust.normal]
+            w = "move_next_word_start"
+            "#
+        .to_owned();
+        let config = Config::load_with_languages(
+            Ok(&config),
+            Err(ConfigLoadError::default()),
+            &toml::Value::Table(languages),
+        )
+        .unwrap();
+
+        let rust = &config.language_keys["rust"][&Mode::Normal];
+        assert_eq!(
+            rust.search(&[key!('q')]),
+            Some(&KeyTrie::MappableCommand(
+                MappableCommand::move_next_word_end
+            ))
+        );
+        // The keys of the config are merged over the keys of `languages.toml`.
+        assert_eq!(
+            rust.search(&[key!('w')]),
+            Some(&KeyTrie::MappableCommand(
+                MappableCommand::move_next_word_start
+            ))
+        );
+        assert!(!config.language_keys.contains_key("python"));
+    }
+
+    #[test]
+    fn parsing_languages_keymaps_without_config_file() {
+        use crate::key;
+
+        let languages = toml::toml! {
+            [[language]]
+            name = "rust"
+
+            [language.keys.normal]
+            q = "move_next_word_end"
+        };
+        let config = Config::load_languages(&toml::Value::Table(languages)).unwrap();
+
+        assert_eq!(
+            config.language_keys["rust"][&Mode::Normal].search(&[key!('q')]),
+            Some(&KeyTrie::MappableCommand(
+                MappableCommand::move_next_word_end
+            ))
+        );
+        assert_eq!(config.keys, Config::default().keys);
+    }
+
+    #[test]
+    fn parsing_hooks() {
+        let config = Config::load_test(
+            r#"
+            [[hooks]]
+            event = "document-save"
+            language = "rust"
+            glob = "*.rs"
+            commands = [":sh cargo fmt", ":reload"]
+
+            [[hooks]]
+            event = "focus-lost"
+            commands = ["normal_mode"]
+            "#,
+        );
+        assert_eq!(config.hooks.len(), 2);
+        assert_eq!(config.hooks[0].event, HookEvent::DocumentSave);
+        assert_eq!(config.hooks[0].language.as_deref(), Some("rust"));
+        assert!(config.hooks[0]
+            .glob
+            .as_ref()
+            .unwrap()
+            .compile_matcher()
+            .is_match("/src/main.rs"));
+        assert_eq!(config.hooks[1].event, HookEvent::FocusLost);
+        assert_eq!(config.hooks[1].commands, [MappableCommand::normal_mode]);
+
+        let invalid = r#"
+            [[hooks]]
+            event = "document-save"
+            commands = ["no_such_command"]
+        "#;
+        assert!(Config::load(Ok(&invalid.to_owned()), Err(ConfigLoadError::default())).is_err());
+    }
+
+    #[test]
+    fn parsing_commands() {
+        let config = Config::load_test(
+            r#"
+            [commands.open-test]
+            doc = "Open the tests of a module"
+            commands = [":open tests/%{arg}.rs", "goto_file_end"]
+
+            [commands.open-tests]
+            commands = [":open-test %{args}"]
+
+            [keys.normal]
+            X = ":open-test"
+
+            [[hooks]]
+            event = "document-open"
+            commands = [":open-tests"]
+            "#,
+        );
+        let command = &config.commands["open-test"];
+        assert_eq!(command.doc, "Open the tests of a module");
+        assert_eq!(
+            command.commands,
+            [
+                ":open tests/%{arg}.rs".parse::<MappableCommand>().unwrap(),
+                MappableCommand::goto_file_end
+            ]
+        );
+        match &config.keys[&Mode::Normal] {
+            KeyTrie::Node(node) => match &node[&KeyEvent::from_str("X").unwrap()] {
+                KeyTrie::MappableCommand(command) => {
+                    assert_eq!(command.doc(), "Open the tests of a module")
+                }
+                trie => panic!("expected a command, got {trie:?}"),
+            },
+            trie => panic!("expected a node, got {trie:?}"),
+        }
+
+        for invalid in [
+            "[commands.write]\ncommands = [\":sh true\"]",
+            "[commands.deploy]\ncommands = [\":no-such-command\"]",
+            "[commands.\"two words\"]\ncommands = []",
+            "[commands.a]\ncommands = [\":b\"]\n[commands.b]\ncommands = [\":a\"]",
+            "[keys.normal]\nX = \":no-such-command\"",
+            "[[hooks]]\nevent = \"document-open\"\ncommands = [\":no-such-command\"]",
+        ] {
+            let invalid = invalid.to_owned();
+            assert!(Config::load(Ok(&invalid), Err(ConfigLoadError::default())).is_err());
+        }
+    }
+
     #[test]
     fn keys_resolve_to_correct_defaults() {
         // From serde default
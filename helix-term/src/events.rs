@@ -1,9 +1,11 @@
 use helix_event::{events, register_event};
 use helix_view::document::Mode;
 use helix_view::events::{
-    ConfigDidChange, DiagnosticsDidChange, DocumentDidChange, DocumentDidClose, DocumentDidOpen,
-    DocumentFocusLost, LanguageServerExited, LanguageServerInitialized, SelectionDidChange,
+    ConfigDidChange, DiagnosticsDidChange, DocumentDidChange, DocumentDidChangeLanguage,
+    DocumentDidClose, DocumentDidOpen, DocumentDidSave, DocumentFocusLost, LanguageServerExited,
+    LanguageServerInitialized, SelectionDidChange,
 };
+use helix_view::Editor;
 
 use crate::commands;
 use crate::keymap::MappableCommand;
@@ -12,16 +14,20 @@
     OnModeSwitch<'a, 'cx> { old_mode: Mode, new_mode: Mode, cx: &'a mut commands::Context<'cx> }
     PostInsertChar<'a, 'cx> { c: char, cx: &'a mut commands::Context<'cx> }
     PostCommand<'a, 'cx> { command: & 'a MappableCommand, cx: &'a mut commands::Context<'cx> }
+    TerminalFocusLost<'a> { editor: &'a mut Editor }
 }
 
 pub fn register() {
     register_event::<OnModeSwitch>();
     register_event::<PostInsertChar>();
     register_event::<PostCommand>();
+    register_event::<TerminalFocusLost>();
     register_event::<DocumentDidOpen>();
     register_event::<DocumentDidChange>();
+    register_event::<DocumentDidSave>();
     register_event::<DocumentDidClose>();
     register_event::<DocumentFocusLost>();
+    register_event::<DocumentDidChangeLanguage>();
     register_event::<SelectionDidChange>();
     register_event::<DiagnosticsDidChange>();
     register_event::<LanguageServerInitialized>();
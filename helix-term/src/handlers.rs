@@ -8,20 +8,31 @@
 use crate::events;
 use crate::handlers::auto_save::AutoSaveHandler;
 use crate::handlers::diagnostics::PullDiagnosticsHandler;
+use crate::handlers::file_watcher::FileWatcherHandler;
+use crate::handlers::linter::LinterHandler;
 use crate::handlers::signature_help::SignatureHelpHandler;
 
 pub use helix_view::handlers::{word_index, Handlers};
 
+use self::code_lens::CodeLensHandler;
 use self::document_colors::DocumentColorsHandler;
 use self::document_links::DocumentLinksHandler;
+use self::semantic_tokens::SemanticTokensHandler;
 
 mod auto_save;
+pub mod blame;
+pub mod code_lens;
 pub mod completion;
+mod debug_console;
 pub mod diagnostics;
 mod document_colors;
 mod document_highlight;
 mod document_links;
+mod file_watcher;
+mod hooks;
+mod linter;
 mod prompt;
+pub mod semantic_tokens;
 mod signature_help;
 mod snippet;
 mod workspace_trust;
@@ -29,24 +40,34 @@
 pub fn setup(config: Arc<ArcSwap<Config>>) -> Handlers {
     events::register();
 
+    hooks::register_hooks(config.clone());
+
     let event_tx = completion::CompletionHandler::new(config).spawn();
     let signature_hints = SignatureHelpHandler::new().spawn();
     let auto_save = AutoSaveHandler::new().spawn();
     let document_colors = DocumentColorsHandler::default().spawn();
     let document_links = DocumentLinksHandler::default().spawn();
+    let semantic_tokens = SemanticTokensHandler::default().spawn();
+    let code_lens = CodeLensHandler::default().spawn();
     let word_index = word_index::Handler::spawn();
     let pull_diagnostics = PullDiagnosticsHandler::default().spawn();
     let pull_all_documents_diagnostics = PullAllDocumentsDiagnosticHandler::default().spawn();
+    let file_watcher = FileWatcherHandler::default().spawn();
+    let linters = LinterHandler::default().spawn();
 
     let handlers = Handlers {
         completions: helix_view::handlers::completion::CompletionHandler::new(event_tx),
         signature_hints,
         auto_save,
         document_colors,
+        semantic_tokens,
         document_links,
+        code_lens,
         word_index,
         pull_diagnostics,
         pull_all_documents_diagnostics,
+        file_watcher,
+        linters,
     };
 
     helix_view::handlers::register_hooks(&handlers);
@@ -54,10 +75,15 @@ pub fn setup(config: Arc<ArcSwap<Config>>) -> Handlers {
     signature_help::register_hooks(&handlers);
     document_highlight::register_hooks(&handlers);
     auto_save::register_hooks(&handlers);
+    blame::register_hooks(&handlers);
     diagnostics::register_hooks(&handlers);
+    linter::register_hooks(&handlers);
     snippet::register_hooks(&handlers);
     document_colors::register_hooks(&handlers);
+    semantic_tokens::register_hooks(&handlers);
     document_links::register_hooks(&handlers);
+    code_lens::register_hooks(&handlers);
+    debug_console::register_hooks(&handlers);
     prompt::register_hooks(&handlers);
     workspace_trust::register_hooks(&handlers);
     handlers
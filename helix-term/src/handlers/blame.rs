@@ -0,0 +1,76 @@
+use std::path::Path;
+
+use helix_event::register_hook;
+use helix_view::{
+    events::{ConfigDidChange, DocumentDidOpen},
+    handlers::Handlers,
+    DocumentId, Editor,
+};
+
+use crate::{compositor::Compositor, job};
+
+/// Blames the file of a document in the background and attaches the result to the
+/// document. The previous blame is dropped until the request finishes. `callback` is
+/// called once the blame is attached.
+pub(crate) fn request_blame<F>(editor: &mut Editor, doc_id: DocumentId, callback: F)
+where
+    F: FnOnce(&mut Editor, &mut Compositor) + Send + 'static,
+{
+    let Some(doc) = editor.document_mut(doc_id) else {
+        return;
+    };
+    let Some(path) = doc.path().map(Path::to_path_buf) else {
+        return;
+    };
+    doc.blame = None;
+    let diff_providers = editor.diff_providers.clone();
+
+    tokio::spawn(async move {
+        let blame = tokio::task::spawn_blocking(move || diff_providers.blame(&path))
+            .await
+            .ok()
+            .flatten();
+        job::dispatch(move |editor, compositor| {
+            if let Some(doc) = editor.document_mut(doc_id) {
+                doc.blame = blame;
+            }
+            callback(editor, compositor);
+        })
+        .await;
+    });
+}
+
+/// Blames the document again after its diff base was updated if inline blame is
+/// enabled or the document was blamed before.
+pub(crate) fn refresh_blame(editor: &mut Editor, doc_id: DocumentId) {
+    let blamed = editor
+        .document(doc_id)
+        .is_some_and(|doc| doc.blame.is_some());
+    if blamed || editor.config().inline_blame.enable {
+        request_blame(editor, doc_id, |_, _| ());
+    }
+}
+
+pub(super) fn register_hooks(_handlers: &Handlers) {
+    register_hook!(move |event: &mut DocumentDidOpen<'_>| {
+        if event.editor.config().inline_blame.enable {
+            request_blame(event.editor, event.doc, |_, _| ());
+        }
+        Ok(())
+    });
+
+    register_hook!(move |event: &mut ConfigDidChange<'_>| {
+        if event.new.inline_blame.enable && !event.old.inline_blame.enable {
+            let doc_ids: Vec<_> = event
+                .editor
+                .documents()
+                .filter(|doc| doc.blame.is_none())
+                .map(|doc| doc.id())
+                .collect();
+            for doc_id in doc_ids {
+                request_blame(event.editor, doc_id, |_, _| ());
+            }
+        }
+        Ok(())
+    });
+}
@@ -0,0 +1,199 @@
+use std::{collections::HashSet, future::Future, time::Duration};
+
+use futures_util::{future::join_all, stream::FuturesUnordered, StreamExt};
+use helix_core::{syntax::config::LanguageServerFeature, Assoc};
+use helix_event::{cancelable_future, register_hook};
+use helix_view::{
+    document::DocumentCodeLens,
+    events::{DocumentDidChange, DocumentDidOpen, LanguageServerExited, LanguageServerInitialized},
+    handlers::{lsp::CodeLensEvent, Handlers},
+    DocumentId, Editor,
+};
+use tokio::time::Instant;
+
+use crate::job;
+
+#[derive(Default)]
+pub(super) struct CodeLensHandler {
+    docs: HashSet<DocumentId>,
+}
+
+const DOCUMENT_CHANGE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+impl helix_event::AsyncHook for CodeLensHandler {
+    type Event = CodeLensEvent;
+
+    fn handle_event(&mut self, event: Self::Event, _timeout: Option<Instant>) -> Option<Instant> {
+        let CodeLensEvent(doc_id) = event;
+        self.docs.insert(doc_id);
+        Some(Instant::now() + DOCUMENT_CHANGE_DEBOUNCE)
+    }
+
+    fn finish_debounce(&mut self) {
+        let docs = std::mem::take(&mut self.docs);
+
+        job::dispatch_blocking(move |editor, _compositor| {
+            for doc in docs {
+                request_code_lenses(editor, doc);
+            }
+        });
+    }
+}
+
+/// Requests the code lenses of a document from every language server supporting them.
+///
+/// Lenses without a command are resolved if the server supports it, unresolved lenses
+/// are kept but can't be displayed or executed.
+pub(crate) fn code_lens_requests(
+    editor: &Editor,
+    doc_id: DocumentId,
+) -> FuturesUnordered<impl Future<Output = anyhow::Result<Vec<DocumentCodeLens>>>> {
+    let Some(doc) = editor.documents.get(&doc_id) else {
+        return FuturesUnordered::new();
+    };
+
+    let mut seen_language_servers = HashSet::new();
+    doc.language_servers_with_feature(LanguageServerFeature::CodeLens)
+        .filter(|ls| seen_language_servers.insert(ls.id()))
+        .filter_map(|language_server| {
+            let language_server = editor.language_servers.get_by_id(language_server.id())?;
+            let language_server = language_server.clone();
+            let text = doc.text().clone();
+            let future = language_server.text_document_code_lens(doc.identifier())?;
+
+            Some(async move {
+                let lenses = future.await?.unwrap_or_default();
+                let lenses = join_all(lenses.into_iter().map(|lens| {
+                    let resolve = if lens.command.is_none() {
+                        language_server.resolve_code_lens(&lens)
+                    } else {
+                        None
+                    };
+                    async move {
+                        let Some(resolve) = resolve else {
+                            return lens;
+                        };
+                        resolve.await.unwrap_or_else(|err| {
+                            log::error!("code lens resolve request failed: {err}");
+                            lens
+                        })
+                    }
+                }))
+                .await;
+
+                let offset_encoding = language_server.offset_encoding();
+                let language_server_id = language_server.id();
+                let lenses = lenses
+                    .into_iter()
+                    .filter_map(|lens| {
+                        let start = helix_lsp::util::lsp_pos_to_pos(
+                            &text,
+                            lens.range.start,
+                            offset_encoding,
+                        )?;
+                        Some(DocumentCodeLens {
+                            start,
+                            lens,
+                            language_server_id,
+                        })
+                    })
+                    .collect();
+                anyhow::Ok(lenses)
+            })
+        })
+        .collect()
+}
+
+/// Request the code lenses of a document and attach them for display.
+pub(crate) fn request_code_lenses(editor: &mut Editor, doc_id: DocumentId) {
+    if !editor.config().lsp.display_code_lenses {
+        return;
+    }
+
+    let Some(doc) = editor.document_mut(doc_id) else {
+        return;
+    };
+    let cancel = doc.code_lens_controller.restart();
+
+    let mut futures = code_lens_requests(editor, doc_id);
+    if futures.is_empty() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut all_lenses = Vec::new();
+        loop {
+            match cancelable_future(futures.next(), &cancel).await {
+                Some(Some(Ok(items))) => all_lenses.extend(items),
+                Some(Some(Err(err))) => log::error!("code lens request failed: {err}"),
+                Some(None) => break,
+                None => return,
+            }
+        }
+
+        job::dispatch(move |editor, _| attach_code_lenses(editor, doc_id, all_lenses)).await;
+    });
+}
+
+fn attach_code_lenses(editor: &mut Editor, doc_id: DocumentId, mut lenses: Vec<DocumentCodeLens>) {
+    let Some(doc) = editor.documents.get_mut(&doc_id) else {
+        return;
+    };
+
+    lenses.sort_by_key(|lens| lens.start);
+    doc.code_lenses = lenses;
+    doc.refresh_code_lens_lines();
+}
+
+pub(super) fn register_hooks(handlers: &Handlers) {
+    register_hook!(move |event: &mut DocumentDidOpen<'_>| {
+        request_code_lenses(event.editor, event.doc);
+        Ok(())
+    });
+
+    let tx = handlers.code_lens.clone();
+    register_hook!(move |event: &mut DocumentDidChange<'_>| {
+        event.changes.update_positions(
+            event
+                .doc
+                .code_lenses
+                .iter_mut()
+                .map(|lens| (&mut lens.start, Assoc::After)),
+        );
+        event.doc.refresh_code_lens_lines();
+
+        if !event.ghost_transaction {
+            event.doc.code_lens_controller.cancel();
+            helix_event::send_blocking(&tx, CodeLensEvent(event.doc.id()));
+        }
+
+        Ok(())
+    });
+
+    register_hook!(move |event: &mut LanguageServerInitialized<'_>| {
+        let doc_ids: Vec<_> = event.editor.documents().map(|doc| doc.id()).collect();
+
+        for doc_id in doc_ids {
+            request_code_lenses(event.editor, doc_id);
+        }
+
+        Ok(())
+    });
+
+    register_hook!(move |event: &mut LanguageServerExited<'_>| {
+        for doc in event.editor.documents_mut() {
+            if doc.supports_language_server(event.server_id) {
+                doc.code_lenses.clear();
+                doc.refresh_code_lens_lines();
+            }
+        }
+
+        let doc_ids: Vec<_> = event.editor.documents().map(|doc| doc.id()).collect();
+
+        for doc_id in doc_ids {
+            request_code_lenses(event.editor, doc_id);
+        }
+
+        Ok(())
+    });
+}
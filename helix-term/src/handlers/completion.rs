@@ -26,6 +26,7 @@
 pub use request::CompletionHandler;
 pub use resolve::ResolveHandler;
 
+mod debugger;
 mod item;
 mod path;
 mod request;
@@ -132,7 +133,19 @@ pub fn trigger_auto_completion(editor: &Editor, trigger_char_only: bool) {
                         trigger_characters: Some(triggers),
                         ..
                     }) if triggers.iter().any(|trigger| text.ends_with(trigger)))
-        });
+        })
+        || (editor.debug_console == Some(doc.id())
+            && editor
+                .debug_adapters
+                .get_active_client()
+                .and_then(|debugger| {
+                    debugger
+                        .caps
+                        .as_ref()?
+                        .completion_trigger_characters
+                        .as_ref()
+                })
+                .is_some_and(|triggers| triggers.iter().any(|trigger| text.ends_with(trigger))));
 
     let cursor_char = text
         .get_bytes_at(text.len_bytes())
@@ -0,0 +1,111 @@
+use std::{borrow::Cow, future::Future, sync::Arc};
+
+use helix_core::{self as core, chars::char_is_word, completion::CompletionProvider, Transaction};
+use helix_lsp::{
+    lsp,
+    util::{lsp_pos_to_pos, pos_to_lsp_pos},
+    OffsetEncoding,
+};
+use helix_view::{document::SavePoint, handlers::completion::ResponseContext, Editor};
+
+use super::{CompletionItem, CompletionItems, CompletionResponse};
+
+const COMPLETION_KIND: &str = "debugger";
+
+/// Requests completions for the line at the cursor from the active debugger when
+/// typing in the debug console.
+pub(super) fn completion(
+    editor: &Editor,
+    savepoint: Arc<SavePoint>,
+) -> Option<impl Future<Output = CompletionResponse>> {
+    let (view, doc) = current_ref!(editor);
+    if editor.debug_console != Some(doc.id()) {
+        return None;
+    }
+    let debugger = editor.debug_adapters.get_active_client()?;
+    if !debugger
+        .caps
+        .as_ref()
+        .and_then(|caps| caps.supports_completions_request)
+        .unwrap_or_default()
+    {
+        return None;
+    }
+
+    let rope = doc.text().clone();
+    let text = rope.slice(..);
+    let cursor = doc.selection(view.id).primary().cursor(text);
+    // DAP columns are UTF-16 code units and the client is initialized with
+    // 1-based columns
+    let position = pos_to_lsp_pos(&rope, cursor, OffsetEncoding::Utf16);
+    let line = position.line as usize;
+    let line_start = text.line_to_char(line);
+    let line_text = text
+        .line(line)
+        .chars()
+        .take_while(|&ch| ch != '\n' && ch != '\r')
+        .collect();
+    let word_start = cursor
+        - text
+            .chars_at(cursor)
+            .reversed()
+            .take_while(|&ch| char_is_word(ch))
+            .count();
+
+    let frame_id = debugger.current_stack_frame().map(|frame| frame.id);
+    let request = debugger.completions(line_text, position.character as usize + 1, frame_id);
+
+    Some(async move {
+        let mut targets = request
+            .await
+            .inspect_err(|err| log::error!("debugger completion request failed: {err}"))
+            .unwrap_or_default();
+        targets.sort_by(|item1, item2| {
+            let sort_text1 = item1.sort_text.as_deref().unwrap_or(&item1.label);
+            let sort_text2 = item2.sort_text.as_deref().unwrap_or(&item2.label);
+            sort_text1.cmp(sort_text2)
+        });
+
+        let to_pos = |column: usize| {
+            let position = lsp::Position::new(line as u32, column.saturating_sub(1) as u32);
+            lsp_pos_to_pos(&rope, position, OffsetEncoding::Utf16)
+        };
+        let items = targets
+            .into_iter()
+            .filter_map(|item| {
+                // Without a start the text replaces the word before the cursor
+                let (from, to) = match item.start {
+                    Some(start) => {
+                        let from = to_pos(start)?;
+                        let to = to_pos(start + item.length.unwrap_or_default())?;
+                        (from, to)
+                    }
+                    None => (word_start, cursor),
+                };
+                if from < line_start {
+                    return None;
+                }
+                let insert = item.text.unwrap_or_else(|| item.label.clone());
+                let transaction =
+                    Transaction::change(&rope, std::iter::once((from, to, Some(insert.into()))));
+                Some(CompletionItem::Other(core::CompletionItem {
+                    transaction,
+                    label: item.label.into(),
+                    kind: item.ty.map_or(Cow::Borrowed(COMPLETION_KIND), Cow::Owned),
+                    documentation: item.detail,
+                    provider: CompletionProvider::Debugger,
+                }))
+            })
+            .collect();
+
+        CompletionResponse {
+            items: CompletionItems::Other(items),
+            provider: CompletionProvider::Debugger,
+            context: ResponseContext {
+                is_incomplete: false,
+                priority: 0,
+                savepoint,
+            },
+        }
+    })
+}
@@ -28,7 +28,7 @@
 use crate::ui;
 use crate::ui::editor::InsertEvent;
 
-use super::word;
+use super::{debugger, word};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub(super) enum TriggerKind {
@@ -248,6 +248,9 @@ fn request_completions(
     ) {
         requests.spawn_blocking(path_completion_request);
     }
+    if let Some(debugger_completion_request) = debugger::completion(editor, savepoint.clone()) {
+        requests.spawn(debugger_completion_request);
+    }
     if let Some(word_completion_request) =
         word::completion(editor, trigger, handle.clone(), savepoint)
     {
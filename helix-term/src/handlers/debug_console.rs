@@ -0,0 +1,57 @@
+use helix_event::register_hook;
+use helix_view::{handlers::Handlers, Editor};
+
+use crate::events::PostCommand;
+use crate::job;
+use crate::keymap::MappableCommand;
+
+/// Evaluates the expression on the line before the cursor if a new line was
+/// just started at the end of the debug console.
+fn evaluate_input(editor: &mut Editor) {
+    let (view, doc) = current_ref!(editor);
+    if editor.debug_console != Some(doc.id()) {
+        return;
+    }
+
+    let text = doc.text().slice(..);
+    let cursor = doc.selection(view.id).primary().cursor(text);
+    let last_line = text.len_lines() - 1;
+    if last_line == 0 || text.char_to_line(cursor) != last_line {
+        return;
+    }
+    let expression = text.line(last_line - 1).to_string();
+    let expression = expression.trim();
+    if expression.is_empty() {
+        return;
+    }
+
+    let Some(debugger) = editor.debug_adapters.get_active_client() else {
+        editor.set_error("No active debug session");
+        return;
+    };
+    let frame_id = debugger.current_stack_frame().map(|frame| frame.id);
+    let request = debugger.repl_eval(expression.to_owned(), frame_id);
+
+    tokio::spawn(async move {
+        let output = match request.await {
+            Ok(response) => response.result,
+            Err(err) => err.to_string(),
+        };
+        job::dispatch(move |editor, _| editor.append_to_debug_console(&output)).await;
+    });
+}
+
+pub(super) fn register_hooks(_handlers: &Handlers) {
+    register_hook!(move |event: &mut PostCommand<'_, '_>| {
+        if matches!(
+            event.command,
+            MappableCommand::Static {
+                name: "insert_newline",
+                ..
+            }
+        ) {
+            evaluate_input(event.cx.editor);
+        }
+        Ok(())
+    });
+}
@@ -195,16 +195,12 @@ fn request_document_diagnostics_for_language_severs(
                     }
                 });
 
-            let provider = DiagnosticProvider::Lsp {
-                server_id: language_server_id,
-                identifier,
-            };
             let uri = doc.uri()?;
 
             Some(async move {
                 let result = future.await;
 
-                (result, provider, uri)
+                (result, language_server_id, identifier, uri)
             })
         })
         .collect();
@@ -217,13 +213,17 @@ fn request_document_diagnostics_for_language_severs(
         let mut retry_language_servers = HashSet::new();
         loop {
             match cancelable_future(futures.next(), &cancel).await {
-                Some(Some((Ok(result), provider, uri))) => {
+                Some(Some((Ok(result), server_id, identifier, uri))) => {
+                    let provider = DiagnosticProvider::Lsp {
+                        server_id,
+                        identifier,
+                    };
                     job::dispatch(move |editor, _| {
                         handle_pull_diagnostics_response(editor, result, provider, uri, doc_id);
                     })
                     .await;
                 }
-                Some(Some((Err(err), DiagnosticProvider::Lsp { server_id, .. }, _))) => {
+                Some(Some((Err(err), server_id, _, _))) => {
                     let parsed_cancellation_data = if let helix_lsp::Error::Rpc(error) = err {
                         error.data.and_then(|data| {
                             serde_json::from_value::<lsp::DiagnosticServerCancellationData>(data)
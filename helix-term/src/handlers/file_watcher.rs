@@ -0,0 +1,198 @@
+use std::{collections::HashMap, path::PathBuf, time::Duration};
+
+use helix_core::{diff::unified_diff, Rope};
+use helix_event::send_blocking;
+use helix_lsp::lsp;
+use helix_view::{
+    document::{contents_differ, from_reader},
+    editor::Action,
+    file_watcher::{FileEvent, FileEventKind},
+    Document, DocumentId, Editor,
+};
+use tokio::time::Instant;
+
+use crate::{commands::typed::reload_document, compositor::Compositor, job, ui};
+
+/// Time to collect events for before handling them. Writing a file usually
+/// produces a few events in quick succession.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Default)]
+pub(super) struct FileWatcherHandler {
+    events: HashMap<PathBuf, FileEventKind>,
+}
+
+impl helix_event::AsyncHook for FileWatcherHandler {
+    type Event = FileEvent;
+
+    fn handle_event(&mut self, event: Self::Event, timeout: Option<Instant>) -> Option<Instant> {
+        let FileEvent { path, kind } = event;
+        self.events
+            .entry(path)
+            .and_modify(|old| *old = old.merge(kind))
+            .or_insert(kind);
+        // Don't extend the timeout so that a steady stream of changes is still
+        // handled periodically.
+        Some(timeout.unwrap_or_else(|| Instant::now() + DEBOUNCE))
+    }
+
+    fn finish_debounce(&mut self) {
+        let events = std::mem::take(&mut self.events);
+        job::dispatch_blocking(move |editor, _| handle_events(editor, events));
+    }
+}
+
+fn handle_events(editor: &mut Editor, events: HashMap<PathBuf, FileEventKind>) {
+    if editor.write_count > 0 {
+        // Our own writes would be mistaken for external changes until the
+        // documents pick up the new modification time, try again later.
+        for (path, kind) in events {
+            send_blocking(&editor.handlers.file_watcher, FileEvent { path, kind });
+        }
+        return;
+    }
+
+    // Documents whose files were written to. Reading the files to find out whether their
+    // contents changed is done in the background.
+    let mut written = Vec::new();
+    for (path, kind) in events {
+        let typ = match kind {
+            FileEventKind::Created => lsp::FileChangeType::CREATED,
+            FileEventKind::Changed => lsp::FileChangeType::CHANGED,
+            FileEventKind::Deleted => lsp::FileChangeType::DELETED,
+        };
+        editor
+            .language_servers
+            .file_event_handler
+            .file_event(path.clone(), typ);
+
+        if kind == FileEventKind::Deleted {
+            continue;
+        }
+        let Some(doc) = editor.document_by_path(&path) else {
+            continue;
+        };
+        if doc.modified_on_disk() {
+            written.push((doc.id(), path, doc.encoding(), doc.text().clone()));
+        }
+    }
+    if written.is_empty() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let changes = tokio::task::spawn_blocking(move || {
+            written
+                .into_iter()
+                .map(|(doc_id, path, encoding, text)| {
+                    (doc_id, contents_differ(&path, encoding, &text))
+                })
+                .collect::<Vec<_>>()
+        })
+        .await;
+        let Ok(changes) = changes else {
+            return;
+        };
+        job::dispatch(move |editor, compositor| handle_changes(editor, compositor, changes)).await;
+    });
+}
+
+/// Reloads the documents whose files changed on disk or asks what to do if they have unsaved
+/// changes. `changes` contains whether the contents of the file of each document changed.
+fn handle_changes(
+    editor: &mut Editor,
+    compositor: &mut Compositor,
+    changes: Vec<(DocumentId, bool)>,
+) {
+    for (doc_id, changed) in changes {
+        let Some(doc) = editor.document_mut(doc_id) else {
+            continue;
+        };
+        if !changed {
+            doc.pickup_last_saved_time();
+            continue;
+        }
+
+        if doc.is_modified() {
+            // Only prompt once per change: writing the document after choosing to
+            // keep it overwrites the file without an error.
+            doc.pickup_last_saved_time();
+            let message = format!(
+                "'{}' was changed on disk but the buffer has unsaved changes.",
+                doc.display_name()
+            );
+            compositor.push(Box::new(conflict_select(message, doc_id)));
+        } else if let Err(err) = reload_document(editor, doc_id) {
+            editor.set_error(format!("Failed to reload changed file: {err}"));
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ConflictAction {
+    Reload,
+    Keep,
+    Diff,
+}
+
+fn conflict_select(message: String, doc_id: DocumentId) -> ui::Select<ConflictAction> {
+    ui::Select::new(
+        message,
+        [
+            ConflictAction::Reload,
+            ConflictAction::Keep,
+            ConflictAction::Diff,
+        ],
+        (),
+        move |editor, option, event| {
+            if event != ui::PromptEvent::Validate || !editor.documents.contains_key(&doc_id) {
+                return;
+            }
+            let result = match option {
+                ConflictAction::Reload => reload_document(editor, doc_id),
+                ConflictAction::Keep => Ok(()),
+                ConflictAction::Diff => open_diff(editor, doc_id),
+            };
+            if let Err(err) = result {
+                editor.set_error(err.to_string());
+            }
+        },
+    )
+}
+
+/// Opens a scratch buffer with the changes between the file on disk and the buffer.
+fn open_diff(editor: &mut Editor, doc_id: DocumentId) -> anyhow::Result<()> {
+    let doc = doc!(editor, &doc_id);
+    let Some(path) = doc.path() else {
+        return Ok(());
+    };
+    let mut file = std::fs::File::open(path)?;
+    let (on_disk, ..) = from_reader(&mut file, Some(doc.encoding()))?;
+    let diff = unified_diff(&on_disk, doc.text());
+
+    let mut diff_doc = Document::from(
+        Rope::from(diff),
+        None,
+        editor.config.clone(),
+        editor.syn_loader.clone(),
+    );
+    let loader = editor.syn_loader.load();
+    if let Err(err) = diff_doc.set_language_by_language_id("diff", &loader) {
+        log::debug!("failed to highlight diff: {err}");
+    }
+    editor.new_file_from_document(Action::VerticalSplit, diff_doc);
+    Ok(())
+}
+
+impl ui::menu::Item for ConflictAction {
+    type Data = ();
+
+    fn format(&self, _data: &Self::Data) -> tui::widgets::Row<'_> {
+        match self {
+            ConflictAction::Reload => "Reload from disk",
+            ConflictAction::Keep => "Keep buffer",
+            ConflictAction::Diff => "Show diff",
+        }
+        .into()
+    }
+}
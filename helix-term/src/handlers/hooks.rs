@@ -0,0 +1,205 @@
+//! Runs the commands of the `[[hooks]]` configured for an event.
+
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use helix_event::{register_hook, runtime_local};
+use helix_view::events::{
+    DocumentDidChangeLanguage, DocumentDidClose, DocumentDidOpen, DocumentDidSave,
+    DocumentFocusLost,
+};
+use helix_view::{Document, DocumentId, Editor, ViewId};
+use parking_lot::Mutex;
+
+use crate::commands;
+use crate::compositor::{self, Compositor};
+use crate::config::{Config, HookConfig, HookEvent};
+use crate::events::{OnModeSwitch, TerminalFocusLost};
+use crate::job;
+use crate::keymap::MappableCommand;
+
+/// The event and document of a hook.
+type HookKey = (HookEvent, Option<DocumentId>);
+
+struct RunningHooks {
+    /// The hook whose commands are running.
+    running: Option<HookKey>,
+    /// The writes started by the commands of `document-save` hooks which haven't finished.
+    save_writes: usize,
+}
+
+runtime_local! {
+    /// Hooks don't run for the events caused by the commands of a hook for the same event
+    /// and document, which would run them again endlessly. Writes finish after the
+    /// commands ran, so saves started by `document-save` hooks are counted instead.
+    static RUNNING_HOOKS: Mutex<RunningHooks> = Mutex::new(RunningHooks {
+        running: None,
+        save_writes: 0,
+    });
+}
+
+pub(super) fn register_hooks(config: Arc<ArcSwap<Config>>) {
+    let hooks = config.clone();
+    register_hook!(move |event: &mut DocumentDidOpen<'_>| {
+        run_doc_hooks(&hooks, HookEvent::DocumentOpen, event.editor, event.doc);
+        Ok(())
+    });
+
+    let hooks = config.clone();
+    register_hook!(move |event: &mut DocumentDidSave<'_>| {
+        if !is_save_hook_write(event.editor) {
+            run_doc_hooks(&hooks, HookEvent::DocumentSave, event.editor, event.doc);
+        }
+        Ok(())
+    });
+
+    let hooks = config.clone();
+    register_hook!(move |event: &mut DocumentDidClose<'_>| {
+        // The document is gone, the commands run in the focused view.
+        run_hooks(&hooks, HookEvent::DocumentClose, Some(&event.doc), None);
+        Ok(())
+    });
+
+    let hooks = config.clone();
+    register_hook!(move |event: &mut DocumentFocusLost<'_>| {
+        run_doc_hooks(
+            &hooks,
+            HookEvent::DocumentFocusLost,
+            event.editor,
+            event.doc,
+        );
+        Ok(())
+    });
+
+    let hooks = config.clone();
+    register_hook!(move |event: &mut DocumentDidChangeLanguage<'_>| {
+        run_doc_hooks(&hooks, HookEvent::LanguageChange, event.editor, event.doc);
+        Ok(())
+    });
+
+    let hooks = config.clone();
+    register_hook!(move |event: &mut OnModeSwitch<'_, '_>| {
+        let doc = event.cx.editor.tree.try_get(event.cx.editor.tree.focus);
+        let doc = doc.and_then(|view| event.cx.editor.document(view.doc));
+        run_hooks(&hooks, HookEvent::ModeSwitch, doc, None);
+        Ok(())
+    });
+
+    let hooks = config;
+    register_hook!(move |event: &mut TerminalFocusLost<'_>| {
+        let doc = event.editor.tree.try_get(event.editor.tree.focus);
+        let doc = doc.and_then(|view| event.editor.document(view.doc));
+        run_hooks(&hooks, HookEvent::FocusLost, doc, None);
+        Ok(())
+    });
+}
+
+fn run_doc_hooks(config: &ArcSwap<Config>, event: HookEvent, editor: &Editor, doc: DocumentId) {
+    run_hooks(config, event, editor.document(doc), Some(doc));
+}
+
+/// Schedules the commands of the hooks for `event` that match `doc`. The commands run
+/// after the event was handled, in a view of `target` if given or in the focused view
+/// otherwise.
+fn run_hooks(
+    config: &ArcSwap<Config>,
+    event: HookEvent,
+    doc: Option<&Document>,
+    target: Option<DocumentId>,
+) {
+    let key = (event, doc.map(Document::id));
+    if RUNNING_HOOKS.lock().running == Some(key) {
+        return;
+    }
+    let config = config.load();
+    let commands: Vec<MappableCommand> = config
+        .hooks
+        .iter()
+        .filter(|hook| hook.event == event && matches(hook, doc))
+        .flat_map(|hook| hook.commands.iter().cloned())
+        .collect();
+    if commands.is_empty() {
+        return;
+    }
+
+    job::dispatch_context_blocking(move |cx, compositor| {
+        let Some(target) = target else {
+            execute_hook_commands(cx, compositor, key, &commands);
+            return;
+        };
+        // Documents that were closed in the meantime or aren't displayed have no view to
+        // run the commands in.
+        let focus = cx.editor.tree.focus;
+        let Some(view) = doc_view(cx.editor, target) else {
+            return;
+        };
+        cx.editor.tree.focus = view;
+        execute_hook_commands(cx, compositor, key, &commands);
+        // Restore the focus unless the commands moved it.
+        if cx.editor.tree.focus == view && cx.editor.tree.contains(focus) {
+            cx.editor.tree.focus = focus;
+        }
+    });
+}
+
+fn execute_hook_commands(
+    cx: &mut compositor::Context,
+    compositor: &mut Compositor,
+    key: HookKey,
+    commands: &[MappableCommand],
+) {
+    let write_count = cx.editor.write_count;
+    RUNNING_HOOKS.lock().running = Some(key);
+    commands::execute_commands(cx, compositor, commands, None);
+
+    let mut hooks = RUNNING_HOOKS.lock();
+    hooks.running = None;
+    if key.0 == HookEvent::DocumentSave {
+        hooks.save_writes += cx.editor.write_count.saturating_sub(write_count);
+    }
+}
+
+/// Returns whether a finished write was started by the commands of a `document-save` hook.
+fn is_save_hook_write(editor: &Editor) -> bool {
+    let mut hooks = RUNNING_HOOKS.lock();
+    // Failed writes aren't reported, at most the writes which didn't finish before this one
+    // can be left.
+    hooks.save_writes = hooks.save_writes.min(editor.write_count + 1);
+    if hooks.save_writes == 0 {
+        return false;
+    }
+    hooks.save_writes -= 1;
+    true
+}
+
+/// Returns the focused view if it displays `doc` or else any view that displays it.
+fn doc_view(editor: &Editor, doc: DocumentId) -> Option<ViewId> {
+    let focus = editor.tree.get(editor.tree.focus);
+    if focus.doc == doc {
+        return Some(focus.id);
+    }
+    editor
+        .tree
+        .views()
+        .find(|(view, _)| view.doc == doc)
+        .map(|(view, _)| view.id)
+}
+
+fn matches(hook: &HookConfig, doc: Option<&Document>) -> bool {
+    if let Some(language) = &hook.language {
+        if doc.and_then(Document::language_name) != Some(language.as_str()) {
+            return false;
+        }
+    }
+    if let Some(glob) = &hook.glob {
+        let Some(path) = doc.and_then(Document::path) else {
+            return false;
+        };
+        let glob = glob.compile_matcher();
+        let relative = helix_stdx::path::get_relative_path(path);
+        if !glob.is_match(path) && !glob.is_match(relative) {
+            return false;
+        }
+    }
+    true
+}
@@ -0,0 +1,225 @@
+//! Runs the external linters configured for a language and publishes their output as
+//! diagnostics next to the diagnostics of the language servers.
+
+use std::collections::HashSet;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::Context as _;
+use helix_core::command_line::Token;
+use helix_core::diagnostic::{DiagnosticProvider, Severity};
+use helix_core::linter::{parse_output, LinterDiagnostic};
+use helix_core::syntax::config::{LinterConfiguration, LinterFormat, LinterInput, LinterTrigger};
+use helix_core::{encoding, Rope};
+use helix_event::{register_hook, send_blocking};
+use helix_lsp::lsp;
+use helix_view::document::to_writer;
+use helix_view::events::{DocumentDidChange, DocumentDidOpen, DocumentDidSave};
+use helix_view::expansion;
+use helix_view::handlers::{Handlers, LintEvent};
+use helix_view::{DocumentId, Editor};
+use tokio::time::Instant;
+
+use crate::job;
+
+pub(super) fn register_hooks(handlers: &Handlers) {
+    register_hook!(move |event: &mut DocumentDidOpen<'_>| {
+        run_linters(event.editor, event.doc, None);
+        Ok(())
+    });
+
+    register_hook!(move |event: &mut DocumentDidSave<'_>| {
+        run_linters(event.editor, event.doc, None);
+        Ok(())
+    });
+
+    let tx = handlers.linters.clone();
+    register_hook!(move |event: &mut DocumentDidChange<'_>| {
+        let runs_on_change = event.doc.language_config().is_some_and(|config| {
+            config
+                .linters
+                .iter()
+                .any(|linter| linter.trigger == LinterTrigger::Idle)
+        });
+        if runs_on_change && !event.ghost_transaction {
+            send_blocking(
+                &tx,
+                LintEvent {
+                    document_id: event.doc.id(),
+                },
+            );
+        }
+        Ok(())
+    });
+}
+
+#[derive(Debug, Default)]
+pub(super) struct LinterHandler {
+    document_ids: HashSet<DocumentId>,
+}
+
+impl helix_event::AsyncHook for LinterHandler {
+    type Event = LintEvent;
+
+    fn handle_event(&mut self, event: Self::Event, _timeout: Option<Instant>) -> Option<Instant> {
+        self.document_ids.insert(event.document_id);
+        Some(Instant::now() + Duration::from_millis(500))
+    }
+
+    fn finish_debounce(&mut self) {
+        let document_ids = mem::take(&mut self.document_ids);
+        job::dispatch_blocking(move |editor, _| {
+            for document_id in document_ids {
+                run_linters(editor, document_id, Some(LinterTrigger::Idle));
+            }
+        })
+    }
+}
+
+/// Runs the linters of a document in the background, only those with `trigger` if set.
+fn run_linters(editor: &Editor, doc_id: DocumentId, trigger: Option<LinterTrigger>) {
+    let Some(doc) = editor.document(doc_id) else {
+        return;
+    };
+    let (Some(config), Some(uri)) = (doc.language_config(), doc.uri()) else {
+        return;
+    };
+
+    for linter in &config.linters {
+        if trigger.is_some_and(|trigger| linter.trigger != trigger)
+            || (linter.input == LinterInput::File && doc.is_modified())
+        {
+            continue;
+        }
+        let args = match linter
+            .args
+            .iter()
+            .map(|arg| expansion::expand(editor, Token::expand(arg)).map(|arg| arg.into_owned()))
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(args) => args,
+            Err(err) => {
+                log::error!(
+                    "Failed to expand arguments of linter '{}': {err}",
+                    linter.name
+                );
+                continue;
+            }
+        };
+
+        let linter = linter.clone();
+        let path = doc.path().map(Path::to_path_buf);
+        let text = doc.text().clone();
+        let version = doc.version();
+        let uri = uri.clone();
+        tokio::spawn(async move {
+            let diagnostics = match lint(&linter, args, path, text).await {
+                Ok(diagnostics) => diagnostics,
+                Err(err) => {
+                    log::error!("Linter '{}' failed: {err:#}", linter.name);
+                    return;
+                }
+            };
+            let provider = DiagnosticProvider::Linter {
+                name: linter.name.as_str().into(),
+            };
+            job::dispatch(move |editor, _| {
+                editor.handle_lsp_diagnostics(&provider, uri, Some(version), diagnostics)
+            })
+            .await;
+        });
+    }
+}
+
+async fn lint(
+    linter: &LinterConfiguration,
+    args: Vec<String>,
+    path: Option<PathBuf>,
+    text: Rope,
+) -> anyhow::Result<Vec<lsp::Diagnostic>> {
+    let mut process = tokio::process::Command::new(helix_stdx::env::which(&linter.command)?);
+    process.args(args);
+    if let Some(dir) = path.as_deref().and_then(Path::parent) {
+        process.current_dir(dir);
+    }
+    let stdin = match linter.input {
+        LinterInput::Stdin => Stdio::piped(),
+        LinterInput::File => {
+            process.arg(path.context("the document has no path")?);
+            Stdio::null()
+        }
+    };
+    process
+        .stdin(stdin)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let mut child = process.spawn().context("failed to spawn")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        tokio::spawn(async move {
+            // Dropping `stdin` closes the pipe so the linter doesn't wait for more input.
+            to_writer(&mut stdin, (encoding::UTF_8, false), &text).await
+        });
+    }
+    let output = tokio::time::timeout(
+        Duration::from_secs(linter.timeout),
+        child.wait_with_output(),
+    )
+    .await
+    .context("timed out")??;
+
+    // Many linters exit with an error status when they find problems so the status is
+    // ignored. Linters with a line based format may print diagnostics to either stream.
+    let mut diagnostics = parse_output(linter, &String::from_utf8_lossy(&output.stdout))?;
+    if matches!(linter.format, LinterFormat::Regex { .. }) {
+        diagnostics.extend(parse_output(
+            linter,
+            &String::from_utf8_lossy(&output.stderr),
+        )?);
+    }
+    Ok(diagnostics
+        .into_iter()
+        .map(|diagnostic| lsp_diagnostic(linter, diagnostic))
+        .collect())
+}
+
+/// Converts a diagnostic of `linter` to a diagnostic positioned in characters.
+fn lsp_diagnostic(linter: &LinterConfiguration, diagnostic: LinterDiagnostic) -> lsp::Diagnostic {
+    let position = |line: usize, column: usize| {
+        lsp::Position::new(
+            line.saturating_sub(1) as u32,
+            column.saturating_sub(1) as u32,
+        )
+    };
+    let start = position(diagnostic.line, diagnostic.column.unwrap_or(1));
+    let end = match (
+        diagnostic.column,
+        diagnostic.end_line,
+        diagnostic.end_column,
+    ) {
+        (_, end_line, Some(end_column)) => {
+            position(end_line.unwrap_or(diagnostic.line), end_column)
+        }
+        // Positions past the end of a line are clamped to the end of the line.
+        (_, Some(end_line), None) => position(end_line, usize::MAX),
+        (None, None, None) => position(diagnostic.line, usize::MAX),
+        (Some(_), None, None) => start,
+    };
+    let severity = diagnostic.severity.map(|severity| match severity {
+        Severity::Error => lsp::DiagnosticSeverity::ERROR,
+        Severity::Warning => lsp::DiagnosticSeverity::WARNING,
+        Severity::Info => lsp::DiagnosticSeverity::INFORMATION,
+        Severity::Hint => lsp::DiagnosticSeverity::HINT,
+    });
+    lsp::Diagnostic {
+        range: lsp::Range::new(start, end),
+        severity,
+        code: diagnostic.code.map(lsp::NumberOrString::String),
+        source: Some(linter.name.clone()),
+        message: diagnostic.message,
+        ..Default::default()
+    }
+}
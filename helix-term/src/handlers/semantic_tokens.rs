@@ -0,0 +1,393 @@
+use std::{collections::HashSet, time::Duration};
+
+use helix_core::{syntax::config::LanguageServerFeature, Assoc};
+use helix_event::{cancelable_future, register_hook};
+use helix_lsp::{lsp, LanguageServerId, OffsetEncoding};
+use helix_view::{
+    document::{DocumentSemanticTokens, SemanticToken},
+    events::{DocumentDidChange, DocumentDidOpen, LanguageServerExited, LanguageServerInitialized},
+    handlers::{lsp::SemanticTokensEvent, Handlers},
+    DocumentId, Editor,
+};
+use tokio::time::Instant;
+
+use crate::job;
+
+#[derive(Default)]
+pub(super) struct SemanticTokensHandler {
+    docs: HashSet<DocumentId>,
+}
+
+const DOCUMENT_CHANGE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+impl helix_event::AsyncHook for SemanticTokensHandler {
+    type Event = SemanticTokensEvent;
+
+    fn handle_event(&mut self, event: Self::Event, _timeout: Option<Instant>) -> Option<Instant> {
+        let SemanticTokensEvent(doc_id) = event;
+        self.docs.insert(doc_id);
+        Some(Instant::now() + DOCUMENT_CHANGE_DEBOUNCE)
+    }
+
+    fn finish_debounce(&mut self) {
+        let docs = std::mem::take(&mut self.docs);
+
+        job::dispatch_blocking(move |editor, _compositor| {
+            for doc in docs {
+                request_semantic_tokens(editor, doc);
+            }
+        });
+    }
+}
+
+enum SemanticTokensResponse {
+    Full(lsp::SemanticTokensResult),
+    Delta(lsp::SemanticTokensFullDeltaResult),
+}
+
+pub(crate) fn request_semantic_tokens(editor: &mut Editor, doc_id: DocumentId) {
+    if !editor.config().lsp.display_semantic_tokens {
+        return;
+    }
+
+    let Some(doc) = editor.document_mut(doc_id) else {
+        return;
+    };
+
+    // Tokens from different servers use different legends and may overlap so only the first
+    // server supporting semantic tokens is used.
+    let Some(language_server) = doc
+        .language_servers_with_feature(LanguageServerFeature::SemanticTokens)
+        .next()
+    else {
+        return;
+    };
+    let language_server_id = language_server.id();
+    let version = doc.version();
+
+    // Request a delta if the server sent a result ID along with the current tokens.
+    let previous_result_id = doc
+        .semantic_tokens
+        .as_ref()
+        .filter(|tokens| tokens.language_server_id == language_server_id)
+        .and_then(|tokens| tokens.result_id.clone());
+    let delta = previous_result_id.and_then(|result_id| {
+        language_server.text_document_semantic_tokens_full_delta(doc.identifier(), result_id)
+    });
+    let future = match delta {
+        Some(future) => futures_util::future::Either::Left(async move {
+            anyhow::Ok(future.await?.map(SemanticTokensResponse::Delta))
+        }),
+        None => {
+            let future = language_server
+                .text_document_semantic_tokens_full(doc.identifier())
+                .unwrap();
+            futures_util::future::Either::Right(async move {
+                anyhow::Ok(future.await?.map(SemanticTokensResponse::Full))
+            })
+        }
+    };
+
+    let cancel = doc.semantic_tokens_controller.restart();
+
+    tokio::spawn(async move {
+        let response = match cancelable_future(future, &cancel).await {
+            Some(Ok(response)) => response,
+            Some(Err(err)) => {
+                log::error!("semantic tokens request failed: {err}");
+                return;
+            }
+            // The request was cancelled.
+            None => return,
+        };
+        job::dispatch(move |editor, _| {
+            attach_semantic_tokens(editor, doc_id, language_server_id, version, response)
+        })
+        .await;
+    });
+}
+
+fn attach_semantic_tokens(
+    editor: &mut Editor,
+    doc_id: DocumentId,
+    language_server_id: LanguageServerId,
+    version: i32,
+    response: Option<SemanticTokensResponse>,
+) {
+    if !editor.config().lsp.display_semantic_tokens {
+        return;
+    }
+
+    let Some(offset_encoding) = editor
+        .language_server_by_id(language_server_id)
+        .map(|language_server| language_server.offset_encoding())
+    else {
+        return;
+    };
+
+    let Some(doc) = editor.documents.get_mut(&doc_id) else {
+        return;
+    };
+
+    // The document changed while the response was being dispatched. A new request has
+    // already been queued by the change.
+    if doc.version() != version {
+        return;
+    }
+
+    let previous = doc
+        .semantic_tokens
+        .take()
+        .filter(|tokens| tokens.language_server_id == language_server_id);
+
+    let (result_id, data) = match response {
+        None => return,
+        Some(SemanticTokensResponse::Full(lsp::SemanticTokensResult::Tokens(tokens))) => {
+            (tokens.result_id, tokens.data)
+        }
+        Some(SemanticTokensResponse::Full(lsp::SemanticTokensResult::Partial(tokens))) => {
+            (None, tokens.data)
+        }
+        Some(SemanticTokensResponse::Delta(lsp::SemanticTokensFullDeltaResult::Tokens(tokens))) => {
+            (tokens.result_id, tokens.data)
+        }
+        Some(SemanticTokensResponse::Delta(lsp::SemanticTokensFullDeltaResult::TokensDelta(
+            delta,
+        ))) => {
+            let Some(previous) = previous else {
+                return;
+            };
+            let data = apply_semantic_tokens_edits(previous.data, delta.edits);
+            (delta.result_id, data)
+        }
+        Some(SemanticTokensResponse::Delta(
+            lsp::SemanticTokensFullDeltaResult::PartialTokensDelta { edits },
+        )) => {
+            let Some(previous) = previous else {
+                return;
+            };
+            (None, apply_semantic_tokens_edits(previous.data, edits))
+        }
+    };
+
+    let tokens = decode_semantic_tokens(doc.text(), &data, offset_encoding);
+    doc.semantic_tokens = Some(DocumentSemanticTokens {
+        language_server_id,
+        result_id,
+        data,
+        tokens,
+    });
+}
+
+/// Applies the edits of a `textDocument/semanticTokens/full/delta` response. The edit
+/// offsets index into the flattened token array where every token takes five integers.
+fn apply_semantic_tokens_edits(
+    data: Vec<lsp::SemanticToken>,
+    mut edits: Vec<lsp::SemanticTokensEdit>,
+) -> Vec<lsp::SemanticToken> {
+    let mut flat: Vec<u32> = data
+        .into_iter()
+        .flat_map(|token| {
+            [
+                token.delta_line,
+                token.delta_start,
+                token.length,
+                token.token_type,
+                token.token_modifiers_bitset,
+            ]
+        })
+        .collect();
+
+    // Apply the edits back to front so that earlier offsets stay valid.
+    edits.sort_by_key(|edit| std::cmp::Reverse(edit.start));
+    for edit in edits {
+        let start = (edit.start as usize).min(flat.len());
+        let end = (start + edit.delete_count as usize).min(flat.len());
+        let inserted = edit.data.unwrap_or_default().into_iter().flat_map(|token| {
+            [
+                token.delta_line,
+                token.delta_start,
+                token.length,
+                token.token_type,
+                token.token_modifiers_bitset,
+            ]
+        });
+        flat.splice(start..end, inserted);
+    }
+
+    flat.chunks_exact(5)
+        .map(|chunk| lsp::SemanticToken {
+            delta_line: chunk[0],
+            delta_start: chunk[1],
+            length: chunk[2],
+            token_type: chunk[3],
+            token_modifiers_bitset: chunk[4],
+        })
+        .collect()
+}
+
+/// Converts the relative token positions sent by the server into character ranges.
+fn decode_semantic_tokens(
+    text: &helix_core::Rope,
+    data: &[lsp::SemanticToken],
+    offset_encoding: OffsetEncoding,
+) -> Vec<SemanticToken> {
+    let mut tokens = Vec::with_capacity(data.len());
+    let mut line = 0;
+    let mut character = 0;
+
+    for token in data {
+        if token.delta_line == 0 {
+            character += token.delta_start;
+        } else {
+            line += token.delta_line;
+            character = token.delta_start;
+        }
+
+        let start = lsp::Position::new(line, character);
+        let end = lsp::Position::new(line, character + token.length);
+        let (Some(start), Some(end)) = (
+            helix_lsp::util::lsp_pos_to_pos(text, start, offset_encoding),
+            helix_lsp::util::lsp_pos_to_pos(text, end, offset_encoding),
+        ) else {
+            continue;
+        };
+        if start >= end {
+            continue;
+        }
+
+        tokens.push(SemanticToken {
+            start,
+            end,
+            token_type: token.token_type,
+            token_modifiers: token.token_modifiers_bitset,
+        });
+    }
+
+    tokens
+}
+
+pub(super) fn register_hooks(handlers: &Handlers) {
+    register_hook!(move |event: &mut DocumentDidOpen<'_>| {
+        request_semantic_tokens(event.editor, event.doc);
+
+        Ok(())
+    });
+
+    let tx = handlers.semantic_tokens.clone();
+    register_hook!(move |event: &mut DocumentDidChange<'_>| {
+        // Keep the existing tokens in place until the server sends new ones.
+        if let Some(semantic_tokens) = &mut event.doc.semantic_tokens {
+            event
+                .changes
+                .update_positions(semantic_tokens.tokens.iter_mut().flat_map(|token| {
+                    [
+                        (&mut token.start, Assoc::After),
+                        (&mut token.end, Assoc::Before),
+                    ]
+                }));
+            semantic_tokens
+                .tokens
+                .retain(|token| token.start < token.end);
+        }
+
+        // Avoid re-requesting semantic tokens if the change is a ghost transaction (completion)
+        // because the language server will not know about the updates to the document.
+        if !event.ghost_transaction {
+            event.doc.semantic_tokens_controller.cancel();
+            helix_event::send_blocking(&tx, SemanticTokensEvent(event.doc.id()));
+        }
+
+        Ok(())
+    });
+
+    register_hook!(move |event: &mut LanguageServerInitialized<'_>| {
+        let doc_ids: Vec<_> = event.editor.documents().map(|doc| doc.id()).collect();
+
+        for doc_id in doc_ids {
+            request_semantic_tokens(event.editor, doc_id);
+        }
+
+        Ok(())
+    });
+
+    register_hook!(move |event: &mut LanguageServerExited<'_>| {
+        for doc in event.editor.documents_mut() {
+            if doc
+                .semantic_tokens
+                .as_ref()
+                .is_some_and(|tokens| tokens.language_server_id == event.server_id)
+            {
+                doc.semantic_tokens = None;
+            }
+        }
+
+        let doc_ids: Vec<_> = event.editor.documents().map(|doc| doc.id()).collect();
+
+        for doc_id in doc_ids {
+            request_semantic_tokens(event.editor, doc_id);
+        }
+
+        Ok(())
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(delta_line: u32, delta_start: u32, length: u32) -> lsp::SemanticToken {
+        lsp::SemanticToken {
+            delta_line,
+            delta_start,
+            length,
+            token_type: 0,
+            token_modifiers_bitset: 0,
+        }
+    }
+
+    #[test]
+    fn apply_edits() {
+        let data = vec![token(0, 0, 3), token(1, 4, 5), token(2, 0, 1)];
+
+        // Replace the second token.
+        let edits = vec![lsp::SemanticTokensEdit {
+            start: 5,
+            delete_count: 5,
+            data: Some(vec![token(1, 2, 2)]),
+        }];
+        assert_eq!(
+            apply_semantic_tokens_edits(data.clone(), edits),
+            vec![token(0, 0, 3), token(1, 2, 2), token(2, 0, 1)]
+        );
+
+        // Multiple edits are applied relative to the original array.
+        let edits = vec![
+            lsp::SemanticTokensEdit {
+                start: 0,
+                delete_count: 5,
+                data: None,
+            },
+            lsp::SemanticTokensEdit {
+                start: 15,
+                delete_count: 0,
+                data: Some(vec![token(3, 1, 1)]),
+            },
+        ];
+        assert_eq!(
+            apply_semantic_tokens_edits(data, edits),
+            vec![token(1, 4, 5), token(2, 0, 1), token(3, 1, 1)]
+        );
+    }
+
+    #[test]
+    fn decode_tokens() {
+        let text = helix_core::Rope::from("let a = 1;\nlet bc = a;\n");
+        let data = vec![token(0, 4, 1), token(1, 4, 2), token(0, 5, 1)];
+        let ranges: Vec<_> = decode_semantic_tokens(&text, &data, OffsetEncoding::Utf8)
+            .into_iter()
+            .map(|token| token.start..token.end)
+            .collect();
+        assert_eq!(ranges, vec![4..5, 15..17, 20..21]);
+    }
+}
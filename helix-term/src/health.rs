@@ -17,6 +17,7 @@ pub enum TsFeature {
     AutoIndent,
     Tags,
     RainbowBracket,
+    Fold,
 }
 
 impl TsFeature {
@@ -27,6 +28,7 @@ pub fn all() -> &'static [Self] {
             Self::AutoIndent,
             Self::Tags,
             Self::RainbowBracket,
+            Self::Fold,
         ]
     }
 
@@ -37,6 +39,7 @@ pub fn runtime_filename(&self) -> &'static str {
             Self::AutoIndent => "indents.scm",
             Self::Tags => "tags.scm",
             Self::RainbowBracket => "rainbows.scm",
+            Self::Fold => "folds.scm",
         }
     }
 
@@ -47,6 +50,7 @@ pub fn long_title(&self) -> &'static str {
             Self::AutoIndent => "Auto Indent",
             Self::Tags => "Code Navigation Tags",
             Self::RainbowBracket => "Rainbow Brackets",
+            Self::Fold => "Code Folding",
         }
     }
 
@@ -57,6 +61,7 @@ pub fn short_title(&self) -> &'static str {
             Self::AutoIndent => "Indent",
             Self::Tags => "Tags",
             Self::RainbowBracket => "Rainbow",
+            Self::Fold => "Fold",
         }
     }
 }
@@ -349,6 +354,13 @@ pub fn language(lang_str: String) -> std::io::Result<()> {
             .map(|formatter| formatter.command.to_string()),
     )?;
 
+    probe_protocols(
+        "linter",
+        lang.linters
+            .iter()
+            .map(|linter| (linter.name.as_str(), linter.command.as_str())),
+    )?;
+
     probe_parser(lang.grammar.as_ref().unwrap_or(&lang.language_id))?;
 
     for ts_feat in TsFeature::all() {
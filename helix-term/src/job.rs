@@ -3,7 +3,7 @@
 use helix_view::Editor;
 use once_cell::sync::OnceCell;
 
-use crate::compositor::Compositor;
+use crate::compositor::{self, Compositor};
 
 use futures_util::future::{BoxFuture, Future, FutureExt};
 use futures_util::stream::{FuturesUnordered, StreamExt};
@@ -11,6 +11,7 @@
 
 pub type EditorCompositorCallback = Box<dyn FnOnce(&mut Editor, &mut Compositor) + Send>;
 pub type EditorCallback = Box<dyn FnOnce(&mut Editor) + Send>;
+pub type ContextCallback = Box<dyn FnOnce(&mut compositor::Context, &mut Compositor) + Send>;
 
 runtime_local! {
     static JOB_QUEUE: OnceCell<Sender<Callback>> = OnceCell::new();
@@ -32,9 +33,19 @@ pub fn dispatch_blocking(job: impl FnOnce(&mut Editor, &mut Compositor) + Send +
     send_blocking(jobs, Callback::EditorCompositor(Box::new(job)))
 }
 
+/// Like [dispatch_blocking] but the job receives a [compositor::Context] so that it can
+/// execute commands.
+pub fn dispatch_context_blocking(
+    job: impl FnOnce(&mut compositor::Context, &mut Compositor) + Send + 'static,
+) {
+    let jobs = JOB_QUEUE.wait();
+    send_blocking(jobs, Callback::Context(Box::new(job)))
+}
+
 pub enum Callback {
     EditorCompositor(EditorCompositorCallback),
     Editor(EditorCallback),
+    Context(ContextCallback),
 }
 
 pub type JobFuture = BoxFuture<'static, anyhow::Result<Option<Callback>>>;
@@ -100,7 +111,7 @@ pub fn callback<F: Future<Output = anyhow::Result<Callback>> + Send + 'static>(
     }
 
     pub fn handle_callback(
-        &self,
+        &mut self,
         editor: &mut Editor,
         compositor: &mut Compositor,
         call: anyhow::Result<Option<Callback>>,
@@ -110,6 +121,14 @@ pub fn handle_callback(
             Ok(Some(call)) => match call {
                 Callback::EditorCompositor(call) => call(editor, compositor),
                 Callback::Editor(call) => call(editor),
+                Callback::Context(call) => {
+                    let mut cx = compositor::Context {
+                        editor,
+                        scroll: None,
+                        jobs: self,
+                    };
+                    call(&mut cx, compositor)
+                }
             },
             Err(e) => {
                 editor.set_error(format!("Async job failed: {}", e));
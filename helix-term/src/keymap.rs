@@ -5,7 +5,7 @@
 pub use default::default;
 
 use arc_swap::{
-    access::{DynAccess, DynGuard},
+    access::{Constant, DynAccess, DynGuard},
     ArcSwap,
 };
 use helix_view::{document::Mode, info::Info, input::KeyEvent};
@@ -26,8 +26,10 @@ pub struct KeyTrieNode {
     name: String,
     #[serde(flatten)]
     map: IndexMap<KeyEvent, KeyTrie>,
+    /// Whether the node stays active after running one of its commands. `None` keeps the
+    /// stickiness of the node it's merged into and isn't sticky otherwise.
     #[serde(skip)]
-    pub is_sticky: bool,
+    pub sticky: Option<bool>,
 }
 
 impl KeyTrieNode {
@@ -35,7 +37,7 @@ pub fn new(name: &str, map: IndexMap<KeyEvent, KeyTrie>) -> Self {
         Self {
             name: name.to_string(),
             map,
-            is_sticky: false,
+            sticky: None,
         }
     }
 
@@ -43,6 +45,12 @@ pub fn new(name: &str, map: IndexMap<KeyEvent, KeyTrie>) -> Self {
     /// corresponding keyevent in self, except when both other and self have
     /// subnodes for same key. In that case the merge is recursive.
     pub fn merge(&mut self, mut other: Self) {
+        if !other.name.is_empty() {
+            self.name = std::mem::take(&mut other.name);
+        }
+        if other.sticky.is_some() {
+            self.sticky = other.sticky;
+        }
         for (key, trie) in std::mem::take(&mut other.map) {
             if let Some(KeyTrie::Node(node)) = self.map.get_mut(&key) {
                 if let KeyTrie::Node(other_node) = trie {
@@ -54,6 +62,14 @@ pub fn merge(&mut self, mut other: Self) {
         }
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_sticky(&self) -> bool {
+        self.sticky == Some(true)
+    }
+
     pub fn infobox(&self) -> Info {
         let mut body: Vec<(BTreeSet<KeyEvent>, &str)> = Vec::with_capacity(self.len());
         for (&key, trie) in self.iter() {
@@ -174,10 +190,22 @@ fn visit_map<M>(self, mut map: M) -> Result<Self::Value, M::Error>
         M: serde::de::MapAccess<'de>,
     {
         let mut mapping = IndexMap::new();
-        while let Some((key, value)) = map.next_entry::<KeyEvent, KeyTrie>()? {
-            mapping.insert(key, value);
+        let mut name = String::new();
+        let mut sticky = None;
+        // `name` and `sticky` are not valid keys so they can configure the node itself.
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "name" => name = map.next_value()?,
+                "sticky" => sticky = Some(map.next_value()?),
+                _ => {
+                    let key = key.parse::<KeyEvent>().map_err(serde::de::Error::custom)?;
+                    mapping.insert(key, map.next_value::<KeyTrie>()?);
+                }
+            }
         }
-        Ok(KeyTrie::Node(KeyTrieNode::new("", mapping)))
+        let mut node = KeyTrieNode::new(&name, mapping);
+        node.sticky = sticky;
+        Ok(KeyTrie::Node(node))
     }
 }
 
@@ -261,8 +289,25 @@ pub enum KeymapResult {
 /// A map of command names to keybinds that will execute the command.
 pub type ReverseKeymap = HashMap<String, Vec<Vec<KeyEvent>>>;
 
+/// Returns the keymap of `mode` in `language_map` if it has keymaps for `language`, or in
+/// `map` otherwise.
+fn language_keymap<'a>(
+    map: &'a HashMap<Mode, KeyTrie>,
+    language_map: &'a HashMap<String, HashMap<Mode, KeyTrie>>,
+    mode: Mode,
+    language: Option<&str>,
+) -> &'a KeyTrie {
+    let keymaps = language
+        .and_then(|language| language_map.get(language))
+        .unwrap_or(map);
+    &keymaps[&mode]
+}
+
 pub struct Keymaps {
     pub map: Box<dyn DynAccess<HashMap<Mode, KeyTrie>>>,
+    /// Keymaps of documents with a language, by language name. These contain the keys of
+    /// [Self::map] as well.
+    pub language_map: Box<dyn DynAccess<HashMap<String, HashMap<Mode, KeyTrie>>>>,
     /// Stores pending keys waiting for the next key. This is relative to a
     /// sticky node if one is in use.
     state: Vec<KeyEvent>,
@@ -274,28 +319,47 @@ impl Keymaps {
     pub fn new(map: Box<dyn DynAccess<HashMap<Mode, KeyTrie>>>) -> Self {
         Self {
             map,
+            language_map: Box::new(Constant(HashMap::new())),
             state: Vec::new(),
             sticky: None,
         }
     }
 
+    pub fn with_language_map(
+        mut self,
+        language_map: Box<dyn DynAccess<HashMap<String, HashMap<Mode, KeyTrie>>>>,
+    ) -> Self {
+        self.language_map = language_map;
+        self
+    }
+
     pub fn map(&self) -> DynGuard<HashMap<Mode, KeyTrie>> {
         self.map.load()
     }
 
+    /// Returns the reverse map of the keymap used for `mode` in documents with `language`.
+    pub fn reverse_map(&self, mode: Mode, language: Option<&str>) -> ReverseKeymap {
+        let (map, language_map) = (self.map(), self.language_map.load());
+        language_keymap(&map, &language_map, mode, language).reverse_map()
+    }
+
     /// Returns list of keys waiting to be disambiguated in current mode.
     pub fn pending(&self) -> &[KeyEvent] {
         &self.state
     }
 
+    /// Cancels the pending keys and returns them.
+    pub fn cancel_pending(&mut self) -> Vec<KeyEvent> {
+        std::mem::take(&mut self.state)
+    }
+
     pub fn sticky(&self) -> Option<&KeyTrieNode> {
         self.sticky.as_ref()
     }
 
-    pub fn contains_key(&self, mode: Mode, key: KeyEvent) -> bool {
-        let keymaps = &*self.map();
-        let keymap = &keymaps[&mode];
-        keymap
+    pub fn contains_key(&self, mode: Mode, language: Option<&str>, key: KeyEvent) -> bool {
+        let (map, language_map) = (self.map(), self.language_map.load());
+        language_keymap(&map, &language_map, mode, language)
             .search(self.pending())
             .and_then(KeyTrie::node)
             .is_some_and(|node| node.contains_key(&key))
@@ -305,9 +369,19 @@ pub fn contains_key(&self, mode: Mode, key: KeyEvent) -> bool {
     /// key cancels pending keystrokes. If there are no pending keystrokes but a
     /// sticky node is in use, it will be cleared.
     pub fn get(&mut self, mode: Mode, key: KeyEvent) -> KeymapResult {
+        self.get_for_language(mode, None, key)
+    }
+
+    /// Like [Self::get] but uses the keymap of `language` if it has one.
+    pub fn get_for_language(
+        &mut self,
+        mode: Mode,
+        language: Option<&str>,
+        key: KeyEvent,
+    ) -> KeymapResult {
         // TODO: remove the sticky part and look up manually
-        let keymaps = &*self.map();
-        let keymap = &keymaps[&mode];
+        let (map, language_map) = (self.map(), self.language_map.load());
+        let keymap = language_keymap(&map, &language_map, mode, language);
 
         if key!(Esc) == key {
             if !self.state.is_empty() {
@@ -337,7 +411,7 @@ pub fn get(&mut self, mode: Mode, key: KeyEvent) -> KeymapResult {
         self.state.push(key);
         match trie.search(&self.state[1..]) {
             Some(KeyTrie::Node(map)) => {
-                if map.is_sticky {
+                if map.is_sticky() {
                     self.state.clear();
                     self.sticky = Some(map.clone());
                 }
@@ -378,7 +452,6 @@ mod tests {
     use super::macros::keymap;
     use super::*;
     use crate::commands::MappableCommand;
-    use arc_swap::access::Constant;
     use helix_core::hashmap;
     use helix_view::input::{KeyCode, KeyEvent, KeyModifiers};
     use indexmap::indexmap;
@@ -468,6 +541,97 @@ fn merge_partial_keys() {
             .is_empty());
     }
 
+    #[test]
+    fn get_for_language() {
+        let mut rust = default();
+        merge_keys(
+            &mut rust,
+            hashmap! {
+                Mode::Normal => keymap!({ "Normal mode"
+                    "i" => normal_mode,
+                    "space" => { "Space"
+                        "r" => goto_file_end,
+                    },
+                })
+            },
+        );
+        let mut keymap = Keymaps::new(Box::new(Constant(default()))).with_language_map(Box::new(
+            Constant(hashmap! {
+                "rust".to_owned() => rust,
+            }),
+        ));
+
+        assert_eq!(
+            keymap.get_for_language(Mode::Normal, Some("rust"), key!('i')),
+            KeymapResult::Matched(MappableCommand::normal_mode),
+            "The keymap of the language should be used"
+        );
+        assert_eq!(
+            keymap.get_for_language(Mode::Normal, Some("python"), key!('i')),
+            KeymapResult::Matched(MappableCommand::insert_mode),
+            "Languages without a keymap should use the global keymap"
+        );
+        assert_eq!(
+            keymap.get_for_language(Mode::Normal, None, key!('i')),
+            KeymapResult::Matched(MappableCommand::insert_mode),
+            "Documents without a language should use the global keymap"
+        );
+
+        assert!(matches!(
+            keymap.get_for_language(Mode::Normal, Some("rust"), key!(' ')),
+            KeymapResult::Pending(_)
+        ));
+        assert_eq!(
+            keymap.get_for_language(Mode::Normal, Some("rust"), key!('r')),
+            KeymapResult::Matched(MappableCommand::goto_file_end),
+            "Nodes of the language should be merged into the global nodes"
+        );
+        assert!(keymap.contains_key(Mode::Normal, Some("rust"), key!(' ')));
+        assert!(
+            keymap.reverse_map(Mode::Normal, Some("rust"))["goto_file_end"]
+                .contains(&vec![key!(' '), key!('r')])
+        );
+        assert!(!keymap
+            .reverse_map(Mode::Normal, None)
+            .contains_key("goto_file_end"));
+    }
+
+    #[test]
+    fn merge_sticky() {
+        let mut keys = hashmap! {
+            Mode::Normal => keymap!({ "Normal mode"
+                "z" => { "View" sticky=true
+                    "k" => scroll_up,
+                },
+            })
+        };
+        let toml = r#"
+            [normal.z]
+            j = "scroll_down"
+
+            [normal.Z]
+            sticky = true
+            k = "scroll_up"
+            "#;
+        merge_keys(&mut keys, toml::from_str(toml).unwrap());
+        let normal = &keys[&Mode::Normal];
+        let node = |key| normal.search(&[key]).and_then(KeyTrie::node).unwrap();
+        assert!(
+            node(key!('z')).is_sticky(),
+            "Unset stickiness should be kept"
+        );
+        assert!(node(key!('Z')).is_sticky());
+
+        let toml = r#"
+            [normal.z]
+            sticky = false
+            "#;
+        merge_keys(&mut keys, toml::from_str(toml).unwrap());
+        let normal = &keys[&Mode::Normal];
+        let node = normal.search(&[key!('z')]).and_then(KeyTrie::node).unwrap();
+        assert!(!node.is_sticky(), "Stickiness should be overridden");
+    }
+
     #[test]
     fn order_should_be_set() {
         let keymap = hashmap! {
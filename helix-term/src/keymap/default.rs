@@ -49,6 +49,10 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
             "y" => goto_type_definition,
             "r" => goto_reference,
             "i" => goto_implementation,
+            "C" => incoming_calls,
+            "O" => outgoing_calls,
+            "S" => supertypes,
+            "T" => subtypes,
             "t" => goto_window_top,
             "c" => goto_window_center,
             "b" => goto_window_bottom,
@@ -121,6 +125,7 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
             "T" => goto_prev_test,
             "p" => goto_prev_paragraph,
             "x" => goto_prev_xml_element,
+            "n" => goto_prev_conflict,
             "space" => add_newline_above,
         },
         "]" => { "Right bracket"
@@ -136,6 +141,7 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
             "T" => goto_next_test,
             "p" => goto_next_paragraph,
             "x" => goto_next_xml_element,
+            "n" => goto_next_conflict,
             "space" => add_newline_below,
         },
 
@@ -207,6 +213,12 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
             "K" => swap_view_up,
             "H" => swap_view_left,
             "J" => swap_view_down,
+            ">" => grow_view_width,
+            "<" => shrink_view_width,
+            "+" => grow_view_height,
+            "-" => shrink_view_height,
+            "=" => equalize_views,
+            "z" => zoom_view,
             "n" => { "New split scratch buffer"
                 "C-s" | "s" => hsplit_new,
                 "C-v" | "v" => vsplit_new,
@@ -234,12 +246,28 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
             "d" => diagnostics_picker,
             "D" => workspace_diagnostics_picker,
             "g" => changed_file_picker,
+            "B" => blame_line,
+            "v" => { "Version control"
+                "s" => stage_diff_change,
+                "u" => unstage_diff_change,
+                "b" => show_diff_change_base,
+                "o" => accept_conflict_ours,
+                "t" => accept_conflict_theirs,
+                "a" => accept_conflict_both,
+                "c" => accept_conflict_base,
+            },
             "a" => code_action,
+            "l" => code_lens,
             "'" => last_picker,
             "G" => { "Debug (experimental)" sticky=true
                 "l" => dap_launch,
                 "r" => dap_restart,
                 "b" => dap_toggle_breakpoint,
+                "f" => dap_toggle_function_breakpoint,
+                "d" => dap_toggle_data_breakpoint,
+                "w" => dap_toggle_watch,
+                "W" => dap_watches,
+                "R" => dap_console,
                 "c" => dap_continue,
                 "h" => dap_pause,
                 "i" => dap_step_in,
@@ -274,6 +302,12 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
                 "J" => swap_view_down,
                 "K" => swap_view_up,
                 "L" => swap_view_right,
+                ">" => grow_view_width,
+                "<" => shrink_view_width,
+                "+" => grow_view_height,
+                "-" => shrink_view_height,
+                "=" => equalize_views,
+                "z" => zoom_view,
                 "n" => { "New split scratch buffer"
                     "C-s" | "s" => hsplit_new,
                     "C-v" | "v" => vsplit_new,
@@ -285,6 +319,7 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
             "P" => paste_clipboard_before,
             "R" => replace_selections_with_clipboard,
             "/" => global_search,
+            "A-/" => global_replace,
             "k" => hover,
             "r" => rename_symbol,
             "h" => select_references_to_symbol_under_cursor,
@@ -309,6 +344,12 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
             "?" => rsearch,
             "n" => search_next,
             "N" => search_prev,
+
+            "f" => fold,
+            "o" => unfold,
+            "a" => toggle_fold,
+            "M" => fold_all,
+            "R" => unfold_all,
         },
         "Z" => { "View" sticky=true
             "z" | "c" => align_view_center,
@@ -326,6 +367,12 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
             "?" => rsearch,
             "n" => search_next,
             "N" => search_prev,
+
+            "f" => fold,
+            "o" => unfold,
+            "a" => toggle_fold,
+            "M" => fold_all,
+            "R" => unfold_all,
         },
 
         "\"" => select_register,
@@ -10,6 +10,7 @@
 pub mod health;
 pub mod job;
 pub mod keymap;
+pub mod remote;
 pub mod ui;
 
 #[cfg(not(windows))]
@@ -1,8 +1,10 @@
 use anyhow::{Context, Error, Result};
+use helix_core::Position;
 use helix_loader::VERSION_AND_GIT_HASH;
 use helix_term::application::Application;
 use helix_term::args::Args;
 use helix_term::config::{Config, ConfigLoadError};
+use helix_term::remote::{self, Request, Response};
 
 fn setup_logging(verbosity: u64) -> Result<()> {
     let mut base_config = fern::Dispatch::new();
@@ -32,6 +34,48 @@ fn setup_logging(verbosity: u64) -> Result<()> {
     Ok(())
 }
 
+/// Sends the requests given by `args` to the running instance and prints the results.
+fn run_remote(args: Args) -> Result<i32> {
+    let paths: Vec<_> = args.files.keys().cloned().collect();
+    let mut requests: Vec<_> = args
+        .files
+        .into_iter()
+        .flat_map(|(path, positions)| {
+            positions.into_iter().map(move |pos| Request::Open {
+                path: path.clone(),
+                // Files without a position are parsed at the start of the document, keep
+                // the cursor of documents that are already open instead.
+                position: (pos != Position::default()).then_some(remote::Position {
+                    line: pos.row + 1,
+                    column: pos.col + 1,
+                }),
+            })
+        })
+        .collect();
+    if let Some(command) = args.remote_command {
+        requests.push(Request::Command { command });
+    }
+    if args.remote_current_path {
+        requests.push(Request::CurrentPath);
+    }
+    if args.remote_wait {
+        requests.push(Request::Wait { paths });
+    }
+
+    let mut exit_code = 0;
+    for response in remote::send(&helix_loader::remote_socket(), &requests)? {
+        match response {
+            Response::Ok { value: Some(value) } => println!("{value}"),
+            Response::Ok { value: None } => (),
+            Response::Error { message } => {
+                eprintln!("{message}");
+                exit_code = 1;
+            }
+        }
+    }
+    Ok(exit_code)
+}
+
 fn main() -> Result<()> {
     let exit_code = main_impl()?;
     std::process::exit(exit_code);
@@ -76,6 +120,12 @@ async fn main_impl() -> Result<i32> {
     --vsplit                       Split all given files vertically into different windows
     --hsplit                       Split all given files horizontally into different windows
     -w, --working-dir <path>       Specify an initial working directory
+    --remote                       Open the given files in a running instance with
+                                   `remote-control` enabled instead of starting Helix
+    --remote-wait                  Like --remote but wait until the files are closed,
+                                   for example to use Helix as $GIT_EDITOR
+    --remote-command <command>     Run a command like 'write' in a running instance
+    --remote-current-path          Print the path of the current buffer of a running instance
     +[N]                           Open the first given file at line number N, or the last line, if
                                    N is not specified.
 ",
@@ -115,6 +165,10 @@ async fn main_impl() -> Result<i32> {
         return Ok(0);
     }
 
+    if args.is_remote() {
+        return run_remote(args);
+    }
+
     setup_logging(args.verbosity).context("failed to initialize logging")?;
 
     // NOTE: Set the working directory early so the correct configuration is loaded. Be aware that
@@ -130,11 +184,14 @@ async fn main_impl() -> Result<i32> {
         return Ok(1);
     }
 
-    let config = match Config::load_default() {
-        Ok(config) => config,
-        Err(ConfigLoadError::Error(err)) if err.kind() == std::io::ErrorKind::NotFound => {
-            Config::default()
+    let config = match Config::load_default().or_else(|err| match err {
+        // The keys in `languages.toml` apply without a `config.toml` too.
+        ConfigLoadError::Error(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            Config::load_without_config_file()
         }
+        err => Err(err),
+    }) {
+        Ok(config) => config,
         Err(ConfigLoadError::Error(err)) => return Err(Error::new(err)),
         Err(ConfigLoadError::BadConfig(err)) => {
             eprintln!("Bad config: {}", err);
@@ -0,0 +1,280 @@
+//! Remote control of a running instance over a Unix socket.
+//!
+//! Clients send one [Request] per line as JSON and receive one [Response] per line, for
+//! example:
+//!
+//! ```text
+//! {"type":"open","path":"/tmp/file.rs","position":{"line":10,"column":2}}
+//! {"status":"ok","value":null}
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", tag = "type")]
+pub enum Request {
+    /// Opens `path` in the focused view and moves the cursor to `position` if given.
+    Open {
+        path: PathBuf,
+        #[serde(default)]
+        position: Option<Position>,
+    },
+    /// Runs a typable command like `:write`, without the leading colon.
+    Command { command: String },
+    /// Returns the path of the document in the focused view, `null` for scratch buffers.
+    CurrentPath,
+    /// Responds once none of `paths` is open anymore, or the instance exits. Used by
+    /// `hx --remote-wait` so that it can be used as `$GIT_EDITOR`.
+    Wait { paths: Vec<PathBuf> },
+}
+
+/// A position in a document. `line` and `column` start at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", tag = "status")]
+pub enum Response {
+    Ok { value: Option<String> },
+    Error { message: String },
+}
+
+impl Response {
+    pub fn from_result(result: Result<Option<String>>) -> Self {
+        match result {
+            Ok(value) => Response::Ok { value },
+            Err(err) => Response::Error {
+                message: err.to_string(),
+            },
+        }
+    }
+}
+
+/// A request received by the [Server] with the channel to send the response to.
+pub type PendingRequest = (Request, oneshot::Sender<Response>);
+
+/// Listens for requests on a Unix socket. The socket is removed when the server is
+/// dropped.
+pub struct Server {
+    path: PathBuf,
+    requests: mpsc::Receiver<PendingRequest>,
+}
+
+impl Server {
+    /// Listens on the socket at `path`. Fails if another instance is already listening
+    /// on it.
+    #[cfg(unix)]
+    pub fn bind(path: PathBuf) -> Result<Self> {
+        use std::os::unix::fs::PermissionsExt;
+
+        if path.exists() {
+            if std::os::unix::net::UnixStream::connect(&path).is_ok() {
+                bail!("another instance is listening on {}", path.display());
+            }
+            // Left behind by an instance that crashed.
+            std::fs::remove_file(&path)?;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let listener = tokio::net::UnixListener::bind(&path)
+            .with_context(|| format!("failed to listen on {}", path.display()))?;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+
+        let (tx, requests) = mpsc::channel(16);
+        tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(handle_connection(stream, tx.clone()));
+                    }
+                    Err(err) => {
+                        log::error!("failed to accept remote connection: {err}");
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(Self { path, requests })
+    }
+
+    #[cfg(not(unix))]
+    pub fn bind(_path: PathBuf) -> Result<Self> {
+        bail!("remote control is only supported on Unix")
+    }
+
+    pub async fn recv(&mut self) -> Option<PendingRequest> {
+        self.requests.recv().await
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Waits for the next request of `server`, or forever if there is no server.
+pub async fn recv(server: &mut Option<Server>) -> Option<PendingRequest> {
+    match server {
+        Some(server) => server.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(unix)]
+async fn handle_connection(stream: tokio::net::UnixStream, requests: mpsc::Sender<PendingRequest>) {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str(&line) {
+            Ok(request) => {
+                let (tx, rx) = oneshot::channel();
+                if requests.send((request, tx)).await.is_err() {
+                    break;
+                }
+                match rx.await {
+                    Ok(response) => response,
+                    Err(_) => break,
+                }
+            }
+            Err(err) => Response::Error {
+                message: format!("invalid request: {err}"),
+            },
+        };
+        let Ok(mut response) = serde_json::to_string(&response) else {
+            break;
+        };
+        response.push('\n');
+        if writer.write_all(response.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Sends `requests` to the instance listening on `path` and returns its responses.
+#[cfg(unix)]
+pub fn send(path: &Path, requests: &[Request]) -> Result<Vec<Response>> {
+    use std::io::{BufRead, BufReader, Write};
+
+    let mut stream = std::os::unix::net::UnixStream::connect(path)
+        .with_context(|| format!("no instance is listening on {}", path.display()))?;
+    let mut lines = BufReader::new(stream.try_clone()?).lines();
+    requests
+        .iter()
+        .map(|request| {
+            let mut request = serde_json::to_string(request)?;
+            request.push('\n');
+            stream.write_all(request.as_bytes())?;
+            let response = lines.next().context("connection closed")??;
+            Ok(serde_json::from_str(&response)?)
+        })
+        .collect()
+}
+
+#[cfg(not(unix))]
+pub fn send(_path: &Path, _requests: &[Request]) -> Result<Vec<Response>> {
+    bail!("remote control is only supported on Unix")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn request_format() {
+        let request: Request = serde_json::from_str(
+            r#"{"type":"open","path":"/tmp/file.rs","position":{"line":3,"column":1}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            request,
+            Request::Open {
+                path: "/tmp/file.rs".into(),
+                position: Some(Position { line: 3, column: 1 })
+            }
+        );
+        let request: Request =
+            serde_json::from_str(r#"{"type":"open","path":"/tmp/file.rs"}"#).unwrap();
+        assert_eq!(
+            request,
+            Request::Open {
+                path: "/tmp/file.rs".into(),
+                position: None
+            }
+        );
+        let request: Request = serde_json::from_str(r#"{"type":"current-path"}"#).unwrap();
+        assert_eq!(request, Request::CurrentPath);
+        assert_eq!(
+            serde_json::to_string(&Response::Ok { value: None }).unwrap(),
+            r#"{"status":"ok","value":null}"#
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("remote.sock");
+        let mut server = Server::bind(path.clone()).unwrap();
+        assert!(Server::bind(path.clone()).is_err());
+
+        let client = tokio::task::spawn_blocking({
+            let path = path.clone();
+            move || {
+                send(
+                    &path,
+                    &[
+                        Request::CurrentPath,
+                        Request::Command {
+                            command: "write".into(),
+                        },
+                    ],
+                )
+            }
+        });
+        let (request, reply) = server.recv().await.unwrap();
+        assert_eq!(request, Request::CurrentPath);
+        reply
+            .send(Response::Ok {
+                value: Some("/tmp/file.rs".into()),
+            })
+            .unwrap();
+        let (request, reply) = server.recv().await.unwrap();
+        assert!(matches!(request, Request::Command { .. }));
+        reply
+            .send(Response::Error {
+                message: "failed".into(),
+            })
+            .unwrap();
+
+        let responses = client.await.unwrap().unwrap();
+        assert_eq!(
+            responses,
+            [
+                Response::Ok {
+                    value: Some("/tmp/file.rs".into())
+                },
+                Response::Error {
+                    message: "failed".into()
+                }
+            ]
+        );
+
+        drop(server);
+        assert!(!path.exists());
+    }
+}
@@ -71,12 +71,13 @@ pub fn render_text(
     theme: &Theme,
     mut decorations: DecorationManager,
 ) {
-    let row_off = visual_offset_from_block(text, anchor, anchor, text_fmt, text_annotations)
+    let anchor_row = visual_offset_from_block(text, anchor, anchor, text_fmt, text_annotations)
         .0
         .row;
 
     let mut formatter =
         DocumentFormatter::new_at_prev_checkpoint(text, text_fmt, text_annotations, anchor);
+    let row_off = formatter.visual_line_start(anchor_row);
     let mut syntax_highlighter =
         SyntaxHighlighter::new(syntax_highlighter, text, theme, renderer.text_style);
     let mut overlay_highlighter = OverlayHighlighter::new(overlay_highlights, theme);
@@ -91,6 +92,10 @@ pub fn render_text(
     let mut last_line_indent_level = 0;
     let mut reached_view_top = false;
 
+    if row_off == 0 && formatter.virtual_lines_above_text() != 0 {
+        decorations.render_virtual_lines_above_text(renderer);
+    }
+
     loop {
         let Some(mut grapheme) = formatter.next() else {
             break;
@@ -139,20 +144,32 @@ pub fn render_text(
             overlay_highlighter.advance();
         }
 
-        let grapheme_style = if let GraphemeSource::VirtualText { highlight } = grapheme.source {
-            let mut style = renderer.text_style;
-            if let Some(highlight) = highlight {
-                style = style.patch(theme.highlight(highlight));
+        let grapheme_style = match grapheme.source {
+            GraphemeSource::VirtualText { highlight } => {
+                let mut style = renderer.text_style;
+                if let Some(highlight) = highlight {
+                    style = style.patch(theme.highlight(highlight));
+                }
+                GraphemeStyle {
+                    syntax_style: style,
+                    overlay_style: Style::default(),
+                }
             }
-            GraphemeStyle {
-                syntax_style: style,
-                overlay_style: Style::default(),
+            // the fold placeholder covers document text so selections are still shown on top
+            GraphemeSource::Fold { highlight, .. } => {
+                let mut style = renderer.text_style;
+                if let Some(highlight) = highlight {
+                    style = style.patch(theme.highlight(highlight));
+                }
+                GraphemeStyle {
+                    syntax_style: style,
+                    overlay_style: overlay_highlighter.style,
+                }
             }
-        } else {
-            GraphemeStyle {
+            GraphemeSource::Document { .. } => GraphemeStyle {
                 syntax_style: syntax_highlighter.style,
                 overlay_style: overlay_highlighter.style,
-            }
+            },
         };
         decorations.decorate_grapheme(renderer, &grapheme);
 
@@ -279,13 +296,13 @@ pub fn draw_decoration_grapheme(
         mut row: u16,
         col: u16,
     ) -> bool {
-        if (row as usize) < self.offset.row
-            || row >= self.viewport.height
-            || col >= self.viewport.width
-        {
+        if (row as usize) < self.offset.row || col >= self.viewport.width {
             return false;
         }
         row -= self.offset.row as u16;
+        if row >= self.viewport.height {
+            return false;
+        }
         // TODO is it correct to apply the whitspace style to all unicode white spaces?
         if grapheme.is_whitespace() {
             style = style.patch(self.whitespace_style);
@@ -414,12 +431,18 @@ pub fn draw_indent_guides(&mut self, indent_level: usize, mut row: u16) {
         }
     }
 
+    /// Translates a row of the rendered text to a row of the surface, returns `None`
+    /// if the row is outside the viewport.
+    fn surface_row(&self, row: u16) -> Option<u16> {
+        let row = (row as usize).checked_sub(self.offset.row)?;
+        (row < self.viewport.height as usize).then(|| self.viewport.y + row as u16)
+    }
+
     pub fn set_string(&mut self, x: u16, y: u16, string: impl AsRef<str>, style: Style) {
-        if (y as usize) < self.offset.row {
+        let Some(y) = self.surface_row(y) else {
             return;
-        }
-        self.surface
-            .set_string(x, y + self.viewport.y, string, style)
+        };
+        self.surface.set_string(x, y, string, style)
     }
 
     pub fn set_stringn(
@@ -430,18 +453,25 @@ pub fn set_stringn(
         width: usize,
         style: Style,
     ) {
-        if (y as usize) < self.offset.row {
+        let Some(y) = self.surface_row(y) else {
             return;
-        }
-        self.surface
-            .set_stringn(x, y + self.viewport.y, string, width, style);
+        };
+        self.surface.set_stringn(x, y, string, width, style);
     }
 
     /// Sets the style of an area **within the text viewport* this accounts
     /// both for the renderers vertical offset and its viewport
-    pub fn set_style(&mut self, mut area: Rect, style: Style) {
-        area = area.clip_top(self.offset.row as u16);
-        area.y += self.viewport.y;
+    pub fn set_style(&mut self, area: Rect, style: Style) {
+        let top = (area.top() as usize).max(self.offset.row);
+        let bottom = (area.bottom() as usize).min(self.offset.row + self.viewport.height as usize);
+        if top >= bottom {
+            return;
+        }
+        let area = Rect {
+            y: self.viewport.y + (top - self.offset.row) as u16,
+            height: (bottom - top) as u16,
+            ..area
+        };
         self.surface.set_style(area, style);
     }
 
@@ -456,18 +486,11 @@ pub fn set_string_truncated(
         ellipsis: bool,
         truncate_start: bool,
     ) -> (u16, u16) {
-        if (y as usize) < self.offset.row {
+        let Some(y) = self.surface_row(y) else {
             return (x, y);
-        }
-        self.surface.set_string_truncated(
-            x,
-            y + self.viewport.y,
-            string,
-            width,
-            style,
-            ellipsis,
-            truncate_start,
-        )
+        };
+        self.surface
+            .set_string_truncated(x, y, string, width, style, ellipsis, truncate_start)
     }
 }
 
@@ -1,15 +1,18 @@
 use crate::{
     commands::{self, OnKeyCallback, OnKeyCallbackKind},
     compositor::{Component, Context, Event, EventResult},
-    events::{OnModeSwitch, PostCommand},
+    events::{OnModeSwitch, PostCommand, TerminalFocusLost},
     handlers::completion::CompletionItem,
-    key,
-    keymap::{KeymapResult, Keymaps},
+    job, key,
+    keymap::{KeyTrieNode, KeymapResult, Keymaps},
     ui::{
         document::{render_document, LinePos, TextRenderer},
         statusline,
-        text_decorations::{self, Decoration, DecorationManager, InlineDiagnostics},
-        Completion, ProgressSpinners,
+        text_decorations::{
+            self, CodeLenses, Decoration, DecorationManager, DiffFillers, InlineBlame,
+            InlineDiagnostics,
+        },
+        Completion, LastSearch, ProgressSpinners,
     },
 };
 
@@ -22,6 +25,8 @@
     unicode::width::UnicodeWidthStr,
     visual_offset_from_block, Change, Position, Range, Selection, Transaction,
 };
+use helix_stdx::rope::RopeSliceExt;
+use helix_vcs::Hunk;
 use helix_view::{
     annotations::diagnostics::DiagnosticFilter,
     document::{Mode, SCRATCH_BUFFER_NAME},
@@ -29,7 +34,8 @@
     graphics::{Color, CursorKind, Modifier, Rect, Style},
     input::{KeyEvent, MouseButton, MouseEvent, MouseEventKind},
     keyboard::{KeyCode, KeyModifiers},
-    Document, Editor, Theme, View,
+    tree::Layout,
+    Document, DocumentId, Editor, Theme, View, ViewId,
 };
 use std::{mem::take, num::NonZeroUsize, ops, path::PathBuf, rc::Rc};
 
@@ -42,8 +48,81 @@ pub struct EditorView {
     pub(crate) last_insert: (commands::MappableCommand, Vec<InsertEvent>),
     pub(crate) completion: Option<Completion>,
     spinners: ProgressSpinners,
+    /// The regex and match counts of the last search, cached between renders.
+    last_search: LastSearch,
     /// Tracks if the terminal window is focused by reaction to terminal focus events
     terminal_focused: bool,
+    /// The split border being dragged with the mouse.
+    dragged_border: Option<(ViewId, Layout)>,
+    insert_sequence: Option<InsertSequence>,
+    /// The number of key sequences started in insert mode, used to identify their timeouts.
+    insert_sequence_count: usize,
+}
+
+/// A key sequence pending in insert mode, like `jk` mapped to `normal_mode`. Its characters
+/// are inserted as they are typed and removed again if the sequence completes.
+struct InsertSequence {
+    id: usize,
+    /// The number of pending keys that were inserted.
+    inserted: usize,
+    inserted_keys: Option<InsertedKeys>,
+}
+
+/// The insertions of the keys of an [InsertSequence].
+struct InsertedKeys {
+    doc: DocumentId,
+    view: ViewId,
+    /// The selection before the first key was inserted.
+    selection: Selection,
+    /// The inverse of the insertions, applying to the document at `version`.
+    revert: Transaction,
+    /// The version of the document after the last key was inserted.
+    version: i32,
+}
+
+impl InsertSequence {
+    /// Inserts the character of a pending key and records how to remove it.
+    fn insert(&mut self, cx: &mut commands::Context, ch: char) {
+        let (view, doc) = current_ref!(cx.editor);
+        let (doc_id, view_id) = (doc.id(), view.id);
+        let selection = doc.selection(view_id).clone();
+        let inverse = commands::insert::insert_char_with_inverse(cx, ch);
+        let version = doc!(cx.editor, &doc_id).version();
+        self.inserted += 1;
+
+        match &mut self.inserted_keys {
+            Some(keys) if keys.doc == doc_id && keys.view == view_id => {
+                let revert = std::mem::take(&mut keys.revert);
+                keys.revert = inverse.compose(revert);
+                keys.version = version;
+            }
+            keys => {
+                *keys = Some(InsertedKeys {
+                    doc: doc_id,
+                    view: view_id,
+                    selection,
+                    revert: inverse,
+                    version,
+                })
+            }
+        }
+    }
+
+    /// Removes the inserted keys from the document, unless it changed in some other way
+    /// since they were inserted.
+    fn revert(self, editor: &mut Editor) {
+        let Some(keys) = self.inserted_keys else {
+            return;
+        };
+        let Some(doc) = editor.documents.get_mut(&keys.doc) else {
+            return;
+        };
+        if !editor.tree.contains(keys.view) || doc.version() != keys.version {
+            return;
+        }
+        let transaction = keys.revert.with_selection(keys.selection);
+        doc.apply(&transaction, keys.view);
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -66,7 +145,11 @@ pub fn new(keymaps: Keymaps) -> Self {
             last_insert: (commands::MappableCommand::normal_mode, Vec::new()),
             completion: None,
             spinners: ProgressSpinners::default(),
+            last_search: LastSearch::default(),
             terminal_focused: true,
+            dragged_border: None,
+            insert_sequence: None,
+            insert_sequence_count: 0,
         }
     }
 
@@ -94,6 +177,14 @@ pub fn render_view(
         let text_annotations = view.text_annotations(doc, Some(theme));
         let mut decorations = DecorationManager::default();
 
+        if let Some(diff_highlights) = Self::diff_highlights(doc, theme, inner) {
+            decorations.add_decoration(diff_highlights);
+        }
+
+        if !doc.conflicts().is_empty() {
+            decorations.add_decoration(Self::conflict_highlights(doc, theme, inner));
+        }
+
         if is_focused && config.cursorline {
             decorations.add_decoration(Self::cursorline(doc, view, theme));
         }
@@ -139,12 +230,41 @@ pub fn render_view(
             }
         }
 
+        if config.lsp.display_semantic_tokens {
+            Self::doc_semantic_tokens_highlights_into(
+                editor,
+                doc,
+                view_offset.anchor,
+                inner.height,
+                &mut overlays,
+            );
+        }
+
+        if let Some(overlay) =
+            Self::doc_diff_text_highlights(doc, view_offset.anchor, inner.height, theme)
+        {
+            overlays.push(overlay);
+        }
+
         if let Some(overlay) = Self::doc_document_link_highlights(doc, theme) {
             overlays.push(overlay);
         }
 
         Self::doc_diagnostics_highlights_into(doc, theme, &mut overlays);
 
+        if config.search.highlight_matches {
+            if let Some(overlay) = Self::doc_search_highlights(
+                editor,
+                &self.last_search,
+                doc,
+                view_offset.anchor,
+                inner.height,
+                theme,
+            ) {
+                overlays.push(overlay);
+            }
+        }
+
         if is_focused {
             if config.lsp.auto_document_highlight {
                 if let Some(overlay) = Self::doc_document_highlights(doc, view, theme) {
@@ -205,6 +325,18 @@ pub fn render_view(
             inline_diagnostic_config,
             config.end_of_line_diagnostics,
         ));
+        if is_focused && config.inline_blame.enable {
+            if let Some(blame) = InlineBlame::new(doc, theme, primary_cursor, &config.inline_blame)
+            {
+                decorations.add_decoration(blame);
+            }
+        }
+        if doc.diff_mode().is_some() {
+            decorations.add_decoration(DiffFillers::new(doc, theme));
+        }
+        if config.lsp.display_code_lenses && !doc.code_lens_lines().is_empty() {
+            decorations.add_decoration(CodeLenses::new(doc, theme));
+        }
         render_document(
             surface,
             inner,
@@ -240,8 +372,17 @@ pub fn render_view(
             .clip_top(view.area.height.saturating_sub(1))
             .clip_bottom(1); // -1 from bottom to remove commandline
 
-        let mut context =
-            statusline::RenderContext::new(editor, doc, view, is_focused, &self.spinners);
+        let mut context = statusline::RenderContext::new(
+            editor,
+            doc,
+            view,
+            is_focused,
+            &self.spinners,
+            &self.last_search,
+        );
+        if is_focused {
+            context.minor_mode = self.keymaps.sticky().map(KeyTrieNode::name);
+        }
 
         statusline::render(&mut context, statusline_area, surface);
     }
@@ -346,6 +487,78 @@ pub fn doc_rainbow_highlights(
         Some(syntax.rainbow_highlights(text, theme.rainbow_length(), loader, range))
     }
 
+    /// Get highlight spans for the LSP semantic tokens in the viewport. Token types map to
+    /// `lsp.type.<type>` scopes and each modifier to a `lsp.mod.<modifier>` scope.
+    pub fn doc_semantic_tokens_highlights_into(
+        editor: &Editor,
+        doc: &Document,
+        anchor: usize,
+        height: u16,
+        overlay_highlights: &mut Vec<OverlayHighlights>,
+    ) {
+        let Some(semantic_tokens) = &doc.semantic_tokens else {
+            return;
+        };
+        let Some(legend) = editor
+            .language_server_by_id(semantic_tokens.language_server_id)
+            .and_then(|language_server| language_server.semantic_tokens_legend())
+        else {
+            return;
+        };
+        let theme = &editor.theme;
+
+        let text = doc.text().slice(..);
+        let row = text.char_to_line(anchor.min(text.len_chars()));
+        let visible_range = Self::viewport_byte_range(text, row, height);
+        let visible_range =
+            text.byte_to_char(visible_range.start)..text.byte_to_char(visible_range.end);
+
+        let tokens = &semantic_tokens.tokens;
+        let first = tokens.partition_point(|token| token.end <= visible_range.start);
+        let visible_tokens = tokens[first..]
+            .iter()
+            .take_while(|token| token.start < visible_range.end);
+
+        let type_highlights: Vec<_> = legend
+            .token_types
+            .iter()
+            .map(|token_type| theme.find_highlight(&format!("lsp.type.{}", token_type.as_str())))
+            .collect();
+        let mut modifier_ranges: Vec<_> = legend
+            .token_modifiers
+            .iter()
+            .take(u32::BITS as usize)
+            .map(|modifier| {
+                let highlight = theme.find_highlight(&format!("lsp.mod.{}", modifier.as_str()));
+                (highlight, Vec::new())
+            })
+            .collect();
+
+        let mut highlights = Vec::new();
+        for token in visible_tokens {
+            if let Some(Some(highlight)) = type_highlights.get(token.token_type as usize) {
+                highlights.push((*highlight, token.start..token.end));
+            }
+            for (bit, (highlight, ranges)) in modifier_ranges.iter_mut().enumerate() {
+                if highlight.is_some() && token.token_modifiers & (1 << bit) != 0 {
+                    ranges.push(token.start..token.end);
+                }
+            }
+        }
+
+        overlay_highlights.push(OverlayHighlights::Heterogenous { highlights });
+        overlay_highlights.extend(
+            modifier_ranges
+                .into_iter()
+                .filter_map(|(highlight, ranges)| {
+                    Some(OverlayHighlights::Homogeneous {
+                        highlight: highlight?,
+                        ranges,
+                    })
+                }),
+        );
+    }
+
     /// Get highlight spans for document diagnostics
     pub fn doc_diagnostics_highlights_into(
         doc: &Document,
@@ -867,6 +1080,131 @@ pub fn cursorline(doc: &Document, view: &View, theme: &Theme) -> impl Decoration
         }
     }
 
+    /// Highlights the lines of merge conflicts with separate styles for the conflict markers
+    /// and each side of the conflicts.
+    pub fn conflict_highlights(doc: &Document, theme: &Theme, viewport: Rect) -> impl Decoration {
+        let conflicts = doc.conflicts().to_vec();
+        let marker_style = theme.get("ui.conflict.marker");
+        let ours_style = theme.get("ui.conflict.ours");
+        let base_style = theme.get("ui.conflict.base");
+        let theirs_style = theme.get("ui.conflict.theirs");
+
+        move |renderer: &mut TextRenderer, pos: LinePos| {
+            let line = pos.doc_line;
+            let idx = conflicts.partition_point(|conflict| conflict.end < line);
+            let Some(conflict) = conflicts.get(idx).filter(|conflict| conflict.start <= line)
+            else {
+                return;
+            };
+            let style = if conflict.is_marker(line) {
+                marker_style
+            } else if conflict.ours().contains(&line) {
+                ours_style
+            } else if conflict.theirs().contains(&line) {
+                theirs_style
+            } else {
+                base_style
+            };
+            let area = Rect::new(viewport.x, pos.visual_line, viewport.width, 1);
+            renderer.set_style(area, style);
+        }
+    }
+
+    /// Highlights the lines of a document in diff mode that differ from the other document.
+    pub fn diff_highlights(
+        doc: &Document,
+        theme: &Theme,
+        viewport: Rect,
+    ) -> Option<impl Decoration> {
+        let diff = doc.diff_mode()?.handle.load();
+        let hunks: Vec<Hunk> = (0..diff.len())
+            .map(|i| diff.nth_hunk(i))
+            .filter(|hunk| !hunk.after.is_empty())
+            .collect();
+        let add_style = theme.get("ui.diff.add");
+        let change_style = theme.get("ui.diff.change");
+
+        Some(move |renderer: &mut TextRenderer, pos: LinePos| {
+            let line = pos.doc_line as u32;
+            let idx = hunks.partition_point(|hunk| hunk.after.end <= line);
+            let Some(hunk) = hunks.get(idx).filter(|hunk| hunk.after.start <= line) else {
+                return;
+            };
+            let style = if hunk.before.is_empty() {
+                add_style
+            } else {
+                change_style
+            };
+            let area = Rect::new(viewport.x, pos.visual_line, viewport.width, 1);
+            renderer.set_style(area, style);
+        })
+    }
+
+    /// Highlights the changed characters within the changed lines of a document in diff mode.
+    pub fn doc_diff_text_highlights(
+        doc: &Document,
+        anchor: usize,
+        height: u16,
+        theme: &Theme,
+    ) -> Option<OverlayHighlights> {
+        let highlight = theme.find_highlight_exact("ui.diff.text")?;
+        let diff_mode = doc.diff_mode()?;
+        let diff = diff_mode.handle.load();
+        let text = doc.text().slice(..);
+        let first_line = text.char_to_line(anchor.min(text.len_chars())) as u32;
+        let last_line = first_line + height as u32;
+
+        let mut ranges = Vec::new();
+        for n in 0..diff.len() {
+            let hunk = diff.nth_hunk(n);
+            if hunk.after.end <= first_line
+                || hunk.after.start > last_line
+                || hunk.after.end as usize > text.len_lines()
+            {
+                continue;
+            }
+            let start = text.line_to_char(hunk.after.start as usize);
+            let end = text.line_to_char(hunk.after.end as usize);
+            ranges.extend(
+                diff_mode
+                    .hunk_text_changes(&diff, n)
+                    .iter()
+                    .map(|range| (start + range.start).min(end)..(start + range.end).min(end)),
+            );
+        }
+
+        if ranges.is_empty() {
+            return None;
+        }
+        Some(OverlayHighlights::Homogeneous { highlight, ranges })
+    }
+
+    /// Highlights the matches of the last search within the visible lines.
+    pub fn doc_search_highlights(
+        editor: &Editor,
+        last_search: &LastSearch,
+        doc: &Document,
+        anchor: usize,
+        height: u16,
+        theme: &Theme,
+    ) -> Option<OverlayHighlights> {
+        let highlight = theme.find_highlight("ui.highlight.search")?;
+        let regex = last_search.regex(editor, doc)?;
+        let text = doc.text().slice(..);
+        let row = text.char_to_line(anchor.min(text.len_chars()));
+        let range = Self::viewport_byte_range(text, row, height);
+
+        let ranges: Vec<_> = regex
+            .find_iter(text.regex_input_at_bytes(range))
+            .filter(|mat| !mat.is_empty())
+            .map(|mat| text.byte_to_char(mat.start())..text.byte_to_char(mat.end()))
+            .collect();
+        if ranges.is_empty() {
+            return None;
+        }
+        Some(OverlayHighlights::Homogeneous { highlight, ranges })
+    }
+
     /// Apply the highlighting on the columns where a cursor is active
     pub fn highlight_cursorcolumn(
         doc: &Document,
@@ -933,42 +1271,66 @@ fn handle_keymap_event(
     ) -> Option<KeymapResult> {
         let mut last_mode = mode;
         self.pseudo_pending.extend(self.keymaps.pending());
-        let key_result = self.keymaps.get(mode, event);
+        let key_result =
+            self.keymaps
+                .get_for_language(mode, doc!(cxt.editor).language_name(), event);
         cxt.editor.autoinfo = self.keymaps.sticky().map(|node| node.infobox());
+        if matches!(
+            key_result,
+            KeymapResult::Matched(_) | KeymapResult::MatchedSequence(_)
+        ) {
+            if let Some(sequence) = self.insert_sequence.take() {
+                sequence.revert(cxt.editor);
+            }
+        }
 
-        let mut execute_command = |command: &commands::MappableCommand| {
-            command.execute(cxt);
-            helix_event::dispatch(PostCommand { command, cx: cxt });
-
-            let current_mode = cxt.editor.mode();
-            if current_mode != last_mode {
-                helix_event::dispatch(OnModeSwitch {
-                    old_mode: last_mode,
-                    new_mode: current_mode,
-                    cx: cxt,
-                });
+        let mut execute_command =
+            |cxt: &mut commands::Context, command: &commands::MappableCommand| {
+                command.execute(cxt);
+                helix_event::dispatch(PostCommand { command, cx: cxt });
+
+                let current_mode = cxt.editor.mode();
+                if current_mode != last_mode {
+                    helix_event::dispatch(OnModeSwitch {
+                        old_mode: last_mode,
+                        new_mode: current_mode,
+                        cx: cxt,
+                    });
 
-                // HAXX: if we just entered insert mode from normal, clear key buf
-                // and record the command that got us into this mode.
-                if current_mode == Mode::Insert {
-                    // how we entered insert mode is important, and we should track that so
-                    // we can repeat the side effect.
-                    self.last_insert.0 = command.clone();
-                    self.last_insert.1.clear();
+                    // HAXX: if we just entered insert mode from normal, clear key buf
+                    // and record the command that got us into this mode.
+                    if current_mode == Mode::Insert {
+                        // how we entered insert mode is important, and we should track that so
+                        // we can repeat the side effect.
+                        self.last_insert.0 = command.clone();
+                        self.last_insert.1.clear();
+                    }
                 }
-            }
 
-            last_mode = current_mode;
-        };
+                last_mode = current_mode;
+            };
 
         match &key_result {
             KeymapResult::Matched(command) => {
-                execute_command(command);
+                execute_command(cxt, command);
             }
             KeymapResult::Pending(node) => cxt.editor.autoinfo = Some(node.infobox()),
             KeymapResult::MatchedSequence(commands) => {
-                for command in commands {
-                    execute_command(command);
+                for (i, command) in commands.iter().enumerate() {
+                    let callbacks = cxt.callback.len();
+                    execute_command(cxt, command);
+
+                    // Commands like macros and custom commands run in a callback, so the
+                    // rest of the sequence has to run after it.
+                    if cxt.callback.len() > callbacks {
+                        let rest = commands[i + 1..].to_vec();
+                        if !rest.is_empty() {
+                            cxt.callback.push(Box::new(move |compositor, cx| {
+                                commands::execute_commands(cx, compositor, &rest, None)
+                            }));
+                        }
+                        break;
+                    }
                 }
             }
             KeymapResult::NotFound | KeymapResult::Cancelled(_) => return Some(key_result),
@@ -978,6 +1340,11 @@ fn handle_keymap_event(
 
     fn insert_mode(&mut self, cx: &mut commands::Context, event: KeyEvent) {
         if let Some(keyresult) = self.handle_keymap_event(Mode::Insert, cx, event) {
+            // The keys of a cancelled sequence that were inserted when they were typed.
+            let inserted = self
+                .insert_sequence
+                .take()
+                .map_or(0, |sequence| sequence.inserted);
             match keyresult {
                 KeymapResult::NotFound => {
                     if !self.on_next_key(OnKeyCallbackKind::Fallback, cx, event) {
@@ -987,22 +1354,94 @@ fn insert_mode(&mut self, cx: &mut commands::Context, event: KeyEvent) {
                     }
                 }
                 KeymapResult::Cancelled(pending) => {
-                    for ev in pending {
-                        match ev.char() {
-                            Some(ch) => commands::insert::insert_char(cx, ch),
-                            None => {
-                                if let KeymapResult::Matched(command) =
-                                    self.keymaps.get(Mode::Insert, ev)
-                                {
-                                    command.execute(cx);
-                                }
-                            }
-                        }
-                    }
+                    self.insert_keys(cx, &pending[inserted..]);
                 }
                 _ => unreachable!(),
             }
+        } else if !self.keymaps.pending().is_empty() {
+            self.continue_insert_sequence(cx, event);
+        }
+    }
+
+    /// Inserts the characters of the keys of a cancelled key sequence and executes the
+    /// commands of the other keys.
+    fn insert_keys(&mut self, cx: &mut commands::Context, keys: &[KeyEvent]) {
+        for &ev in keys {
+            match ev.char() {
+                Some(ch) => commands::insert::insert_char(cx, ch),
+                None => {
+                    let language = doc!(cx.editor).language_name();
+                    if let KeymapResult::Matched(command) =
+                        self.keymaps.get_for_language(Mode::Insert, language, ev)
+                    {
+                        command.execute(cx);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Inserts `event` if it continues a key sequence of typed characters in insert mode and
+    /// restarts the timeout of the sequence.
+    fn continue_insert_sequence(&mut self, cx: &mut commands::Context, event: KeyEvent) {
+        self.insert_sequence_count += 1;
+        let id = self.insert_sequence_count;
+        let pending = self.keymaps.pending().len();
+        let sequence = self.insert_sequence.get_or_insert(InsertSequence {
+            id,
+            inserted: 0,
+            inserted_keys: None,
+        });
+        sequence.id = id;
+
+        let is_typed = event.modifiers.difference(KeyModifiers::SHIFT).is_empty();
+        if let Some(ch) = event
+            .char()
+            .filter(|_| is_typed && sequence.inserted + 1 == pending)
+        {
+            sequence.insert(cx, ch);
+        }
+
+        let timeout = cx.editor.config().insert_sequence_timeout;
+        tokio::spawn(async move {
+            tokio::time::sleep(timeout).await;
+            job::dispatch_callback(job::Callback::Context(Box::new(move |cx, compositor| {
+                if let Some(editor_view) = compositor.find::<EditorView>() {
+                    editor_view.expire_insert_sequence(cx, id);
+                }
+            })))
+            .await;
+        });
+    }
+
+    /// Keeps the inserted keys of the key sequence `id` as text if it is still pending.
+    fn expire_insert_sequence(&mut self, cx: &mut Context, id: usize) {
+        if self
+            .insert_sequence
+            .as_ref()
+            .is_none_or(|sequence| sequence.id != id)
+        {
+            return;
+        }
+        let inserted = self.insert_sequence.take().unwrap().inserted;
+        let pending = self.keymaps.cancel_pending();
+        cx.editor.autoinfo = None;
+        if cx.editor.mode() != Mode::Insert {
+            return;
         }
+
+        let mut cx = commands::Context {
+            register: None,
+            count: None,
+            editor: cx.editor,
+            callback: Vec::new(),
+            on_next_key_callback: None,
+            jobs: cx.jobs,
+        };
+        self.insert_keys(&mut cx, &pending[inserted..]);
+        let scrolloff = cx.editor.config().scrolloff;
+        let (view, doc) = current!(cx.editor);
+        view.ensure_cursor_in_view(doc, scrolloff);
     }
 
     fn command_mode(&mut self, mode: Mode, cxt: &mut commands::Context, event: KeyEvent) {
@@ -1017,7 +1456,11 @@ fn command_mode(&mut self, mode: Mode, cxt: &mut commands::Context, event: KeyEv
                 cxt.editor.count = NonZeroUsize::new(count);
             }
             // A non-zero digit will start the count if that number isn't used by a keymap.
-            (key!(i @ '1'..='9'), None) if !self.keymaps.contains_key(mode, event) => {
+            (key!(i @ '1'..='9'), None)
+                if !self
+                    .keymaps
+                    .contains_key(mode, doc!(cxt.editor).language_name(), event) =>
+            {
                 let i = i.to_digit(10).unwrap() as usize;
                 cxt.editor.count = NonZeroUsize::new(i);
             }
@@ -1178,6 +1621,7 @@ fn handle_non_key_input(&mut self, cxt: &mut commands::Context) {
         }
         self.handle_keymap_event(cxt.editor.mode, cxt, null_key_event);
         self.pseudo_pending.clear();
+        self.insert_sequence = None;
     }
 
     fn handle_mouse_event(
@@ -1199,7 +1643,7 @@ fn handle_mouse_event(
         } = *event;
 
         let pos_and_view = |editor: &Editor, row, column, ignore_virtual_text| {
-            editor.tree.views().find_map(|(view, _focus)| {
+            editor.tree.visible_views().find_map(|(view, _focus)| {
                 view.pos_at_screen_coords(
                     &editor.documents[&view.doc],
                     row,
@@ -1211,7 +1655,7 @@ fn handle_mouse_event(
         };
 
         let gutter_coords_and_view = |editor: &Editor, row, column| {
-            editor.tree.views().find_map(|(view, _focus)| {
+            editor.tree.visible_views().find_map(|(view, _focus)| {
                 view.gutter_coords_at_screen_coords(row, column)
                     .map(|coords| (coords, view.id))
             })
@@ -1221,6 +1665,11 @@ fn handle_mouse_event(
             MouseEventKind::Down(MouseButton::Left) => {
                 let editor = &mut cxt.editor;
 
+                if let Some(border) = editor.tree.split_border_at(column, row) {
+                    self.dragged_border = Some(border);
+                    return EventResult::Consumed(None);
+                }
+
                 if let Some((pos, view_id)) = pos_and_view(editor, row, column, true) {
                     editor.focus(view_id);
 
@@ -1274,6 +1723,15 @@ fn handle_mouse_event(
             }
 
             MouseEventKind::Drag(MouseButton::Left) => {
+                if let Some((view_id, layout)) = self.dragged_border {
+                    if cxt.editor.tree.contains(view_id) {
+                        cxt.editor
+                            .tree
+                            .drag_split_border(view_id, layout, column, row);
+                    }
+                    return EventResult::Consumed(None);
+                }
+
                 let (view, doc) = current!(cxt.editor);
 
                 let pos = match view.pos_at_screen_coords(doc, row, column, true) {
@@ -1314,6 +1772,9 @@ fn handle_mouse_event(
             }
 
             MouseEventKind::Up(MouseButton::Left) => {
+                if self.dragged_border.take().is_some() {
+                    return EventResult::Consumed(None);
+                }
                 if !config.middle_click_paste {
                     return EventResult::Ignored(None);
                 }
@@ -1590,6 +2051,9 @@ fn handle_event(
                     }
                 }
                 self.terminal_focused = false;
+                helix_event::dispatch(TerminalFocusLost {
+                    editor: context.editor,
+                });
                 EventResult::Consumed(None)
             }
         }
@@ -1616,12 +2080,13 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
 
         // if the terminal size suddenly changed, we need to trigger a resize
         cx.editor.resize(editor_area);
+        cx.editor.sync_diff_scroll();
 
         if use_bufferline {
             Self::render_bufferline(cx.editor, area.with_height(1), surface);
         }
 
-        for (view, is_focused) in cx.editor.tree.views() {
+        for (view, is_focused) in cx.editor.tree.visible_views() {
             let doc = cx.editor.document(view.doc).unwrap();
             self.render_view(cx.editor, doc, view, area, surface, is_focused);
         }
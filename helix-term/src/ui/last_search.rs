@@ -0,0 +1,263 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+
+use helix_core::{LineEnding, RopeSlice};
+use helix_stdx::rope::{self, RopeSliceExt};
+use helix_view::{Document, DocumentId, Editor};
+
+/// The number of matches of the last search that are counted at most.
+const MAX_SEARCH_COUNT: usize = 999;
+/// The number of bytes of a document that are searched at most to count the matches.
+const MAX_SEARCH_BYTES: usize = 16 * 1024 * 1024;
+
+/// The regex of the last search and the matches counted in each document. Both are cached
+/// between renders: the regex until the query changes and the matches until the document
+/// changes.
+#[derive(Default)]
+pub struct LastSearch {
+    regex: RefCell<Option<CachedRegex>>,
+    matches: RefCell<HashMap<DocumentId, CachedMatches>>,
+}
+
+struct CachedRegex {
+    query: String,
+    case_insensitive: bool,
+    crlf: bool,
+    /// `None` if the query isn't a valid regex.
+    regex: Option<rope::Regex>,
+}
+
+struct CachedMatches {
+    /// The document version the matches were counted for.
+    version: i32,
+    /// The byte offsets of the first matches.
+    starts: Vec<usize>,
+    /// The end of the searched part of the document, the matches after it weren't counted.
+    searched: usize,
+    /// Whether all matches were counted.
+    complete: bool,
+}
+
+/// The position of the primary cursor among the matches of the last search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchPosition {
+    /// The number of matches that start at or before the cursor, `None` if the cursor is
+    /// after the counted matches.
+    pub current: Option<usize>,
+    pub total: usize,
+    /// Whether `total` is all matches of the document.
+    pub complete: bool,
+}
+
+impl LastSearch {
+    /// Returns the regex of the last search, used to highlight and count its matches in
+    /// `doc`.
+    pub fn regex(&self, editor: &Editor, doc: &Document) -> Option<rope::Regex> {
+        let query = editor
+            .registers
+            .first(editor.registers.last_search_register, editor)?;
+        if query.is_empty() {
+            return None;
+        }
+        let case_insensitive = if editor.config().search.smart_case {
+            !query.chars().any(char::is_uppercase)
+        } else {
+            false
+        };
+        let crlf = doc.line_ending == LineEnding::Crlf;
+
+        let mut cached = self.regex.borrow_mut();
+        let outdated = cached.as_ref().is_none_or(|cached| {
+            cached.query != query
+                || cached.case_insensitive != case_insensitive
+                || cached.crlf != crlf
+        });
+        if outdated {
+            let regex = rope::RegexBuilder::new()
+                .syntax(
+                    rope::Config::new()
+                        .case_insensitive(case_insensitive)
+                        .multi_line(true)
+                        .crlf(crlf),
+                )
+                .build(&query)
+                .ok();
+            *cached = Some(CachedRegex {
+                query: query.into_owned(),
+                case_insensitive,
+                crlf,
+                regex,
+            });
+            self.matches.borrow_mut().clear();
+        }
+        cached.as_ref()?.regex.clone()
+    }
+
+    /// Returns the position of the primary cursor of `view` among the matches of the last
+    /// search in `doc`.
+    pub fn position(
+        &self,
+        editor: &Editor,
+        doc: &Document,
+        view: &helix_view::View,
+    ) -> Option<SearchPosition> {
+        let regex = self.regex(editor, doc)?;
+        let text = doc.text().slice(..);
+        let mut matches = self.matches.borrow_mut();
+        // Forget the matches of closed documents.
+        matches.retain(|&id, _| editor.document(id).is_some());
+        let matches = matches
+            .entry(doc.id())
+            .and_modify(|matches| {
+                if matches.version != doc.version() {
+                    *matches = CachedMatches::count(&regex, text, doc.version());
+                }
+            })
+            .or_insert_with(|| CachedMatches::count(&regex, text, doc.version()));
+
+        let cursor = text.char_to_byte(doc.selection(view.id).primary().from());
+        matches.position(cursor)
+    }
+}
+
+impl CachedMatches {
+    fn count(regex: &rope::Regex, text: RopeSlice, version: i32) -> Self {
+        Self::count_at_most(regex, text, version, MAX_SEARCH_COUNT, MAX_SEARCH_BYTES)
+    }
+
+    /// Counts the matches of `regex` in `text`, at most `max_count` matches in the first
+    /// `max_bytes` bytes.
+    fn count_at_most(
+        regex: &rope::Regex,
+        text: RopeSlice,
+        version: i32,
+        max_count: usize,
+        max_bytes: usize,
+    ) -> Self {
+        let mut searched = text.len_bytes().min(max_bytes);
+        let mut starts: Vec<_> = regex
+            .find_iter(text.regex_input_at_bytes(..searched))
+            .filter(|mat| !mat.is_empty())
+            .map(|mat| mat.start())
+            .take(max_count + 1)
+            .collect();
+        if starts.len() > max_count {
+            // The search stopped at the first match that isn't counted.
+            searched = starts.pop().unwrap();
+        }
+        Self {
+            version,
+            complete: searched == text.len_bytes(),
+            searched,
+            starts,
+        }
+    }
+
+    /// Returns the position of the byte offset `cursor` among the matches.
+    fn position(&self, cursor: usize) -> Option<SearchPosition> {
+        if self.starts.is_empty() {
+            return None;
+        }
+        let current = self.starts.partition_point(|&start| start <= cursor);
+        Some(SearchPosition {
+            current: (self.complete || cursor < self.searched).then_some(current),
+            total: self.starts.len(),
+            complete: self.complete,
+        })
+    }
+}
+
+impl fmt::Display for SearchPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.current {
+            Some(current) => write!(f, "[{current}/")?,
+            None => write!(f, "[>{}/", self.total)?,
+        }
+        if self.complete {
+            write!(f, "{}]", self.total)
+        } else {
+            write!(f, ">{}]", self.total)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use helix_core::Rope;
+
+    fn count(query: &str, text: &str, max_count: usize, max_bytes: usize) -> CachedMatches {
+        let regex = rope::Regex::new(query).unwrap();
+        CachedMatches::count_at_most(&regex, Rope::from(text).slice(..), 0, max_count, max_bytes)
+    }
+
+    fn position(matches: &CachedMatches, cursor: usize) -> Option<String> {
+        matches
+            .position(cursor)
+            .map(|position| position.to_string())
+    }
+
+    #[test]
+    fn count_all_matches() {
+        let matches = count("a", "xa a a", 10, 100);
+        assert_eq!(matches.starts, [1, 3, 5]);
+        assert!(matches.complete);
+        assert_eq!(matches.searched, 6);
+
+        // A cursor before the first match is at none of the matches.
+        assert_eq!(position(&matches, 0).as_deref(), Some("[0/3]"));
+        assert_eq!(position(&matches, 1).as_deref(), Some("[1/3]"));
+        assert_eq!(position(&matches, 2).as_deref(), Some("[1/3]"));
+        assert_eq!(position(&matches, 5).as_deref(), Some("[3/3]"));
+    }
+
+    #[test]
+    fn count_no_matches() {
+        let matches = count("b", "aaa", 10, 100);
+        assert!(matches.starts.is_empty());
+        assert_eq!(matches.position(0), None);
+
+        // Empty matches aren't counted.
+        let matches = count("b*", "aba", 10, 100);
+        assert_eq!(matches.starts, [1]);
+    }
+
+    #[test]
+    fn count_at_most_max_count() {
+        // Exactly `max_count` matches are all matches.
+        let matches = count("a", "a a", 2, 100);
+        assert!(matches.complete);
+        assert_eq!(position(&matches, 2).as_deref(), Some("[2/2]"));
+
+        let matches = count("a", "a a a a", 2, 100);
+        assert_eq!(matches.starts, [0, 2]);
+        assert!(!matches.complete);
+        assert_eq!(matches.searched, 4);
+        assert_eq!(position(&matches, 2).as_deref(), Some("[2/>2]"));
+        // The cursor is after the counted matches.
+        assert_eq!(position(&matches, 4).as_deref(), Some("[>2/>2]"));
+        assert_eq!(
+            matches.position(6),
+            Some(SearchPosition {
+                current: None,
+                total: 2,
+                complete: false,
+            })
+        );
+    }
+
+    #[test]
+    fn count_at_most_max_bytes() {
+        let matches = count("a", "a a a", 10, 3);
+        assert_eq!(matches.starts, [0, 2]);
+        assert!(!matches.complete);
+        assert_eq!(matches.searched, 3);
+        assert_eq!(position(&matches, 2).as_deref(), Some("[2/>2]"));
+        assert_eq!(position(&matches, 4).as_deref(), Some("[>2/>2]"));
+
+        // Matches are only counted within the searched bytes.
+        let matches = count("aa", "a aa", 10, 3);
+        assert!(matches.starts.is_empty());
+    }
+}
@@ -2,6 +2,7 @@
 mod document;
 pub(crate) mod editor;
 mod info;
+mod last_search;
 pub mod lsp;
 mod markdown;
 pub mod menu;
@@ -22,6 +23,7 @@
 pub use editor::EditorView;
 use helix_stdx::rope;
 use helix_view::theme::Style;
+pub use last_search::LastSearch;
 pub use markdown::Markdown;
 pub use menu::Menu;
 pub use picker::{Column as PickerColumn, FileLocation, Picker};
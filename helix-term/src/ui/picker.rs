@@ -259,6 +259,12 @@ pub struct Picker<T: 'static + Send + Sync, D: 'static> {
 
     callback_fn: PickerCallback<T>,
     default_action: Action,
+    /// Called to expand the selected item into a new picker, for example to drill into
+    /// the next level of a hierarchy.
+    expand_fn: Option<PickerExpandCallback<T>>,
+    /// Called to toggle whether the selected item is marked, for example to choose which
+    /// matches of a global replace are applied.
+    toggle_fn: Option<PickerToggleCallback<T>>,
 
     pub truncate_start: bool,
     /// Caches paths to documents
@@ -387,6 +393,8 @@ fn with(
             show_preview: true,
             callback_fn: Box::new(callback_fn),
             default_action: Action::Replace,
+            expand_fn: None,
+            toggle_fn: None,
             completion_height: 0,
             widths,
             preview_cache: HashMap::new(),
@@ -455,6 +463,20 @@ pub fn with_default_action(mut self, action: Action) -> Self {
         self
     }
 
+    /// Allows expanding the selected item with `Ctrl-l`. The picker is closed afterwards
+    /// so the expand callback may push a replacement picker.
+    pub fn with_expand(mut self, expand_fn: impl Fn(&mut Context, &T) + 'static) -> Self {
+        self.expand_fn = Some(Box::new(expand_fn));
+        self
+    }
+
+    /// Allows toggling the selected item with `Ctrl-x`, which also moves to the next item.
+    /// Items track their own state so that columns can display it.
+    pub fn with_toggle(mut self, toggle_fn: impl Fn(&T) + 'static) -> Self {
+        self.toggle_fn = Some(Box::new(toggle_fn));
+        self
+    }
+
     /// Move the cursor by a number of lines, either down (`Forward`) or up (`Backward`)
     pub fn move_by(&mut self, amount: u32, direction: Direction) {
         let len = self.matcher.snapshot().matched_item_count();
@@ -1162,6 +1184,18 @@ fn handle_event(&mut self, event: &Event, ctx: &mut Context) -> EventResult {
             ctrl!('t') => {
                 self.toggle_preview();
             }
+            ctrl!('l') if self.expand_fn.is_some() => {
+                if let (Some(option), Some(expand_fn)) = (self.selection(), &self.expand_fn) {
+                    expand_fn(ctx, option);
+                }
+                return close_fn(self);
+            }
+            ctrl!('x') if self.toggle_fn.is_some() => {
+                if let (Some(option), Some(toggle_fn)) = (self.selection(), &self.toggle_fn) {
+                    toggle_fn(option);
+                }
+                self.move_by(1, Direction::Forward);
+            }
             _ => {
                 self.prompt_handle_event(event, ctx);
             }
@@ -1206,3 +1240,5 @@ fn drop(&mut self) {
 }
 
 type PickerCallback<T> = Box<dyn Fn(&mut Context, &T, Action)>;
+type PickerExpandCallback<T> = Box<dyn Fn(&mut Context, &T)>;
+type PickerToggleCallback<T> = Box<dyn Fn(&T)>;
@@ -9,7 +9,7 @@
     Document, Editor, View,
 };
 
-use crate::ui::ProgressSpinners;
+use crate::ui::{LastSearch, ProgressSpinners};
 
 use helix_view::editor::StatusLineElement as StatusLineElementID;
 use tui::buffer::Buffer as Surface;
@@ -21,6 +21,9 @@ pub struct RenderContext<'a> {
     pub view: &'a View,
     pub focused: bool,
     pub spinners: &'a ProgressSpinners,
+    pub last_search: &'a LastSearch,
+    /// The name of the active sticky minor mode.
+    pub minor_mode: Option<&'a str>,
     pub parts: RenderBuffer<'a>,
 }
 
@@ -31,6 +34,7 @@ pub fn new(
         view: &'a View,
         focused: bool,
         spinners: &'a ProgressSpinners,
+        last_search: &'a LastSearch,
     ) -> Self {
         RenderContext {
             editor,
@@ -38,6 +42,8 @@ pub fn new(
             view,
             focused,
             spinners,
+            last_search,
+            minor_mode: None,
             parts: RenderBuffer::default(),
         }
     }
@@ -131,6 +137,7 @@ fn get_render_function<'a, F>(element_id: StatusLineElementID) -> impl Fn(&mut R
 {
     match element_id {
         helix_view::editor::StatusLineElement::Mode => render_mode,
+        helix_view::editor::StatusLineElement::MinorMode => render_minor_mode,
         helix_view::editor::StatusLineElement::Spinner => render_lsp_spinner,
         helix_view::editor::StatusLineElement::FileBaseName => render_file_base_name,
         helix_view::editor::StatusLineElement::FileName => render_file_name,
@@ -157,6 +164,7 @@ fn get_render_function<'a, F>(element_id: StatusLineElementID) -> impl Fn(&mut R
         helix_view::editor::StatusLineElement::VersionControl => render_version_control,
         helix_view::editor::StatusLineElement::Register => render_register,
         helix_view::editor::StatusLineElement::CurrentWorkingDirectory => render_cwd,
+        helix_view::editor::StatusLineElement::SearchPosition => render_search_position,
     }
 }
 
@@ -190,6 +198,15 @@ fn render_mode<'a, F>(context: &mut RenderContext<'a>, write: F)
     write(context, Span::styled(content, style));
 }
 
+fn render_minor_mode<'a, F>(context: &mut RenderContext<'a>, write: F)
+where
+    F: Fn(&mut RenderContext<'a>, Span<'a>) + Copy,
+{
+    if let Some(name) = context.minor_mode.filter(|name| !name.is_empty()) {
+        write(context, format!(" {name} ").into());
+    }
+}
+
 fn render_lsp_spinner<'a, F>(context: &mut RenderContext<'a>, write: F)
 where
     F: Fn(&mut RenderContext<'a>, Span<'a>) + Copy,
@@ -343,6 +360,20 @@ fn render_selections<'a, F>(context: &mut RenderContext<'a>, write: F)
     );
 }
 
+/// Matches are only counted up to this limit so the element stays fast in large files.
+fn render_search_position<'a, F>(context: &mut RenderContext<'a>, write: F)
+where
+    F: Fn(&mut RenderContext<'a>, Span<'a>) + Copy,
+{
+    let Some(position) = context
+        .last_search
+        .position(context.editor, context.doc, context.view)
+    else {
+        return;
+    };
+    write(context, format!(" {position} ").into());
+}
+
 fn render_primary_selection_length<'a, F>(context: &mut RenderContext<'a>, write: F)
 where
     F: Fn(&mut RenderContext<'a>, Span<'a>) + Copy,
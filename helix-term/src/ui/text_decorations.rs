@@ -6,9 +6,15 @@
 
 use crate::ui::document::{LinePos, TextRenderer};
 
+pub use blame::InlineBlame;
+pub use code_lens::CodeLenses;
 pub use diagnostics::InlineDiagnostics;
+pub use diff::DiffFillers;
 
+mod blame;
+mod code_lens;
 mod diagnostics;
+mod diff;
 
 /// Decorations are the primary mechanism for extending the text rendering.
 ///
@@ -63,6 +69,19 @@ fn render_virt_lines(
         Position::new(0, 0)
     }
 
+    /// Called before the first line of the document is rendered to render the virtual lines
+    /// above it, which are reserved by
+    /// [`LineAnnotation::insert_virtual_lines_above_text`](helix_core::text_annotations::LineAnnotation::insert_virtual_lines_above_text).
+    /// Like [`Decoration::render_virt_lines`] this function must return the number of virtual
+    /// text lines it has taken up and may render virtual text starting at row `virt_off.row`.
+    fn render_virt_lines_above_text(
+        &mut self,
+        _renderer: &mut TextRenderer,
+        _virt_off: Position,
+    ) -> Position {
+        Position::new(0, 0)
+    }
+
     fn reset_pos(&mut self, _pos: usize) -> usize {
         usize::MAX
     }
@@ -141,6 +160,13 @@ pub fn render_virtual_lines(
             virt_off += decoration.render_virt_lines(renderer, pos, virt_off);
         }
     }
+
+    pub fn render_virtual_lines_above_text(&mut self, renderer: &mut TextRenderer) {
+        let mut virt_off = Position::new(0, 0);
+        for (decoration, _) in &mut self.decorations {
+            virt_off += decoration.render_virt_lines_above_text(renderer, virt_off);
+        }
+    }
 }
 
 /// Cursor rendering is done externally so all the cursor decoration
@@ -0,0 +1,89 @@
+use helix_core::doc_formatter::FormattedGrapheme;
+use helix_core::line_ending::line_end_char_index;
+use helix_core::unicode::width::UnicodeWidthStr;
+use helix_core::Position;
+use helix_view::document::LineBlame;
+use helix_view::editor::InlineBlameConfig;
+use helix_view::theme::Style;
+use helix_view::{Document, Theme};
+
+use crate::ui::document::{LinePos, TextRenderer};
+use crate::ui::text_decorations::Decoration;
+
+/// Space between the end of the line and the blame.
+const PADDING: usize = 4;
+
+/// Renders the commit that last changed the cursor line at the end of the line.
+pub struct InlineBlame {
+    /// The char index of the end of the cursor line.
+    anchor: usize,
+    text: Option<String>,
+    pending: Option<String>,
+    style: Style,
+}
+
+impl InlineBlame {
+    pub fn new(
+        doc: &Document,
+        theme: &Theme,
+        cursor: usize,
+        config: &InlineBlameConfig,
+    ) -> Option<Self> {
+        let text = doc.text().slice(..);
+        let line = text.char_to_line(cursor);
+        let blame = match doc.line_blame(line)? {
+            LineBlame::Committed(commit) => config.format_commit(commit),
+            LineBlame::Uncommitted => "Not committed yet".to_owned(),
+        };
+        Some(InlineBlame {
+            anchor: line_end_char_index(&text, line),
+            text: Some(blame),
+            pending: None,
+            style: theme.get("ui.virtual.inline-blame"),
+        })
+    }
+}
+
+impl Decoration for InlineBlame {
+    fn render_virt_lines(
+        &mut self,
+        renderer: &mut TextRenderer,
+        pos: LinePos,
+        virt_off: Position,
+    ) -> Position {
+        let Some(text) = self.pending.take() else {
+            return Position::new(0, 0);
+        };
+        let col = virt_off.col + PADDING;
+        if !renderer.column_in_bounds(col, 1) {
+            return Position::new(0, 0);
+        }
+        let x = (col - renderer.offset.col) as u16;
+        let width = renderer.viewport.width.saturating_sub(x) as usize;
+        renderer.set_stringn(
+            renderer.viewport.x + x,
+            pos.visual_line,
+            &text,
+            width,
+            self.style,
+        );
+        Position::new(0, PADDING + text.width())
+    }
+
+    fn reset_pos(&mut self, pos: usize) -> usize {
+        if pos <= self.anchor {
+            self.anchor
+        } else {
+            usize::MAX
+        }
+    }
+
+    fn decorate_grapheme(
+        &mut self,
+        _renderer: &mut TextRenderer,
+        _grapheme: &FormattedGrapheme,
+    ) -> usize {
+        self.pending = self.text.take();
+        usize::MAX
+    }
+}
@@ -0,0 +1,121 @@
+use helix_core::doc_formatter::FormattedGrapheme;
+use helix_core::{line_ending::line_end_char_index, Position, RopeSlice};
+use helix_view::annotations::code_lens::CodeLensLine;
+use helix_view::theme::Style;
+use helix_view::{Document, Theme};
+
+use crate::ui::document::{LinePos, TextRenderer};
+use crate::ui::text_decorations::Decoration;
+
+/// Renders code lenses into the virtual lines reserved by
+/// [`helix_view::annotations::code_lens::CodeLenses`] above the lines they
+/// apply to.
+pub struct CodeLenses<'a> {
+    text: RopeSlice<'a>,
+    tab_width: usize,
+    /// The lenses of the first line, which are rendered above the document.
+    first_line: Option<&'a CodeLensLine>,
+    /// The lenses of all other lines.
+    lines: &'a [CodeLensLine],
+    /// Index into `lines` of the next line whose lenses are rendered.
+    next: usize,
+    /// Index into `lines` of the lenses to render after the current visual line.
+    pending: Option<usize>,
+    style: Style,
+}
+
+impl<'a> CodeLenses<'a> {
+    pub fn new(doc: &'a Document, theme: &Theme) -> Self {
+        let lines = doc.code_lens_lines();
+        let first_line = lines.first().filter(|lens_line| lens_line.line == 0);
+        CodeLenses {
+            text: doc.text().slice(..),
+            tab_width: doc.tab_width(),
+            first_line,
+            lines: &lines[first_line.is_some() as usize..],
+            next: 0,
+            pending: None,
+            style: theme.get("ui.virtual.code-lens"),
+        }
+    }
+
+    /// The char index of the line ending before the line at `idx`.
+    fn anchor(&self, idx: usize) -> usize {
+        self.lines.get(idx).map_or(usize::MAX, |lens_line| {
+            line_end_char_index(&self.text, lens_line.line - 1)
+        })
+    }
+
+    /// Renders the titles of `lens_line` aligned with the indentation of its line.
+    fn render_lens(&self, renderer: &mut TextRenderer, lens_line: &CodeLensLine, row: u16) {
+        let col = indent_width(self.text, lens_line.line, self.tab_width);
+        let titles = lens_line.titles.join(" | ");
+        let horizontal_offset = renderer.offset.col;
+        let (col, text) = if col >= horizontal_offset {
+            ((col - horizontal_offset) as u16, titles)
+        } else {
+            let skip = horizontal_offset - col;
+            (0, titles.chars().skip(skip).collect())
+        };
+        let width = renderer.viewport.width.saturating_sub(col) as usize;
+        renderer.set_stringn(renderer.viewport.x + col, row, text, width, self.style);
+    }
+}
+
+/// The visual width of the indentation of `line`.
+fn indent_width(text: RopeSlice, line: usize, tab_width: usize) -> usize {
+    let mut width = 0;
+    for ch in text.line(line).chars() {
+        match ch {
+            ' ' => width += 1,
+            '\t' => width += tab_width - width % tab_width,
+            _ => break,
+        }
+    }
+    width
+}
+
+impl Decoration for CodeLenses<'_> {
+    fn render_virt_lines(
+        &mut self,
+        renderer: &mut TextRenderer,
+        pos: LinePos,
+        virt_off: Position,
+    ) -> Position {
+        let Some(idx) = self.pending.take() else {
+            return Position::new(0, 0);
+        };
+        let row = pos.visual_line + virt_off.row as u16;
+        self.render_lens(renderer, &self.lines[idx], row);
+        Position::new(1, 0)
+    }
+
+    fn render_virt_lines_above_text(
+        &mut self,
+        renderer: &mut TextRenderer,
+        virt_off: Position,
+    ) -> Position {
+        let Some(lens_line) = self.first_line else {
+            return Position::new(0, 0);
+        };
+        self.render_lens(renderer, lens_line, virt_off.row as u16);
+        Position::new(1, 0)
+    }
+
+    fn reset_pos(&mut self, pos: usize) -> usize {
+        self.next = self
+            .lines
+            .partition_point(|lens_line| line_end_char_index(&self.text, lens_line.line - 1) < pos);
+        self.anchor(self.next)
+    }
+
+    fn decorate_grapheme(
+        &mut self,
+        _renderer: &mut TextRenderer,
+        _grapheme: &FormattedGrapheme,
+    ) -> usize {
+        self.pending = Some(self.next);
+        self.next += 1;
+        self.anchor(self.next)
+    }
+}
@@ -0,0 +1,92 @@
+use helix_core::doc_formatter::FormattedGrapheme;
+use helix_core::{line_ending::line_end_char_index, Position, RopeSlice};
+use helix_view::annotations::diff::{diff_fillers, top_diff_fillers, DiffFiller};
+use helix_view::graphics::Rect;
+use helix_view::theme::Style;
+use helix_view::{Document, Theme};
+
+use crate::ui::document::{LinePos, TextRenderer};
+use crate::ui::text_decorations::Decoration;
+
+/// Renders the filler lines reserved by [`helix_view::annotations::diff::DiffFillers`]
+/// for the lines that only exist in the other document of a diff.
+pub struct DiffFillers<'a> {
+    text: RopeSlice<'a>,
+    fillers: Vec<DiffFiller>,
+    /// The number of filler lines above the first line.
+    top_fillers: usize,
+    /// Index into `fillers` of the next filler that is rendered.
+    next: usize,
+    /// The number of filler lines to render after the current visual line.
+    pending: Option<usize>,
+    style: Style,
+}
+
+impl<'a> DiffFillers<'a> {
+    pub fn new(doc: &'a Document, theme: &Theme) -> Self {
+        DiffFillers {
+            text: doc.text().slice(..),
+            fillers: diff_fillers(doc),
+            top_fillers: top_diff_fillers(doc),
+            next: 0,
+            pending: None,
+            style: theme.get("ui.diff.filler"),
+        }
+    }
+
+    fn anchor(&self, idx: usize) -> usize {
+        self.fillers.get(idx).map_or(usize::MAX, |filler| {
+            line_end_char_index(&self.text, filler.line)
+        })
+    }
+
+    fn render_fillers(&self, renderer: &mut TextRenderer, row: u16, count: usize) {
+        let width = renderer.viewport.width;
+        let fill = "╱".repeat(width as usize);
+        for row in row..row + count as u16 {
+            renderer.set_style(Rect::new(renderer.viewport.x, row, width, 1), self.style);
+            renderer.set_stringn(renderer.viewport.x, row, &fill, width as usize, self.style);
+        }
+    }
+}
+
+impl Decoration for DiffFillers<'_> {
+    fn render_virt_lines(
+        &mut self,
+        renderer: &mut TextRenderer,
+        pos: LinePos,
+        virt_off: Position,
+    ) -> Position {
+        let Some(count) = self.pending.take() else {
+            return Position::new(0, 0);
+        };
+        self.render_fillers(renderer, pos.visual_line + virt_off.row as u16, count);
+        Position::new(count, 0)
+    }
+
+    fn render_virt_lines_above_text(
+        &mut self,
+        renderer: &mut TextRenderer,
+        virt_off: Position,
+    ) -> Position {
+        self.render_fillers(renderer, virt_off.row as u16, self.top_fillers);
+        Position::new(self.top_fillers, 0)
+    }
+
+    fn reset_pos(&mut self, pos: usize) -> usize {
+        self.next = self
+            .fillers
+            .partition_point(|filler| line_end_char_index(&self.text, filler.line) < pos);
+        self.anchor(self.next)
+    }
+
+    fn decorate_grapheme(
+        &mut self,
+        _renderer: &mut TextRenderer,
+        _grapheme: &FormattedGrapheme,
+    ) -> usize {
+        self.pending = Some(self.fillers[self.next].count);
+        self.next += 1;
+        self.anchor(self.next)
+    }
+}
@@ -17,8 +17,11 @@ async fn hello_world() -> anyhow::Result<()> {
 
     mod auto_indent;
     mod auto_pairs;
+    mod code_lens;
     mod command_line;
     mod commands;
+    mod dap;
+    mod debug_console;
     mod languages;
     mod movement;
     mod splits;
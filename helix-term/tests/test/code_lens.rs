@@ -0,0 +1,166 @@
+use helix_lsp::{lsp, LanguageServerId};
+use helix_term::application::Application;
+use helix_view::{
+    annotations::code_lens::CodeLensLine, current_ref, doc, doc_mut, document::DocumentCodeLens,
+    editor::LspConfig, view, view::ViewPosition, Document, Editor,
+};
+
+use super::*;
+
+fn set_code_lenses(editor: &mut Editor, lenses: &[(usize, &str)]) {
+    let doc = doc_mut!(editor);
+    doc.code_lenses = lenses
+        .iter()
+        .map(|&(start, title)| DocumentCodeLens {
+            start,
+            lens: lsp::CodeLens {
+                range: lsp::Range::default(),
+                command: Some(lsp::Command {
+                    title: title.to_string(),
+                    command: "test".to_string(),
+                    arguments: None,
+                }),
+                data: None,
+            },
+            language_server_id: LanguageServerId::default(),
+        })
+        .collect();
+    doc.refresh_code_lens_lines();
+}
+
+fn lens_starts(doc: &Document) -> Vec<usize> {
+    doc.code_lenses.iter().map(|lens| lens.start).collect()
+}
+
+fn lens_line(line: usize, titles: &[&str]) -> CodeLensLine {
+    CodeLensLine {
+        line,
+        titles: titles.iter().map(|title| title.to_string()).collect(),
+    }
+}
+
+fn view_offset(app: &Application) -> ViewPosition {
+    let (view, doc) = current_ref!(app.editor);
+    doc.view_offset(view.id)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_code_lens_positions() -> anyhow::Result<()> {
+    let mut app = AppBuilder::new()
+        .with_input_text("#[a|]#\nb\nc\n")
+        .build()?;
+    set_code_lenses(&mut app.editor, &[(2, "b"), (4, "c")]);
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (
+                Some("Ox<esc>"),
+                Some(&|app| {
+                    let doc = doc!(app.editor);
+                    assert_eq!(lens_starts(doc), [4, 6]);
+                    assert_eq!(
+                        doc.code_lens_lines(),
+                        [lens_line(2, &["b"]), lens_line(3, &["c"])]
+                    );
+                }),
+            ),
+            // The lenses of a deleted line move to the next line.
+            (
+                Some("jjxd"),
+                Some(&|app| {
+                    let doc = doc!(app.editor);
+                    assert_eq!(doc.text(), "x\na\nc\n");
+                    assert_eq!(lens_starts(doc), [4, 4]);
+                    assert_eq!(doc.code_lens_lines(), [lens_line(2, &["b", "c"])]);
+                }),
+            ),
+        ],
+        false,
+    )
+    .await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_first_line_code_lens_scrolling() -> anyhow::Result<()> {
+    let config = Config {
+        editor: helix_view::editor::Config {
+            lsp: LspConfig {
+                display_code_lenses: true,
+                ..test_editor_config().lsp
+            },
+            ..test_editor_config()
+        },
+        ..test_config()
+    };
+    let text: String = (0..500).map(|i| format!("line {i}\n")).collect();
+    let mut app = AppBuilder::new()
+        .with_config(config)
+        .with_input_text(format!("#[l|]#{}", &text[1..]))
+        .build()?;
+    set_code_lenses(&mut app.editor, &[(0, "lens")]);
+
+    // The lens is displayed in the first row, the line of the cursor is the last line
+    // that is displayed below it within the scrolloff.
+    let height = view!(app.editor).inner_height();
+    let line = height - 1 - app.editor.config().scrolloff;
+    let top = |vertical_offset| ViewPosition {
+        anchor: 0,
+        horizontal_offset: 0,
+        vertical_offset,
+    };
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (
+                Some(&format!("{line}gg")),
+                Some(&|app| {
+                    assert_eq!(view_offset(app), top(0));
+                    let (view, doc) = current_ref!(app.editor);
+                    let cursor = doc
+                        .selection(view.id)
+                        .primary()
+                        .cursor(doc.text().slice(..));
+                    assert_eq!(doc.text().char_to_line(cursor), line - 1);
+                }),
+            ),
+            // The view scrolls the lens out of view to keep the cursor within the scrolloff.
+            (
+                Some("j"),
+                Some(&|app| {
+                    assert_eq!(view_offset(app), top(1));
+                }),
+            ),
+            (
+                Some("zj"),
+                Some(&|app| {
+                    let (view, doc) = current_ref!(app.editor);
+                    assert_eq!(
+                        doc.view_offset(view.id),
+                        ViewPosition {
+                            anchor: doc.text().line_to_char(1),
+                            horizontal_offset: 0,
+                            vertical_offset: 0,
+                        }
+                    );
+                }),
+            ),
+            // Scrolling up reveals the lens and moves the cursor into view.
+            (
+                Some("zkzk"),
+                Some(&|app| {
+                    assert_eq!(view_offset(app), top(0));
+                    let (view, doc) = current_ref!(app.editor);
+                    let cursor = doc
+                        .selection(view.id)
+                        .primary()
+                        .cursor(doc.text().slice(..));
+                    assert_eq!(doc.text().char_to_line(cursor), line - 1);
+                }),
+            ),
+        ],
+        false,
+    )
+    .await
+}
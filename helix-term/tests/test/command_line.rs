@@ -158,3 +158,64 @@ async fn percent_escaping() -> anyhow::Result<()> {
     .await?;
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn nested_custom_commands() -> anyhow::Result<()> {
+    use helix_term::config::CustomCommand;
+    use helix_term::keymap::KeyTrie;
+    use helix_view::document::Mode;
+
+    let custom = |commands: &[&str]| CustomCommand {
+        doc: String::new(),
+        commands: commands.iter().map(|cmd| cmd.parse().unwrap()).collect(),
+    };
+    let mut config = helpers::test_config();
+    config
+        .commands
+        .insert("inner".to_string(), custom(&[":set-register a inner"]));
+    config.commands.insert(
+        "outer".to_string(),
+        custom(&[":inner", ":set-register a %reg{a}-outer"]),
+    );
+    config
+        .keys
+        .get_mut(&Mode::Normal)
+        .and_then(KeyTrie::node_mut)
+        .unwrap()
+        .insert(
+            "C-t".parse()?,
+            KeyTrie::Sequence(vec![
+                ":outer".parse()?,
+                ":set-register b %reg{a}-key".parse()?,
+            ]),
+        );
+
+    // The commands of `:inner` run before the command following it in `:outer`.
+    test_key_sequence(
+        &mut AppBuilder::new().with_config(config.clone()).build()?,
+        Some(":outer<ret>"),
+        Some(&|app| {
+            helpers::assert_status_not_error(&app.editor);
+            let register = app.editor.registers.first('a', &app.editor);
+            assert_eq!(Some("inner-outer"), register.as_deref());
+        }),
+        false,
+    )
+    .await?;
+
+    // The commands of a custom command in a key binding run before the next command of
+    // the binding.
+    test_key_sequence(
+        &mut AppBuilder::new().with_config(config).build()?,
+        Some("<C-t>"),
+        Some(&|app| {
+            helpers::assert_status_not_error(&app.editor);
+            let register = app.editor.registers.first('b', &app.editor);
+            assert_eq!(Some("inner-outer-key"), register.as_deref());
+        }),
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
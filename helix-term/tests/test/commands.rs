@@ -2,6 +2,8 @@
 
 use super::*;
 
+use helix_core::diagnostic::Severity;
+
 mod insert;
 mod movement;
 mod reverse_selection_contents;
@@ -869,6 +871,74 @@ async fn global_search_with_multibyte_chars() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn global_replace_apply() -> anyhow::Result<()> {
+    // Global replace searches the working directory. The name of the directory is used as the
+    // pattern so that only the files of this test match.
+    let dir = tempfile::Builder::new()
+        .prefix("global_replace")
+        .tempdir_in(helix_stdx::env::current_working_dir())?;
+    let pattern = dir
+        .path()
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .into_owned();
+    let contents = format!("one {pattern} two\n");
+    let open = dir.path().join("open.txt");
+    let closed = dir.path().join("closed.txt");
+    let changed = dir.path().join("changed.txt");
+    for path in [&open, &closed, &changed] {
+        std::fs::write(path, &contents)?;
+    }
+
+    let mut app = AppBuilder::new().with_file(&open, None).build()?;
+    test_key_sequences(
+        &mut app,
+        vec![
+            (Some(&format!("<space><A-/>{pattern}<ret>")), None),
+            (
+                Some("three<ret>"),
+                Some(&|_| {
+                    // The picker is open, change a file before the matches are applied.
+                    std::fs::write(&changed, "changed\n").unwrap();
+                }),
+            ),
+            (
+                Some("<ret>"),
+                Some(&|app| {
+                    let (status, &severity) = app.editor.get_status().unwrap();
+                    assert_eq!(severity, Severity::Error);
+                    assert_eq!(
+                        status.as_ref(),
+                        format!(
+                            "Replaced 2 matches, {} changed since the search",
+                            changed.display()
+                        )
+                    );
+
+                    // Open documents are only modified.
+                    let doc = app.editor.document_by_path(&open).unwrap();
+                    assert!(doc.is_modified());
+                    assert_eq!(doc.text(), "one three two\n");
+                    assert_eq!(std::fs::read_to_string(&open).unwrap(), contents);
+
+                    // Other files are saved and closed again.
+                    assert!(app.editor.document_by_path(&closed).is_none());
+                    assert_eq!(std::fs::read_to_string(&closed).unwrap(), "one three two\n");
+
+                    assert!(app.editor.document_by_path(&changed).is_none());
+                    assert_eq!(std::fs::read_to_string(&changed).unwrap(), "changed\n");
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn align_selections_with_varying_columns() -> anyhow::Result<()> {
     test((
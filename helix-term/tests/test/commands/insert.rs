@@ -1,4 +1,7 @@
 use super::*;
+use std::time::Duration;
+
+use helix_view::{current_ref, doc};
 
 #[tokio::test(flavor = "multi_thread")]
 async fn change_line_above_comment() -> anyhow::Result<()> {
@@ -639,3 +642,106 @@ async fn test_indent_with_spaces() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+fn insert_sequence_config(timeout: Duration) -> Config {
+    use helix_core::hashmap;
+    use helix_term::keymap;
+    use helix_view::document::Mode;
+
+    let mut config = helpers::test_config();
+    config.keys.insert(
+        Mode::Insert,
+        keymap!({"Insert Mode"
+            "j" => { "Escape"
+                "k" => normal_mode,
+            },
+        }),
+    );
+    config.editor.insert_sequence_timeout = timeout;
+    config
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_insert_sequence_completed() -> anyhow::Result<()> {
+    use helix_view::document::Mode;
+
+    let mut app = AppBuilder::new()
+        .with_config(insert_sequence_config(Duration::from_secs(10)))
+        .with_input_text("#[h|]#ello")
+        .build()?;
+
+    test_key_sequence(
+        &mut app,
+        Some("iajk"),
+        Some(&|app| {
+            assert_eq!(Mode::Normal, app.editor.mode());
+            assert_eq!("ahello", doc!(app.editor).text().to_string());
+            helpers::assert_status_not_error(&app.editor);
+        }),
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_insert_sequence_cancelled() -> anyhow::Result<()> {
+    use helix_view::document::Mode;
+
+    let mut app = AppBuilder::new()
+        .with_config(insert_sequence_config(Duration::from_secs(10)))
+        .with_input_text("#[h|]#ello")
+        .build()?;
+
+    test_key_sequence(
+        &mut app,
+        Some("ijx"),
+        Some(&|app| {
+            let (view, doc) = current_ref!(app.editor);
+            assert_eq!(Mode::Insert, app.editor.mode());
+            assert_eq!("jxhello", doc.text().to_string());
+            assert_eq!(&Selection::single(3, 2), doc.selection(view.id));
+            assert!(app.editor.autoinfo.is_none());
+        }),
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_insert_sequence_timeout() -> anyhow::Result<()> {
+    use helix_view::document::Mode;
+
+    let mut app = AppBuilder::new()
+        .with_config(insert_sequence_config(Duration::from_millis(10)))
+        .with_input_text("#[h|]#ello")
+        .build()?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (
+                Some("ij"),
+                Some(&|app| {
+                    assert_eq!(Mode::Insert, app.editor.mode());
+                    assert_eq!("jhello", doc!(app.editor).text().to_string());
+                }),
+            ),
+            (
+                // The sequence expired so `k` is inserted as well.
+                Some("k"),
+                Some(&|app| {
+                    assert_eq!(Mode::Insert, app.editor.mode());
+                    assert_eq!("jkhello", doc!(app.editor).text().to_string());
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
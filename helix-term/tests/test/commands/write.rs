@@ -972,3 +972,93 @@ async fn test_move_file_when_given_dir_only() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_write_runs_linter() -> anyhow::Result<()> {
+    let file = tempfile::Builder::new().suffix(".rs").tempfile()?;
+
+    let lang_conf = indoc! {r#"
+            [[language]]
+            name = "rust"
+            language-servers = []
+            linters = [
+              { name = "lint", command = "sh", args = ["-c", "cat > /dev/null; echo '2:5: error: bad name'"], format = { type = "regex", pattern = '^(?P<line>\d+):(?P<column>\d+): (?P<severity>\w+): (?P<message>.*)$' } },
+            ]
+        "#};
+
+    let mut app = helpers::AppBuilder::new()
+        .with_file(file.path(), None)
+        .with_input_text("#[f|]#n main() {\n    let x = 0;\n}\n")
+        .with_lang_loader(helpers::test_syntax_loader(Some(lang_conf.into())))
+        .build()?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (Some(":w<ret>"), None),
+            (
+                Some("gg"),
+                Some(&|app| {
+                    let doc = doc!(app.editor);
+                    let diagnostics = doc.diagnostics();
+                    assert_eq!(diagnostics.len(), 1);
+                    assert_eq!(diagnostics[0].message, "bad name");
+                    assert_eq!(diagnostics[0].source.as_deref(), Some("lint"));
+                    assert_eq!(diagnostics[0].severity, Some(Severity::Error));
+                    assert_eq!(diagnostics[0].line, 1);
+                    assert_eq!(diagnostics[0].range.start, 16);
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_write_in_save_hook() -> anyhow::Result<()> {
+    use helix_term::config::{HookConfig, HookEvent};
+
+    let mut file = tempfile::NamedTempFile::new()?;
+    let mut config = helpers::test_config();
+    config.hooks.push(HookConfig {
+        event: HookEvent::DocumentSave,
+        language: None,
+        glob: None,
+        commands: vec![":write".parse()?, ":set-register a %reg{a}x".parse()?],
+    });
+    let mut app = helpers::AppBuilder::new()
+        .with_config(config)
+        .with_file(file.path(), None)
+        .build()?;
+
+    // The write of the hook doesn't run the hook again, but the next save does.
+    test_key_sequences(
+        &mut app,
+        vec![
+            (
+                Some(":set-register a -<ret>ihello<esc>:w<ret>"),
+                Some(&|app| {
+                    let register = app.editor.registers.first('a', &app.editor);
+                    assert_eq!(Some("-x"), register.as_deref());
+                }),
+            ),
+            (
+                Some(":w<ret>"),
+                Some(&|app| {
+                    let register = app.editor.registers.first('a', &app.editor);
+                    assert_eq!(Some("-xx"), register.as_deref());
+                    assert!(!doc!(app.editor).is_modified());
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    helpers::assert_file_has_content(&mut file, &LineFeedHandling::Native.apply("hello\n"))?;
+
+    Ok(())
+}
@@ -0,0 +1,295 @@
+use std::sync::{Arc, Mutex};
+
+use helix_core::syntax::config::DebugAdapterConfig;
+use helix_dap::{registry::DebugAdapterId, Payload};
+use helix_term::application::Application;
+use helix_view::{
+    editor::{Severity, WatchExpression},
+    handlers::dap::data_breakpoints_changed,
+    Editor,
+};
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpListener,
+};
+
+use super::*;
+
+/// The commands of the requests received by a [FakeAdapter].
+type Requests = Arc<Mutex<Vec<String>>>;
+
+/// A debug adapter which answers the requests of the debugger commands with canned
+/// responses. Evaluated expressions are numbered so that re-evaluations can be told apart.
+struct FakeAdapter {
+    capabilities: Value,
+    requests: Requests,
+    evaluations: usize,
+}
+
+impl FakeAdapter {
+    async fn serve(mut self, listener: TcpListener) -> anyhow::Result<()> {
+        let (stream, _) = listener.accept().await?;
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+        loop {
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                if reader.read_line(&mut header).await? == 0 {
+                    return Ok(());
+                }
+                match header.trim().split_once(": ") {
+                    Some(("Content-Length", value)) => content_length = value.parse()?,
+                    _ if header.trim().is_empty() => break,
+                    _ => (),
+                }
+            }
+            let mut content = vec![0; content_length];
+            reader.read_exact(&mut content).await?;
+            let request: Value = serde_json::from_slice(&content)?;
+
+            let command = request["command"].as_str().unwrap_or_default();
+            self.requests.lock().unwrap().push(command.to_string());
+            let response = json!({
+                "type": "response",
+                "seq": 0,
+                "request_seq": request["seq"],
+                "success": true,
+                "command": command,
+                "body": self.respond(command, &request["arguments"]),
+            })
+            .to_string();
+            writer
+                .write_all(
+                    format!("Content-Length: {}\r\n\r\n{response}", response.len()).as_bytes(),
+                )
+                .await?;
+        }
+    }
+
+    fn respond(&mut self, command: &str, arguments: &Value) -> Value {
+        match command {
+            "initialize" => self.capabilities.clone(),
+            "threads" => json!({ "threads": [{ "id": 1, "name": "main" }] }),
+            "stackTrace" => json!({
+                "stackFrames": [{ "id": 1, "name": "main", "line": 1, "column": 1 }],
+            }),
+            "evaluate" => {
+                self.evaluations += 1;
+                let expression = arguments["expression"].as_str().unwrap();
+                json!({
+                    "result": format!("{expression} = {}", self.evaluations),
+                    "variablesReference": 0,
+                })
+            }
+            "dataBreakpointInfo" => json!({
+                "dataId": arguments["name"],
+                "description": arguments["name"],
+            }),
+            "setDataBreakpoints" => {
+                let breakpoints = arguments["breakpoints"].as_array().unwrap();
+                json!({ "breakpoints": vec![json!({ "verified": true }); breakpoints.len()] })
+            }
+            _ => Value::Null,
+        }
+    }
+}
+
+/// Starts a [FakeAdapter] with the `capabilities` as the active debugger of `editor`.
+async fn start_fake_adapter(
+    editor: &mut Editor,
+    capabilities: Value,
+) -> anyhow::Result<(DebugAdapterId, Requests)> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let socket = listener.local_addr()?;
+    let requests = Requests::default();
+    let adapter = FakeAdapter {
+        capabilities,
+        requests: requests.clone(),
+        evaluations: 0,
+    };
+    tokio::spawn(adapter.serve(listener));
+
+    let config = DebugAdapterConfig {
+        name: "fake".to_string(),
+        transport: "tcp".to_string(),
+        command: String::new(),
+        args: Vec::new(),
+        port_arg: None,
+        templates: Vec::new(),
+        quirks: Default::default(),
+    };
+    let id = editor.debug_adapters.start_client(Some(socket), &config)?;
+    editor.debug_adapters.set_active_client(id);
+    Ok((id, requests))
+}
+
+async fn stop_thread(app: &mut Application, id: DebugAdapterId) -> anyhow::Result<()> {
+    let event = serde_json::from_value(json!({
+        "type": "event",
+        "event": "stopped",
+        "body": { "reason": "breakpoint", "threadId": 1 },
+    }))?;
+    app.editor
+        .handle_debugger_message(id, Payload::Event(event))
+        .await;
+    Ok(())
+}
+
+fn watches(editor: &Editor) -> Vec<(&str, Option<&str>)> {
+    editor
+        .watch_expressions
+        .iter()
+        .map(|watch| {
+            let value = watch.value.as_ref().map(|value| match value {
+                Ok(value) | Err(value) => value.as_str(),
+            });
+            (watch.expression.as_str(), value)
+        })
+        .collect()
+}
+
+fn requests_of(requests: &Requests, command: &str) -> usize {
+    requests
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|request| *request == command)
+        .count()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_dap_toggle_watch() -> anyhow::Result<()> {
+    let mut app = AppBuilder::new().build()?;
+    let (_, requests) = start_fake_adapter(&mut app.editor, json!({})).await?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            // Watches aren't evaluated until a thread is stopped.
+            (
+                Some("<space>Gwx<ret><esc>"),
+                Some(&|app| {
+                    assert_eq!(watches(&app.editor), [("x", None)]);
+                }),
+            ),
+            (
+                Some("<space>Gwy<ret><esc>"),
+                Some(&|app| {
+                    assert_eq!(watches(&app.editor), [("x", None), ("y", None)]);
+                }),
+            ),
+            // Toggling an existing watch removes it.
+            (
+                Some("<space>Gwx<ret><esc>"),
+                Some(&|app| {
+                    assert_eq!(watches(&app.editor), [("y", None)]);
+                    assert_eq!(requests_of(&requests, "evaluate"), 0);
+                }),
+            ),
+        ],
+        false,
+    )
+    .await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_dap_watches_are_evaluated_when_stopped() -> anyhow::Result<()> {
+    let mut app = AppBuilder::new().build()?;
+    let (id, _) = start_fake_adapter(&mut app.editor, json!({})).await?;
+    for expression in ["x", "y"] {
+        app.editor.watch_expressions.push(WatchExpression {
+            expression: expression.to_string(),
+            value: None,
+        });
+    }
+
+    stop_thread(&mut app, id).await?;
+    assert_eq!(
+        watches(&app.editor),
+        [("x", Some("x = 1")), ("y", Some("y = 2"))]
+    );
+    stop_thread(&mut app, id).await?;
+    assert_eq!(
+        watches(&app.editor),
+        [("x", Some("x = 3")), ("y", Some("y = 4"))]
+    );
+
+    // A watch added while stopped is evaluated right away.
+    test_key_sequence(
+        &mut app,
+        Some("<space>Gwz<ret><esc>"),
+        Some(&|app| {
+            assert_eq!(
+                watches(&app.editor),
+                [
+                    ("x", Some("x = 3")),
+                    ("y", Some("y = 4")),
+                    ("z", Some("z = 5"))
+                ]
+            );
+        }),
+        false,
+    )
+    .await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_dap_data_breakpoints_unsupported() -> anyhow::Result<()> {
+    let mut app = AppBuilder::new().build()?;
+    let (_, requests) = start_fake_adapter(&mut app.editor, json!({})).await?;
+
+    let debugger = app.editor.debug_adapters.get_active_client_mut().unwrap();
+    assert!(data_breakpoints_changed(debugger, &mut []).is_err());
+
+    test_key_sequence(
+        &mut app,
+        Some("<space>Gdx<ret>"),
+        Some(&|app| {
+            let (status, severity) = app.editor.get_status().unwrap();
+            assert_eq!(status, "Debugger does not support data breakpoints");
+            assert_eq!(*severity, Severity::Error);
+            assert!(app.editor.data_breakpoints.is_empty());
+            assert_eq!(requests_of(&requests, "dataBreakpointInfo"), 0);
+            assert_eq!(requests_of(&requests, "setDataBreakpoints"), 0);
+        }),
+        false,
+    )
+    .await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_dap_toggle_data_breakpoint() -> anyhow::Result<()> {
+    let mut app = AppBuilder::new().build()?;
+    let (_, requests) =
+        start_fake_adapter(&mut app.editor, json!({ "supportsDataBreakpoints": true })).await?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (
+                Some("<space>Gdx<ret><esc>"),
+                Some(&|app| {
+                    let breakpoints = &app.editor.data_breakpoints;
+                    assert_eq!(breakpoints.len(), 1);
+                    assert_eq!(breakpoints[0].expression, "x");
+                    assert_eq!(breakpoints[0].data_id, "x");
+                    assert!(breakpoints[0].verified);
+                    assert_eq!(requests_of(&requests, "dataBreakpointInfo"), 1);
+                    assert_eq!(requests_of(&requests, "setDataBreakpoints"), 1);
+                }),
+            ),
+            (
+                Some("<space>Gdx<ret><esc>"),
+                Some(&|app| {
+                    assert!(app.editor.data_breakpoints.is_empty());
+                    assert_eq!(requests_of(&requests, "dataBreakpointInfo"), 1);
+                    assert_eq!(requests_of(&requests, "setDataBreakpoints"), 2);
+                }),
+            ),
+        ],
+        false,
+    )
+    .await
+}
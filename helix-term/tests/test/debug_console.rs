@@ -0,0 +1,101 @@
+use helix_core::{Selection, Transaction};
+use helix_view::{document::Mode, editor::Action, Editor};
+
+use super::*;
+
+/// Opens the debug console in a split, which focuses it.
+fn open_debug_console(editor: &mut Editor) {
+    let doc_id = editor.debug_console();
+    editor.switch(doc_id, Action::HorizontalSplit);
+    editor.flush_debug_console();
+}
+
+fn debug_console_text(editor: &Editor) -> String {
+    let doc_id = editor.debug_console.unwrap();
+    editor.document(doc_id).unwrap().text().to_string()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_debug_console_buffers_output() -> anyhow::Result<()> {
+    let mut app = AppBuilder::new().build()?;
+    let editor = &mut app.editor;
+
+    // The output is buffered until the console exists and is displayed.
+    editor.append_to_debug_console("one");
+    assert_eq!(editor.debug_console_output, "one\n");
+    let doc_id = editor.debug_console();
+    assert_eq!(debug_console_text(editor), "one\n");
+    assert!(editor.debug_console_output.is_empty());
+
+    editor.append_to_debug_console("two\n");
+    assert_eq!(debug_console_text(editor), "one\n");
+    assert_eq!(editor.debug_console_output, "two\n");
+
+    editor.switch(doc_id, Action::HorizontalSplit);
+    editor.flush_debug_console();
+    assert_eq!(debug_console_text(editor), "one\ntwo\n");
+    assert!(editor.debug_console_output.is_empty());
+
+    // The output of a displayed console isn't buffered.
+    editor.append_to_debug_console("three");
+    assert_eq!(debug_console_text(editor), "one\ntwo\nthree\n");
+    assert!(editor.debug_console_output.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_debug_console_output_limit() -> anyhow::Result<()> {
+    let mut app = AppBuilder::new().build()?;
+    let editor = &mut app.editor;
+
+    let line = "x".repeat(1023);
+    for _ in 0..2048 {
+        editor.append_to_debug_console(&line);
+    }
+    editor.append_to_debug_console("last");
+
+    // The oldest lines are dropped.
+    let output = &editor.debug_console_output;
+    assert!(output.len() <= 1024 * 1024);
+    assert!(output.starts_with(&line));
+    assert!(output.ends_with("\nlast\n"));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_debug_console_history() -> anyhow::Result<()> {
+    let mut app = AppBuilder::new().build()?;
+    let editor = &mut app.editor;
+    open_debug_console(editor);
+
+    // Output is its own revision.
+    editor.append_to_debug_console("one");
+    let (view, doc) = helix_view::current!(editor);
+    assert_eq!(doc.get_current_revision(), 1);
+    assert_eq!(doc.text(), "\none\n");
+    doc.undo(view);
+    assert_eq!(doc.text(), "\n");
+    doc.redo(view);
+
+    // An expression that is being typed isn't committed together with the output.
+    editor.mode = Mode::Insert;
+    let (view, doc) = helix_view::current!(editor);
+    let end = doc.text().len_chars();
+    let transaction = Transaction::insert(doc.text(), &Selection::point(end), "1 + 1".into());
+    doc.apply(&transaction, view.id);
+    editor.append_to_debug_console("two");
+    let (view, doc) = helix_view::current!(editor);
+    assert_eq!(doc.text(), "\none\ntwo\n1 + 1");
+    assert_eq!(doc.get_current_revision(), 1);
+
+    // Leaving insert mode commits both.
+    editor.mode = Mode::Normal;
+    doc.append_changes_to_history(view);
+    assert_eq!(doc.get_current_revision(), 2);
+    doc.undo(view);
+    assert_eq!(doc.text(), "\none\n");
+
+    Ok(())
+}
@@ -8,7 +8,12 @@
 use anyhow::bail;
 use helix_core::{diagnostic::Severity, test, Selection, Transaction};
 use helix_term::{application::Application, args::Args, config::Config, keymap::merge_keys};
-use helix_view::{current_ref, doc, editor::LspConfig, input::parse_macro, Editor};
+use helix_view::{
+    current_ref, doc,
+    editor::{FileWatcherConfig, LspConfig},
+    input::parse_macro,
+    Editor,
+};
 use tempfile::NamedTempFile;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
@@ -294,6 +299,10 @@ pub fn test_editor_config() -> helix_view::editor::Config {
             enable: false,
             ..Default::default()
         },
+        file_watcher: FileWatcherConfig {
+            enable: false,
+            ..Default::default()
+        },
         ..Default::default()
     }
 }
@@ -842,3 +842,42 @@ async fn tree_sitter_motions_work_across_injections() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn movements_skip_folds() -> anyhow::Result<()> {
+    // vertical movements skip the folded lines
+    test(("#[a|]#\nb\nc\nd\n", "xxzfggj;", "a\nb\n#[c|]#\nd\n")).await?;
+    // horizontal movements skip over the folded text
+    test(("#[a|]#\nb\nc\nd\n", "xxzfgglll", "a\nb\n#[c|]#\nd\n")).await?;
+    test(("#[a|]#\nb\nc\nd\n", "xxzfgglllhh", "a#[\n|]#b\nc\nd\n")).await?;
+    // unfolding shows the lines again
+    test(("#[a|]#\nb\nc\nd\n", "xxzfzoggj;", "a\n#[b|]#\nc\nd\n")).await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn goto_conflict() -> anyhow::Result<()> {
+    let conflict = "<<<<<<< HEAD\nb\n=======\nc\n>>>>>>> branch\n";
+    test((
+        format!("#[a|]#\n{conflict}d\n"),
+        "]n",
+        format!("a\n#[{conflict}|]#d\n"),
+    ))
+    .await?;
+    test((
+        format!("a\n{conflict}#[d|]#\n"),
+        "[n",
+        format!("a\n#[|{conflict}]#d\n"),
+    ))
+    .await?;
+    // the cursor stays in place without a conflict in the direction
+    test((
+        format!("a\n{conflict}#[d|]#\n"),
+        "]n",
+        format!("a\n{conflict}#[d|]#\n"),
+    ))
+    .await?;
+
+    Ok(())
+}
@@ -261,3 +261,118 @@ async fn test_reload_all_with_split_jumplist() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_diff_put() -> anyhow::Result<()> {
+    use std::io::Write;
+    let mut file1 = tempfile::NamedTempFile::new()?;
+    let mut file2 = tempfile::NamedTempFile::new()?;
+    file1.write_all(b"a\nb\nc\n")?;
+    file2.write_all(b"a\nB\nc\nd\n")?;
+    file1.flush()?;
+    file2.flush()?;
+
+    let mut app = helpers::AppBuilder::new()
+        .with_file(file1.path(), None)
+        .build()?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (
+                Some(&format!(":diff {}<ret>", file2.path().to_string_lossy())),
+                Some(&|app| {
+                    helpers::assert_status_not_error(&app.editor);
+                    assert_eq!(2, app.editor.tree.views().count());
+                    let doc = helix_view::doc!(app.editor);
+                    assert_eq!(
+                        doc.path().unwrap(),
+                        &path::normalize(file1.path()),
+                        "focus stays on the compared buffer"
+                    );
+                    assert!(doc.diff_mode().is_some());
+                }),
+            ),
+            (
+                Some("j:diff-put<ret>"),
+                Some(&|app| {
+                    helpers::assert_status_not_error(&app.editor);
+                    let doc2 = app
+                        .editor
+                        .document_by_path(path::normalize(file2.path()))
+                        .unwrap();
+                    assert_eq!("a\nb\nc\nd\n", doc2.text().to_string());
+                }),
+            ),
+            (
+                Some(":diff-off<ret>"),
+                Some(&|app| {
+                    assert!(app.editor.documents().all(|doc| doc.diff_mode().is_none()));
+                }),
+            ),
+            (Some(":qa!<ret>"), None),
+        ],
+        true,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_session_save_load() -> anyhow::Result<()> {
+    use helix_core::Selection;
+    use std::io::Write;
+    let mut file1 = tempfile::NamedTempFile::new()?;
+    let mut file2 = tempfile::NamedTempFile::new()?;
+    let session = tempfile::NamedTempFile::new()?;
+    file1.write_all(b"one\n")?;
+    file2.write_all(b"a\nb\nc\n")?;
+    file1.flush()?;
+    file2.flush()?;
+
+    let mut app = helpers::AppBuilder::new()
+        .with_file(file1.path(), None)
+        .build()?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (
+                Some(&format!(
+                    ":vsplit {}<ret>jj:session-save {}<ret>",
+                    file2.path().to_string_lossy(),
+                    session.path().to_string_lossy()
+                )),
+                Some(&|app| helpers::assert_status_not_error(&app.editor)),
+            ),
+            (
+                Some(&format!(
+                    "<C-w>o:o {}<ret>:session-load {}<ret>",
+                    file1.path().to_string_lossy(),
+                    session.path().to_string_lossy()
+                )),
+                Some(&|app| {
+                    helpers::assert_status_not_error(&app.editor);
+                    assert_eq!(2, app.editor.tree.views().count());
+                    let (view, doc) = helix_view::current_ref!(app.editor);
+                    assert_eq!(doc.path().unwrap(), &path::normalize(file2.path()));
+                    assert_eq!(doc.selection(view.id), &Selection::single(4, 5));
+                    let other = app
+                        .editor
+                        .tree
+                        .views()
+                        .find(|(_, focused)| !focused)
+                        .map(|(view, _)| app.editor.document(view.doc).unwrap())
+                        .unwrap();
+                    assert_eq!(other.path().unwrap(), &path::normalize(file1.path()));
+                }),
+            ),
+            (Some(":qa!<ret>"), None),
+        ],
+        true,
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+/// The commit that last changed a line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlameCommit {
+    /// The full hex id of the commit.
+    pub id: String,
+    pub author: String,
+    pub email: String,
+    /// The author date formatted as `YYYY-MM-DD`.
+    pub date: String,
+    /// The author time in seconds since the UNIX epoch.
+    pub time: i64,
+    /// The first line of the commit message.
+    pub summary: String,
+}
+
+impl BlameCommit {
+    /// The abbreviated commit id.
+    pub fn short_id(&self) -> &str {
+        &self.id[..self.id.len().min(8)]
+    }
+}
+
+/// The commits that last changed each line of a committed file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileBlame {
+    /// Consecutive line ranges of the committed file and the commit that introduced them.
+    hunks: Vec<(Range<u32>, Arc<BlameCommit>)>,
+}
+
+impl FileBlame {
+    /// Creates the blame of a file from line ranges sorted by their start.
+    pub fn new(hunks: Vec<(Range<u32>, Arc<BlameCommit>)>) -> FileBlame {
+        debug_assert!(hunks.windows(2).all(|w| w[0].0.end <= w[1].0.start));
+        FileBlame { hunks }
+    }
+
+    /// Returns the commit that last changed `line` of the committed file.
+    pub fn line(&self, line: u32) -> Option<&BlameCommit> {
+        let idx = self.hunks.partition_point(|(range, _)| range.end <= line);
+        let (range, commit) = self.hunks.get(idx)?;
+        range.contains(&line).then_some(&**commit)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn commit(id: &str) -> Arc<BlameCommit> {
+        Arc::new(BlameCommit {
+            id: id.to_owned(),
+            author: "author".to_owned(),
+            email: "author@example.com".to_owned(),
+            date: "2000-01-01".to_owned(),
+            time: 946684800,
+            summary: "message".to_owned(),
+        })
+    }
+
+    #[test]
+    fn line() {
+        let blame = FileBlame::new(vec![(0..2, commit("aaaa")), (3..4, commit("bbbb"))]);
+        assert_eq!(blame.line(0).unwrap().id, "aaaa");
+        assert_eq!(blame.line(1).unwrap().id, "aaaa");
+        assert_eq!(blame.line(2), None);
+        assert_eq!(blame.line(3).unwrap().id, "bbbb");
+        assert_eq!(blame.line(4), None);
+    }
+}
@@ -33,6 +33,8 @@ struct DiffInner {
     diff_base: Rope,
     doc: Rope,
     hunks: Vec<Hunk>,
+    /// Incremented every time the hunks are replaced.
+    revision: u64,
 }
 
 /// Representation of a diff that can be updated.
@@ -154,6 +156,12 @@ pub fn is_inverted(&self) -> bool {
         self.inverted
     }
 
+    /// Returns a number that changes every time the diff is recomputed, which allows
+    /// caching values derived from the hunks.
+    pub fn revision(&self) -> u64 {
+        self.diff.revision
+    }
+
     /// Returns the `Hunk` for the `n`th change in this file.
     /// if there is no `n`th change  `Hunk::NONE` is returned instead.
     pub fn nth_hunk(&self, n: u32) -> Hunk {
@@ -231,7 +239,7 @@ pub fn prev_hunk(&self, line: u32) -> Option<u32> {
     ///
     /// Hunks are returned at most once even when intersecting with multiple of the line
     /// ranges.
-    pub fn hunks_intersecting_line_ranges<I>(&self, line_ranges: I) -> impl Iterator<Item = &Hunk>
+    pub fn hunks_intersecting_line_ranges<I>(&self, line_ranges: I) -> HunksInLineRangesIter<'_, I>
     where
         I: Iterator<Item = (usize, usize)>,
     {
@@ -243,6 +251,70 @@ pub fn hunks_intersecting_line_ranges<I>(&self, line_ranges: I) -> impl Iterator
         }
     }
 
+    /// Returns the line of the diff base that corresponds to the given line of the
+    /// document, or `None` if the line was added or modified.
+    pub fn base_line(&self, line: u32) -> Option<u32> {
+        let hunk_ranges = |hunk: &Hunk| {
+            if self.inverted {
+                (hunk.after.clone(), hunk.before.clone())
+            } else {
+                (hunk.before.clone(), hunk.after.clone())
+            }
+        };
+
+        let pos = self
+            .diff
+            .hunks
+            .partition_point(|hunk| hunk_ranges(hunk).1.end <= line);
+        if self
+            .diff
+            .hunks
+            .get(pos)
+            .is_some_and(|hunk| hunk_ranges(hunk).1.contains(&line))
+        {
+            return None;
+        }
+        // lines after a hunk are shifted by the difference of its lengths
+        match pos.checked_sub(1) {
+            Some(prev) => {
+                let (before, after) = hunk_ranges(&self.diff.hunks[prev]);
+                Some(line - after.end + before.end)
+            }
+            None => Some(line),
+        }
+    }
+
+    /// Returns the line of the diff base that is displayed next to the given line of the
+    /// document when both are shown side by side. Unlike [Diff::base_line] this maps lines
+    /// inside a hunk to the line at the same offset into the hunk in the diff base, or the
+    /// line after the hunk if the diff base has fewer lines.
+    pub fn aligned_base_line(&self, line: u32) -> u32 {
+        let hunk_ranges = |hunk: &Hunk| {
+            if self.inverted {
+                (hunk.after.clone(), hunk.before.clone())
+            } else {
+                (hunk.before.clone(), hunk.after.clone())
+            }
+        };
+
+        let pos = self
+            .diff
+            .hunks
+            .partition_point(|hunk| hunk_ranges(hunk).1.end <= line);
+        if let Some((before, after)) = self.diff.hunks.get(pos).map(hunk_ranges) {
+            if after.contains(&line) {
+                return (before.start + line - after.start).min(before.end);
+            }
+        }
+        match pos.checked_sub(1) {
+            Some(prev) => {
+                let (before, after) = hunk_ranges(&self.diff.hunks[prev]);
+                line - after.end + before.end
+            }
+            None => line,
+        }
+    }
+
     /// Returns the index of the hunk containing the given line if it exists.
     pub fn hunk_at(&self, line: u32, include_removal: bool) -> Option<u32> {
         let hunk_range = if self.inverted {
@@ -284,7 +356,7 @@ pub struct HunksInLineRangesIter<'a, I: Iterator<Item = (usize, usize)>> {
 }
 
 impl<'a, I: Iterator<Item = (usize, usize)>> Iterator for HunksInLineRangesIter<'a, I> {
-    type Item = &'a Hunk;
+    type Item = Hunk;
 
     fn next(&mut self) -> Option<Self::Item> {
         let hunk_range = if self.inverted {
@@ -306,7 +378,11 @@ fn next(&mut self) -> Option<Self::Item> {
                 // If the hunk under the cursor overlaps with this line range, emit it
                 // and move the cursor up so that the hunk cannot be emitted twice.
                 self.cursor += 1;
-                return Some(hunk);
+                return Some(if self.inverted {
+                    hunk.invert()
+                } else {
+                    hunk.clone()
+                });
             } else {
                 // Otherwise, go to the next line range.
                 self.line_ranges.next();
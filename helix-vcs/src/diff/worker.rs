@@ -75,6 +75,7 @@ fn apply_hunks(&mut self, diff_base: Rope, doc: Rope) {
         diff.doc = doc;
         diff.hunks.clear();
         diff.hunks.extend(self.diff_alloc.hunks());
+        diff.revision += 1;
         drop(diff);
         self.diff_finished_notify.notify_waiters();
     }
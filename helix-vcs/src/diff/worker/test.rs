@@ -1,7 +1,7 @@
 use helix_core::Rope;
 use tokio::task::JoinHandle;
 
-use crate::diff::{DiffHandle, Hunk};
+use crate::diff::{Diff, DiffHandle, Hunk};
 
 impl DiffHandle {
     fn new_test(diff_base: &str, doc: &str) -> (DiffHandle, JoinHandle<()>) {
@@ -143,3 +143,44 @@ async fn update_base() {
         ]
     )
 }
+
+#[tokio::test]
+async fn base_line() {
+    let (differ, handle) = DiffHandle::new_test("a\nb\nc\nd\n", "x\na\nc\nd2\n");
+    let DiffHandle { channel, diff, .. } = differ;
+    drop(channel);
+    handle.await.unwrap();
+    let diff = Diff {
+        diff: diff.read(),
+        inverted: false,
+    };
+    assert_eq!(diff.base_line(0), None);
+    assert_eq!(diff.base_line(1), Some(0));
+    assert_eq!(diff.base_line(2), Some(2));
+    assert_eq!(diff.base_line(3), None);
+}
+
+#[tokio::test]
+async fn aligned_base_line() {
+    let (differ, handle) = DiffHandle::new_test("a\nb\nc\nd\n", "x\na\nc\nd2\nd3\ne\n");
+    let DiffHandle { channel, diff, .. } = differ;
+    drop(channel);
+    handle.await.unwrap();
+    let diff = Diff {
+        diff: diff.read(),
+        inverted: false,
+    };
+    assert_eq!(diff.aligned_base_line(0), 0);
+    assert_eq!(diff.aligned_base_line(1), 0);
+    assert_eq!(diff.aligned_base_line(2), 2);
+    assert_eq!(diff.aligned_base_line(3), 3);
+    assert_eq!(diff.aligned_base_line(4), 4);
+    assert_eq!(diff.aligned_base_line(5), 4);
+    let diff = Diff {
+        inverted: true,
+        ..diff
+    };
+    assert_eq!(diff.aligned_base_line(1), 2);
+    assert_eq!(diff.aligned_base_line(2), 2);
+    assert_eq!(diff.aligned_base_line(3), 3);
+}
@@ -1,13 +1,16 @@
 use anyhow::{bail, Context, Result};
 use arc_swap::ArcSwap;
 use gix::filter::plumbing::driver::apply::Delay;
+use std::collections::HashMap;
 use std::io::Read;
+use std::ops::Range;
 use std::path::Path;
 use std::sync::Arc;
 
-use gix::bstr::ByteSlice;
+use gix::bstr::{BStr, ByteSlice};
 use gix::diff::Rewrites;
 use gix::dir::entry::Status;
+use gix::index::entry::{Mode, Stage};
 use gix::objs::tree::EntryKind;
 use gix::sec::trust::DefaultForLevel;
 use gix::status::{
@@ -16,8 +19,9 @@
     UntrackedFiles,
 };
 use gix::{Commit, ObjectId, Repository, ThreadSafeRepository};
+use imara_diff::{Algorithm, InternedInput};
 
-use crate::FileChange;
+use crate::{BlameCommit, FileBlame, FileChange};
 
 #[cfg(test)]
 mod test;
@@ -44,19 +48,7 @@ pub fn get_diff_base(file: &Path) -> Result<Vec<u8>> {
     let file_object = repo.find_object(file_oid)?;
     let data = file_object.detach().data;
     // Get the actual data that git would make out of the git object.
-    // This will apply the user's git config or attributes like crlf conversions.
-    if let Some(work_dir) = repo.workdir() {
-        let rela_path = file.strip_prefix(work_dir)?;
-        let rela_path = gix::path::try_into_bstr(rela_path)?;
-        let (mut pipeline, _) = repo.filter_pipeline(None)?;
-        let mut worktree_outcome =
-            pipeline.convert_to_worktree(&data, rela_path.as_ref(), Delay::Forbid)?;
-        let mut buf = Vec::with_capacity(data.len());
-        worktree_outcome.read_to_end(&mut buf)?;
-        Ok(buf)
-    } else {
-        Ok(data)
-    }
+    to_worktree(&repo, &file, &data)
 }
 
 pub fn get_current_head_name(file: &Path) -> Result<Arc<ArcSwap<Box<str>>>> {
@@ -79,6 +71,203 @@ pub fn get_current_head_name(file: &Path) -> Result<Arc<ArcSwap<Box<str>>>> {
     Ok(Arc::new(ArcSwap::from_pointee(name.into_boxed_str())))
 }
 
+pub fn blame(file: &Path) -> Result<FileBlame> {
+    debug_assert!(!file.exists() || file.is_file());
+    debug_assert!(file.is_absolute());
+    let file = gix::path::realpath(file).context("resolve symlinks")?;
+
+    let repo_dir = get_repo_dir(&file)?;
+    let repo = open_repo(repo_dir)
+        .context("failed to open git repo")?
+        .to_thread_local();
+    let work_dir = repo.workdir().context("repo has no worktree")?;
+    let rela_path = gix::path::to_unix_separators_on_windows(gix::path::try_into_bstr(
+        file.strip_prefix(work_dir)?,
+    )?);
+    let head = repo.head_commit()?;
+    let outcome = repo.blame_file(rela_path.as_ref(), head.id, Default::default())?;
+
+    let mut commits: HashMap<ObjectId, Arc<BlameCommit>> = HashMap::new();
+    let mut hunks = Vec::with_capacity(outcome.entries.len());
+    for entry in outcome.entries {
+        let commit = match commits.get(&entry.commit_id) {
+            Some(commit) => commit.clone(),
+            None => {
+                let commit = Arc::new(blame_commit(&repo.find_commit(entry.commit_id)?)?);
+                commits.insert(entry.commit_id, commit.clone());
+                commit
+            }
+        };
+        let start = entry.start_in_blamed_file;
+        hunks.push((start..start + entry.len.get(), commit));
+    }
+
+    Ok(FileBlame::new(hunks))
+}
+
+fn blame_commit(commit: &Commit) -> Result<BlameCommit> {
+    let author = commit.author()?;
+    let time = author.time()?;
+    Ok(BlameCommit {
+        id: commit.id.to_string(),
+        author: author.name.to_string(),
+        email: author.email.to_string(),
+        date: time.format_or_unix(gix::date::time::format::SHORT),
+        time: time.seconds,
+        summary: commit.message()?.summary().to_string(),
+    })
+}
+
+/// Returns the contents of the index entry of `file`, which differ from the diff base
+/// while changes are staged.
+pub fn get_index_base(file: &Path) -> Result<Vec<u8>> {
+    debug_assert!(file.is_absolute());
+    let file = gix::path::realpath(file).context("resolve symlinks")?;
+
+    let repo_dir = get_repo_dir(&file)?;
+    let repo = open_repo(repo_dir)
+        .context("failed to open git repo")?
+        .to_thread_local();
+    let work_dir = repo.workdir().context("repo has no worktree")?;
+    let rela_path = gix::path::to_unix_separators_on_windows(gix::path::try_into_bstr(
+        file.strip_prefix(work_dir)?,
+    )?);
+    let index = repo.open_index()?;
+    index_entry_contents(&repo, &index, &file, rela_path.as_ref())
+}
+
+/// Stages the changes between the index and `doc` that intersect the given lines of `doc`.
+/// Returns the number of staged hunks.
+pub fn stage_lines(file: &Path, doc: &[u8], lines: &[Range<u32>]) -> Result<usize> {
+    update_index_entry(file, |_, _, index| Ok(apply_hunks(index, doc, lines)))
+}
+
+/// Unstages the changes between `HEAD` and the index that intersect the given lines of
+/// the file at `HEAD`. Returns the number of unstaged hunks.
+pub fn unstage_lines(file: &Path, lines: &[Range<u32>]) -> Result<usize> {
+    update_index_entry(file, |repo, file, index| {
+        let head = repo.head_commit()?;
+        let head_oid = find_file_in_commit(repo, &head, file)?;
+        let head = to_worktree(repo, file, &repo.find_object(head_oid)?.detach().data)?;
+        Ok(apply_hunks(index, &head, lines))
+    })
+}
+
+/// Returns the contents of the unconflicted index entry at `rela_path` in worktree form.
+fn index_entry_contents(
+    repo: &Repository,
+    index: &gix::index::File,
+    file: &Path,
+    rela_path: &BStr,
+) -> Result<Vec<u8>> {
+    let entry = index
+        .entry_by_path_and_stage(rela_path, Stage::Unconflicted)
+        .context("file is not in the index")?;
+    if !matches!(entry.mode, Mode::FILE | Mode::FILE_EXECUTABLE) {
+        bail!("index entry at {} is not a file", file.display())
+    }
+    to_worktree(repo, file, &repo.find_object(entry.id)?.detach().data)
+}
+
+/// Replaces the index entry of `file` with the contents returned by `update`. `update`
+/// receives the current contents of the entry in worktree form and returns the new contents
+/// together with the number of changed hunks. The index is left untouched if nothing changed.
+fn update_index_entry(
+    file: &Path,
+    update: impl FnOnce(&Repository, &Path, &[u8]) -> Result<(Vec<u8>, usize)>,
+) -> Result<usize> {
+    debug_assert!(file.is_absolute());
+    let file = gix::path::realpath(file).context("resolve symlinks")?;
+
+    let repo_dir = get_repo_dir(&file)?;
+    let repo = open_repo(repo_dir)
+        .context("failed to open git repo")?
+        .to_thread_local();
+    let work_dir = repo.workdir().context("repo has no worktree")?;
+    let rela_path = file.strip_prefix(work_dir)?;
+    let rela_bstr = gix::path::to_unix_separators_on_windows(gix::path::try_into_bstr(rela_path)?);
+
+    let mut index = repo.open_index()?;
+    let contents = index_entry_contents(&repo, &index, &file, rela_bstr.as_ref())?;
+
+    let (contents, hunks) = update(&repo, &file, &contents)?;
+    if hunks == 0 {
+        return Ok(0);
+    }
+
+    // Convert back to what git stores, undoing crlf conversions and the like.
+    let (mut pipeline, _) = repo.filter_pipeline(None)?;
+    let mut data = Vec::with_capacity(contents.len());
+    pipeline
+        .convert_to_git(contents.as_slice(), rela_path, &index)?
+        .read_to_end(&mut data)?;
+    drop(pipeline);
+    let id = repo.write_blob(data)?.detach();
+
+    let entry = index
+        .entry_mut_by_path_and_stage(rela_bstr.as_ref(), Stage::Unconflicted)
+        .context("file is not in the index")?;
+    entry.id = id;
+    // The entry no longer matches the file on disk. Clearing the stat forces git to
+    // compare the contents instead of trusting the timestamps.
+    entry.stat = Default::default();
+    // The cached trees would still contain the old blob.
+    index.remove_tree();
+    index.write(Default::default())?;
+    Ok(hunks)
+}
+
+/// Converts the contents of a blob to what would be checked out into the worktree. This
+/// applies the user's git config or attributes like crlf conversions.
+fn to_worktree(repo: &Repository, file: &Path, data: &[u8]) -> Result<Vec<u8>> {
+    let Some(work_dir) = repo.workdir() else {
+        return Ok(data.to_vec());
+    };
+    let rela_path = gix::path::try_into_bstr(file.strip_prefix(work_dir)?)?;
+    let rela_path: &BStr = rela_path.as_ref();
+    let (mut pipeline, _) = repo.filter_pipeline(None)?;
+    let mut worktree_outcome = pipeline.convert_to_worktree(data, rela_path, Delay::Forbid)?;
+    let mut buf = Vec::with_capacity(data.len());
+    worktree_outcome.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// Applies the hunks of the line diff between `old` and `new` that intersect the given lines
+/// of `new` to `old`. Returns the result and the number of applied hunks.
+fn apply_hunks(old: &[u8], new: &[u8], lines: &[Range<u32>]) -> (Vec<u8>, usize) {
+    // Empty ranges are insertion points and intersect anything they touch.
+    let intersects = |hunk: &Range<u32>, range: &Range<u32>| {
+        if hunk.is_empty() || range.is_empty() {
+            hunk.start <= range.end && range.start <= hunk.end
+        } else {
+            hunk.start < range.end && range.start < hunk.end
+        }
+    };
+
+    let input = InternedInput::new(old, new);
+    let diff = imara_diff::Diff::compute(Algorithm::Histogram, &input);
+    let mut res = Vec::with_capacity(new.len());
+    let mut applied = 0;
+    let mut pos = 0;
+    for hunk in diff.hunks() {
+        let tokens = if lines.iter().any(|range| intersects(&hunk.after, range)) {
+            applied += 1;
+            &input.after[hunk.after.start as usize..hunk.after.end as usize]
+        } else {
+            &input.before[hunk.before.start as usize..hunk.before.end as usize]
+        };
+        let unchanged = &input.before[pos..hunk.before.start as usize];
+        for &token in unchanged.iter().chain(tokens) {
+            res.extend_from_slice(input.interner[token]);
+        }
+        pos = hunk.before.end as usize;
+    }
+    for &token in &input.before[pos..] {
+        res.extend_from_slice(input.interner[token]);
+    }
+    (res, applied)
+}
+
 pub fn for_each_changed_file(cwd: &Path, f: impl Fn(Result<FileChange>) -> bool) -> Result<()> {
     status(&open_repo(cwd)?.to_thread_local(), f)
 }
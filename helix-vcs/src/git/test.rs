@@ -150,3 +150,52 @@ fn symlink_to_git_repo() {
     assert_eq!(git::get_diff_base(&file_link).unwrap(), contents);
     assert_eq!(git::get_diff_base(&file).unwrap(), contents);
 }
+
+#[test]
+fn blame() {
+    let temp_git = empty_git_repo();
+    let file = temp_git.path().join("file.txt");
+    File::create(&file)
+        .unwrap()
+        .write_all(b"foo\nbar\n")
+        .unwrap();
+    create_commit(temp_git.path(), true);
+    File::create(&file)
+        .unwrap()
+        .write_all(b"foo\nbaz\nbar\n")
+        .unwrap();
+    exec_git_cmd("commit -a -m second", temp_git.path());
+
+    let blame = git::blame(&file).unwrap();
+    let first = blame.line(0).unwrap();
+    assert_eq!(first.summary, "message");
+    assert_eq!(first.author, "author");
+    assert_eq!(first.date, "2000-01-01");
+    assert_eq!(blame.line(1).unwrap().summary, "second");
+    assert_eq!(blame.line(2).unwrap().id, first.id);
+    assert!(blame.line(3).is_none());
+}
+
+#[test]
+fn stage_and_unstage_lines() {
+    let temp_git = empty_git_repo();
+    let file = temp_git.path().join("file.txt");
+    File::create(&file)
+        .unwrap()
+        .write_all(b"a\nb\nc\nd\ne\n")
+        .unwrap();
+    create_commit(temp_git.path(), true);
+    let doc = b"a\nB\nc\nd\nE\n";
+    File::create(&file).unwrap().write_all(doc).unwrap();
+
+    assert_eq!(git::stage_lines(&file, doc, &[1..2, 3..4]).unwrap(), 1);
+    assert_eq!(git::get_index_base(&file).unwrap(), b"a\nB\nc\nd\ne\n");
+    exec_git_cmd("commit -m staged", temp_git.path());
+    assert_eq!(git::get_diff_base(&file).unwrap(), b"a\nB\nc\nd\ne\n");
+
+    assert_eq!(git::stage_lines(&file, doc, &[0..2, 4..5]).unwrap(), 1);
+    assert_eq!(git::stage_lines(&file, doc, &[0..2, 4..5]).unwrap(), 0);
+    assert_eq!(git::unstage_lines(&file, &[0..1, 4..5]).unwrap(), 1);
+    exec_git_cmd("commit --allow-empty -m unstaged", temp_git.path());
+    assert_eq!(git::get_diff_base(&file).unwrap(), b"a\nB\nc\nd\ne\n");
+}
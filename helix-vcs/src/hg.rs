@@ -0,0 +1,197 @@
+//! Diff provider for [Mercurial](https://www.mercurial-scm.org/) repositories. This shells out
+//! to the `hg` binary.
+
+use anyhow::{bail, Context, Result};
+use arc_swap::ArcSwap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+
+use crate::FileChange;
+
+/// Finds the root of the Mercurial repository containing `path`.
+fn find_repo_root(path: &Path) -> Result<&Path> {
+    path.ancestors()
+        .find(|dir| dir.join(".hg").is_dir())
+        .context("not inside a hg repository")
+}
+
+/// Runs `hg` in the repository at `root` and returns its stdout.
+fn hg(root: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    let output = Command::new("hg")
+        .current_dir(root)
+        // Disables user configuration that changes the output, like aliases or localization.
+        .env("HGPLAIN", "1")
+        .arg("--repository")
+        .arg(root)
+        .args(args)
+        .output()
+        .context("failed to run hg")?;
+    if !output.status.success() {
+        bail!(
+            "hg {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// Returns a pattern matching exactly `path` relative to the repository root.
+fn path_pattern(path: &Path) -> Result<String> {
+    let path = path
+        .components()
+        .map(|component| component.as_os_str().to_str())
+        .collect::<Option<Vec<_>>>()
+        .context("path is not valid UTF-8")?
+        .join("/");
+    Ok(format!("path:{path}"))
+}
+
+pub fn get_diff_base(file: &Path) -> Result<Vec<u8>> {
+    debug_assert!(!file.exists() || file.is_file());
+    debug_assert!(file.is_absolute());
+    let file = file.canonicalize().context("resolve symlinks")?;
+    let root = find_repo_root(file.parent().context("file has no parent directory")?)?;
+    let pattern = path_pattern(file.strip_prefix(root)?)?;
+
+    hg(root, &["cat", "--rev", ".", "--", &pattern])
+}
+
+pub fn get_current_head_name(file: &Path) -> Result<Arc<ArcSwap<Box<str>>>> {
+    debug_assert!(!file.exists() || file.is_file());
+    debug_assert!(file.is_absolute());
+    let file = file.canonicalize().context("resolve symlinks")?;
+    let root = find_repo_root(file.parent().context("file has no parent directory")?)?;
+
+    let name = hg(
+        root,
+        &[
+            "log",
+            "--rev",
+            ".",
+            "--template",
+            "{if(activebookmark, activebookmark, branch)}",
+        ],
+    )?;
+    let name = String::from_utf8(name)?.trim().to_owned();
+
+    Ok(Arc::new(ArcSwap::from_pointee(name.into_boxed_str())))
+}
+
+pub fn for_each_changed_file(cwd: &Path, f: impl Fn(Result<FileChange>) -> bool) -> Result<()> {
+    let cwd = cwd.canonicalize()?;
+    let root = find_repo_root(&cwd)?;
+    let status = String::from_utf8(hg(root, &["status", "--copies"])?)?;
+    // Only lists anything during a merge.
+    let unresolved = String::from_utf8(hg(root, &["resolve", "--list"])?)?;
+
+    for change in parse_status(root, &status, &unresolved) {
+        if !f(Ok(change)) {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses the output of `hg status --copies` and `hg resolve --list` with paths relative to
+/// `root`.
+fn parse_status(root: &Path, status: &str, unresolved: &str) -> Vec<FileChange> {
+    let unresolved: HashSet<_> = unresolved
+        .lines()
+        .filter_map(|line| line.strip_prefix("U "))
+        .collect();
+    let removed: HashSet<_> = status
+        .lines()
+        .filter_map(|line| line.strip_prefix("R "))
+        .collect();
+    // Renames are shown as an added file followed by its indented source and, as added
+    // files are listed first, later the removed source.
+    let mut renamed_from = HashSet::new();
+
+    let mut changes = Vec::new();
+    let mut lines = status.lines().peekable();
+    while let Some(line) = lines.next() {
+        let Some((status, path)) = line.split_once(' ') else {
+            continue;
+        };
+        let change = match status {
+            _ if unresolved.contains(path) => FileChange::Conflict {
+                path: root.join(path),
+            },
+            "M" => FileChange::Modified {
+                path: root.join(path),
+            },
+            "A" => {
+                let source = lines
+                    .next_if(|line| line.starts_with("  "))
+                    .map(|line| line.trim_start());
+                match source {
+                    Some(source) if removed.contains(source) => {
+                        renamed_from.insert(source);
+                        FileChange::Renamed {
+                            from_path: root.join(source),
+                            to_path: root.join(path),
+                        }
+                    }
+                    _ => FileChange::Untracked {
+                        path: root.join(path),
+                    },
+                }
+            }
+            "?" => FileChange::Untracked {
+                path: root.join(path),
+            },
+            "R" if renamed_from.contains(path) => continue,
+            "R" | "!" => FileChange::Deleted {
+                path: root.join(path),
+            },
+            _ => continue,
+        };
+        changes.push(change);
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn status() {
+        let root = Path::new("/repo");
+        let status = "M modified.rs\nA added.rs\nA moved.rs\n  original.rs\nA copy.rs\n  modified.rs\nR original.rs\n! missing.rs\n? unknown.rs\nM conflict.rs\n";
+        let changes = parse_status(root, status, "U conflict.rs\nR resolved.rs\n");
+        let changes: Vec<_> = changes
+            .iter()
+            .map(|change| {
+                let kind = match change {
+                    FileChange::Untracked { .. } => "untracked",
+                    FileChange::Modified { .. } => "modified",
+                    FileChange::Conflict { .. } => "conflict",
+                    FileChange::Deleted { .. } => "deleted",
+                    FileChange::Renamed { .. } => "renamed",
+                };
+                (
+                    kind,
+                    change.path().strip_prefix(root).unwrap().to_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("modified", "modified.rs"),
+                ("untracked", "added.rs"),
+                ("renamed", "moved.rs"),
+                ("untracked", "copy.rs"),
+                ("deleted", "missing.rs"),
+                ("untracked", "unknown.rs"),
+                ("conflict", "conflict.rs"),
+            ]
+        );
+    }
+}
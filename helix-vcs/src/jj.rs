@@ -0,0 +1,258 @@
+//! Diff provider for [Jujutsu](https://jj-vcs.github.io/jj/) workspaces, both colocated with
+//! git and not. Jujutsu has no stable library interface so this shells out to the `jj` binary.
+
+use anyhow::{bail, Context, Result};
+use arc_swap::ArcSwap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+use crate::FileChange;
+
+/// Finds the root of the Jujutsu workspace containing `path`.
+fn find_workspace_root(path: &Path) -> Result<&Path> {
+    path.ancestors()
+        .find(|dir| dir.join(".jj").is_dir())
+        .context("not inside a jj workspace")
+}
+
+/// Runs `jj` in the workspace at `root` and returns its stdout. All invocations are read-only
+/// so the working copy is never snapshotted, which would create a new operation every time.
+fn jj(root: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    let output = Command::new("jj")
+        .current_dir(root)
+        .arg("--repository")
+        .arg(root)
+        .args(["--no-pager", "--color", "never", "--ignore-working-copy"])
+        .args(args)
+        .output()
+        .context("failed to run jj")?;
+    if !output.status.success() {
+        bail!(
+            "jj {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// Quotes `path` as a fileset matching exactly that file relative to the workspace root.
+fn root_file_pattern(path: &Path) -> Result<String> {
+    let path = path
+        .components()
+        .map(|component| component.as_os_str().to_str())
+        .collect::<Option<Vec<_>>>()
+        .context("path is not valid UTF-8")?
+        .join("/");
+    let path = path.replace('\\', "\\\\").replace('"', "\\\"");
+    Ok(format!("root-file:\"{path}\""))
+}
+
+pub fn get_diff_base(file: &Path) -> Result<Vec<u8>> {
+    debug_assert!(!file.exists() || file.is_file());
+    debug_assert!(file.is_absolute());
+    let file = file.canonicalize().context("resolve symlinks")?;
+    let root = find_workspace_root(file.parent().context("file has no parent directory")?)?;
+    let pattern = root_file_pattern(file.strip_prefix(root)?)?;
+
+    // The parents of the working copy commit are what `jj diff` compares against.
+    let parents = jj(
+        root,
+        &[
+            "log",
+            "--no-graph",
+            "--revisions",
+            "@-",
+            "--template",
+            r#"commit_id ++ "\n""#,
+        ],
+    )?;
+    let parents = String::from_utf8(parents)?;
+    let parents: Vec<_> = parents.lines().collect();
+    let show = |revision: &str| {
+        jj(
+            root,
+            &["file", "show", "--revision", revision, "--", &pattern],
+        )
+    };
+    match parents.as_slice() {
+        [parent] => show(parent),
+        _ => {
+            // The working copy is a merge, `jj diff` compares it with the merged parents.
+            let base = show("fork_point(@-)")?;
+            let contents = parents
+                .iter()
+                .map(|parent| show(parent))
+                .collect::<Result<Vec<_>>>()?;
+            merge_trivially(base, contents)
+        }
+    }
+}
+
+/// Merges the `contents` of a file in the parents of a merge with the `base` they forked from.
+/// This only succeeds if all parents that changed the file made the same change.
+fn merge_trivially(base: Vec<u8>, contents: Vec<Vec<u8>>) -> Result<Vec<u8>> {
+    let mut changed = contents.into_iter().filter(|contents| *contents != base);
+    let Some(merged) = changed.next() else {
+        return Ok(base);
+    };
+    if changed.any(|contents| contents != merged) {
+        bail!("file was changed differently in the parents of the merge")
+    }
+    Ok(merged)
+}
+
+pub fn get_current_head_name(file: &Path) -> Result<Arc<ArcSwap<Box<str>>>> {
+    debug_assert!(!file.exists() || file.is_file());
+    debug_assert!(file.is_absolute());
+    let file = file.canonicalize().context("resolve symlinks")?;
+    let root = find_workspace_root(file.parent().context("file has no parent directory")?)?;
+
+    // The working copy commit rarely has bookmarks, so fall back to its change id.
+    let name = jj(
+        root,
+        &[
+            "log",
+            "--no-graph",
+            "--revisions",
+            "@",
+            "--template",
+            r#"if(bookmarks, bookmarks.join(" "), change_id.shortest(8))"#,
+        ],
+    )?;
+    let name = String::from_utf8(name)?.trim().to_owned();
+
+    Ok(Arc::new(ArcSwap::from_pointee(name.into_boxed_str())))
+}
+
+pub fn for_each_changed_file(cwd: &Path, f: impl Fn(Result<FileChange>) -> bool) -> Result<()> {
+    let cwd = cwd.canonicalize()?;
+    let root = find_workspace_root(&cwd)?;
+    // Files changed since jj last snapshotted the working copy are not listed.
+    let summary = jj(root, &["diff", "--summary", "--revisions", "@"])?;
+    let summary = String::from_utf8(summary)?;
+
+    for line in summary.lines() {
+        let Some(change) = parse_summary_line(root, line) else {
+            continue;
+        };
+        if !f(Ok(change)) {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses a line of `jj diff --summary` with paths relative to `root`.
+fn parse_summary_line(root: &Path, line: &str) -> Option<FileChange> {
+    let (status, path) = line.split_once(' ')?;
+    let change = match status {
+        "M" => FileChange::Modified {
+            path: root.join(path),
+        },
+        // Jujutsu tracks new files automatically, they are shown as untracked
+        // like in git.
+        "A" => FileChange::Untracked {
+            path: root.join(path),
+        },
+        "D" => FileChange::Deleted {
+            path: root.join(path),
+        },
+        "R" => {
+            let (from_path, to_path) = parse_rename(path)?;
+            FileChange::Renamed {
+                from_path: root.join(from_path),
+                to_path: root.join(to_path),
+            }
+        }
+        "C" => FileChange::Untracked {
+            path: root.join(parse_rename(path)?.1),
+        },
+        _ => return None,
+    };
+    Some(change)
+}
+
+/// Parses a rename in the form `common/{from => to}/suffix`.
+fn parse_rename(path: &str) -> Option<(PathBuf, PathBuf)> {
+    let (prefix, rest) = path.split_once('{')?;
+    let (renamed, suffix) = rest.split_once('}')?;
+    let (from, to) = renamed.split_once(" => ")?;
+    // Either side may be empty, e.g. `{ => dir}/file`, which would leave a leading slash.
+    let join = |name: &str| {
+        let path = format!("{prefix}{name}{suffix}").replace("//", "/");
+        PathBuf::from(path.trim_start_matches('/'))
+    };
+    Some((join(from), join(to)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn summary_line() {
+        let root = Path::new("/repo");
+        let path = |change: FileChange| change.path().to_owned();
+        assert!(matches!(
+            parse_summary_line(root, "M src/main.rs"),
+            Some(FileChange::Modified { .. })
+        ));
+        assert!(matches!(
+            parse_summary_line(root, "A new file.txt"),
+            Some(FileChange::Untracked { .. })
+        ));
+        assert_eq!(
+            path(parse_summary_line(root, "A new file.txt").unwrap()),
+            Path::new("/repo/new file.txt")
+        );
+        assert!(matches!(
+            parse_summary_line(root, "D gone"),
+            Some(FileChange::Deleted { .. })
+        ));
+        assert!(parse_summary_line(root, "").is_none());
+    }
+
+    #[test]
+    fn rename() {
+        let rename = |path| {
+            let (from, to) = parse_rename(path).unwrap();
+            (
+                from.to_str().unwrap().to_owned(),
+                to.to_str().unwrap().to_owned(),
+            )
+        };
+        assert_eq!(rename("{a => b}"), ("a".into(), "b".into()));
+        assert_eq!(
+            rename("src/{old => new}/lib.rs"),
+            ("src/old/lib.rs".into(), "src/new/lib.rs".into())
+        );
+        assert_eq!(rename("{ => dir}/file"), ("file".into(), "dir/file".into()));
+        assert_eq!(rename("a/{b => }/c"), ("a/b/c".into(), "a/c".into()));
+        assert!(parse_rename("plain").is_none());
+    }
+
+    #[test]
+    fn trivial_merge() {
+        let merge = |base: &str, contents: &[&str]| {
+            let contents = contents.iter().map(|c| c.as_bytes().to_vec()).collect();
+            merge_trivially(base.as_bytes().to_vec(), contents)
+                .ok()
+                .map(|merged| String::from_utf8(merged).unwrap())
+        };
+        assert_eq!(merge("a", &["a", "a"]).as_deref(), Some("a"));
+        assert_eq!(merge("a", &["b", "a"]).as_deref(), Some("b"));
+        assert_eq!(merge("a", &["a", "b", "b"]).as_deref(), Some("b"));
+        assert_eq!(merge("a", &["b", "c"]), None);
+    }
+
+    #[test]
+    fn file_pattern() {
+        assert_eq!(
+            root_file_pattern(Path::new("dir/a \"b\".rs")).unwrap(),
+            r#"root-file:"dir/a \"b\".rs""#
+        );
+    }
+}
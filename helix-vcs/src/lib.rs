@@ -1,27 +1,34 @@
 //! `helix_vcs` provides types for working with diffs from a Version Control System (VCS).
-//! Currently `git` is the only supported provider for diffs, but this architecture allows
-//! for other providers to be added in the future.
+//! Providers for `git`, Jujutsu (`jj`) and Mercurial (`hg`) are compiled in with the
+//! respective features.
 
 use anyhow::{anyhow, bail, Result};
 use arc_swap::ArcSwap;
 use std::{
+    ops::Range,
     path::{Path, PathBuf},
     sync::Arc,
 };
 
 #[cfg(feature = "git")]
 mod git;
+#[cfg(feature = "hg")]
+mod hg;
+#[cfg(feature = "jj")]
+mod jj;
 
+mod blame;
 mod diff;
 
-pub use diff::{DiffHandle, Hunk};
+pub use blame::{BlameCommit, FileBlame};
+pub use diff::{Diff, DiffHandle, Hunk};
 
 mod status;
 
 pub use status::FileChange;
 
-/// Contains all active diff providers. Diff providers are compiled in via features. Currently
-/// only `git` is supported.
+/// Contains all active diff providers. Diff providers are compiled in via features and are
+/// tried in order until one recognizes the repository of a file.
 #[derive(Clone)]
 pub struct DiffProviderRegistry {
     providers: Vec<DiffProvider>,
@@ -57,6 +64,67 @@ pub fn get_current_head_name(&self, file: &Path) -> Option<Arc<ArcSwap<Box<str>>
             })
     }
 
+    /// Get the commits that last changed each line of the committed version of the given file.
+    /// This walks the history of the file and may be slow.
+    pub fn blame(&self, file: &Path) -> Option<FileBlame> {
+        self.providers
+            .iter()
+            .find_map(|provider| match provider.blame(file) {
+                Ok(res) => Some(res),
+                Err(err) => {
+                    log::debug!("{err:#?}");
+                    log::debug!("failed to blame {}", file.display());
+                    None
+                }
+            })
+    }
+
+    /// Get the staged version of the given file. Returns `None` if the provider has no
+    /// staging area.
+    pub fn get_index_base(&self, file: &Path) -> Option<Vec<u8>> {
+        self.providers
+            .iter()
+            .filter_map(|provider| provider.get_index_base(file))
+            .find_map(|res| match res {
+                Ok(res) => Some(res),
+                Err(err) => {
+                    log::debug!("{err:#?}");
+                    log::debug!("failed to open index base for {}", file.display());
+                    None
+                }
+            })
+    }
+
+    /// Stage the changes of `doc`, the contents of the given file, that intersect `lines`.
+    /// Returns the number of staged hunks.
+    pub fn stage_lines(&self, file: &Path, doc: &[u8], lines: &[Range<u32>]) -> Result<usize> {
+        self.with_staging_provider(|provider| provider.stage_lines(file, doc, lines))
+    }
+
+    /// Unstage the staged changes of the given file that intersect `lines` of its diff base.
+    /// Returns the number of unstaged hunks.
+    pub fn unstage_lines(&self, file: &Path, lines: &[Range<u32>]) -> Result<usize> {
+        self.with_staging_provider(|provider| provider.unstage_lines(file, lines))
+    }
+
+    /// Returns the result of the first provider that succeeds or the first error. Providers
+    /// that do not support the operation return `None` and are skipped.
+    fn with_staging_provider<T>(
+        &self,
+        f: impl Fn(&DiffProvider) -> Option<Result<T>>,
+    ) -> Result<T> {
+        let mut error = None;
+        for res in self.providers.iter().filter_map(f) {
+            match res {
+                Ok(res) => return Ok(res),
+                Err(err) => {
+                    error.get_or_insert(err);
+                }
+            }
+        }
+        Err(error.unwrap_or_else(|| anyhow!("Staging is only supported in git repositories")))
+    }
+
     /// Fire-and-forget changed file iteration. Runs everything in a background task. Keeps
     /// iteration until `on_change` returns `false`.
     pub fn for_each_changed_file(
@@ -79,11 +147,16 @@ pub fn for_each_changed_file(
 
 impl Default for DiffProviderRegistry {
     fn default() -> Self {
-        // currently only git is supported
-        // TODO make this configurable when more providers are added
+        // TODO make this configurable
         let providers = vec![
+            // Jujutsu workspaces may be colocated with a git repository. The jj provider
+            // comes first so its view of the working copy is used for them.
+            #[cfg(feature = "jj")]
+            DiffProvider::Jj,
             #[cfg(feature = "git")]
             DiffProvider::Git,
+            #[cfg(feature = "hg")]
+            DiffProvider::Hg,
             DiffProvider::None,
         ];
         DiffProviderRegistry { providers }
@@ -98,26 +171,90 @@ fn default() -> Self {
 enum DiffProvider {
     #[cfg(feature = "git")]
     Git,
+    #[cfg(feature = "jj")]
+    Jj,
+    #[cfg(feature = "hg")]
+    Hg,
     None,
 }
 
 impl DiffProvider {
+    #[cfg_attr(
+        not(any(feature = "git", feature = "jj", feature = "hg")),
+        allow(unused_variables)
+    )]
     fn get_diff_base(&self, file: &Path) -> Result<Vec<u8>> {
         match self {
             #[cfg(feature = "git")]
             Self::Git => git::get_diff_base(file),
+            #[cfg(feature = "jj")]
+            Self::Jj => jj::get_diff_base(file),
+            #[cfg(feature = "hg")]
+            Self::Hg => hg::get_diff_base(file),
             Self::None => bail!("No diff support compiled in"),
         }
     }
 
+    #[cfg_attr(
+        not(any(feature = "git", feature = "jj", feature = "hg")),
+        allow(unused_variables)
+    )]
     fn get_current_head_name(&self, file: &Path) -> Result<Arc<ArcSwap<Box<str>>>> {
         match self {
             #[cfg(feature = "git")]
             Self::Git => git::get_current_head_name(file),
+            #[cfg(feature = "jj")]
+            Self::Jj => jj::get_current_head_name(file),
+            #[cfg(feature = "hg")]
+            Self::Hg => hg::get_current_head_name(file),
             Self::None => bail!("No diff support compiled in"),
         }
     }
 
+    #[cfg_attr(not(feature = "git"), allow(unused_variables))]
+    fn blame(&self, file: &Path) -> Result<FileBlame> {
+        match self {
+            #[cfg(feature = "git")]
+            Self::Git => git::blame(file),
+            #[cfg(feature = "jj")]
+            Self::Jj => bail!("Blame is not supported for jj"),
+            #[cfg(feature = "hg")]
+            Self::Hg => bail!("Blame is not supported for hg"),
+            Self::None => bail!("No diff support compiled in"),
+        }
+    }
+
+    #[cfg_attr(not(feature = "git"), allow(unused_variables))]
+    fn get_index_base(&self, file: &Path) -> Option<Result<Vec<u8>>> {
+        match self {
+            #[cfg(feature = "git")]
+            Self::Git => Some(git::get_index_base(file)),
+            _ => None,
+        }
+    }
+
+    #[cfg_attr(not(feature = "git"), allow(unused_variables))]
+    fn stage_lines(&self, file: &Path, doc: &[u8], lines: &[Range<u32>]) -> Option<Result<usize>> {
+        match self {
+            #[cfg(feature = "git")]
+            Self::Git => Some(git::stage_lines(file, doc, lines)),
+            _ => None,
+        }
+    }
+
+    #[cfg_attr(not(feature = "git"), allow(unused_variables))]
+    fn unstage_lines(&self, file: &Path, lines: &[Range<u32>]) -> Option<Result<usize>> {
+        match self {
+            #[cfg(feature = "git")]
+            Self::Git => Some(git::unstage_lines(file, lines)),
+            _ => None,
+        }
+    }
+
+    #[cfg_attr(
+        not(any(feature = "git", feature = "jj", feature = "hg")),
+        allow(unused_variables)
+    )]
     fn for_each_changed_file(
         &self,
         cwd: &Path,
@@ -126,6 +263,10 @@ fn for_each_changed_file(
         match self {
             #[cfg(feature = "git")]
             Self::Git => git::for_each_changed_file(cwd, f),
+            #[cfg(feature = "jj")]
+            Self::Jj => jj::for_each_changed_file(cwd, f),
+            #[cfg(feature = "hg")]
+            Self::Hg => hg::for_each_changed_file(cwd, f),
             Self::None => bail!("No diff support compiled in"),
         }
     }
@@ -0,0 +1,182 @@
+use helix_core::text_annotations::LineAnnotation;
+use helix_core::{Position, RopeSlice};
+
+use crate::Document;
+
+/// The resolved code lenses of a single document line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeLensLine {
+    pub line: usize,
+    /// The titles of all lenses on the line.
+    pub titles: Vec<String>,
+}
+
+/// Groups the resolved code lenses of `doc` by line. Unresolved lenses have no
+/// title yet and are skipped. Use [`Document::code_lens_lines`] for the cached result.
+pub(crate) fn code_lens_lines(doc: &Document) -> Vec<CodeLensLine> {
+    let text = doc.text().slice(..);
+    let mut lines: Vec<CodeLensLine> = Vec::new();
+    for code_lens in &doc.code_lenses {
+        let Some(command) = &code_lens.lens.command else {
+            continue;
+        };
+        if code_lens.start > text.len_chars() {
+            continue;
+        }
+        let line = text.char_to_line(code_lens.start);
+        match lines.last_mut() {
+            Some(last) if last.line == line => last.titles.push(command.title.clone()),
+            _ => lines.push(CodeLensLine {
+                line,
+                titles: vec![command.title.clone()],
+            }),
+        }
+    }
+    lines
+}
+
+/// Reserves a virtual line above every line with code lenses.
+///
+/// The lenses of a line are placed at the end of the previous line, the lenses of
+/// the first line are placed above the document.
+pub struct CodeLenses<'a> {
+    text: RopeSlice<'a>,
+    lines: &'a [CodeLensLine],
+}
+
+impl<'a> CodeLenses<'a> {
+    pub fn new(doc: &'a Document) -> Box<Self> {
+        Box::new(CodeLenses {
+            text: doc.text().slice(..),
+            lines: doc.code_lens_lines(),
+        })
+    }
+}
+
+impl LineAnnotation for CodeLenses<'_> {
+    fn insert_virtual_lines(
+        &mut self,
+        line_end_char_idx: usize,
+        _line_end_visual_pos: Position,
+        doc_line: usize,
+    ) -> Position {
+        let next_line = doc_line + 1;
+        // soft wrapped lines end in the middle of the document line
+        let at_line_end = next_line < self.text.len_lines()
+            && self.text.line_to_char(next_line) == line_end_char_idx;
+        if at_line_end
+            && self
+                .lines
+                .binary_search_by_key(&next_line, |lens_line| lens_line.line)
+                .is_ok()
+        {
+            Position::new(1, 0)
+        } else {
+            Position::new(0, 0)
+        }
+    }
+
+    fn insert_virtual_lines_above_text(&mut self) -> usize {
+        self.lines
+            .first()
+            .is_some_and(|lens_line| lens_line.line == 0) as usize
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use arc_swap::ArcSwap;
+    use helix_core::doc_formatter::TextFormat;
+    use helix_core::text_annotations::TextAnnotations;
+    use helix_core::{syntax, visual_offset_from_block, Rope};
+    use helix_lsp::{lsp, LanguageServerId};
+
+    use super::*;
+    use crate::document::DocumentCodeLens;
+    use crate::editor::Config;
+
+    fn doc_with_lenses(text: &str, lenses: &[(usize, Option<&str>)]) -> Document {
+        let mut doc = Document::from(
+            Rope::from(text),
+            None,
+            Arc::new(ArcSwap::new(Arc::new(Config::default()))),
+            Arc::new(ArcSwap::from_pointee(syntax::Loader::default())),
+        );
+        doc.code_lenses = lenses
+            .iter()
+            .map(|&(start, title)| DocumentCodeLens {
+                start,
+                lens: lsp::CodeLens {
+                    range: lsp::Range::default(),
+                    command: title.map(|title| lsp::Command {
+                        title: title.to_string(),
+                        command: "test".to_string(),
+                        arguments: None,
+                    }),
+                    data: None,
+                },
+                language_server_id: LanguageServerId::default(),
+            })
+            .collect();
+        doc.refresh_code_lens_lines();
+        doc
+    }
+
+    fn lens_line(line: usize, titles: &[&str]) -> CodeLensLine {
+        CodeLensLine {
+            line,
+            titles: titles.iter().map(|title| title.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn code_lens_lines_group_lenses_by_line() {
+        let doc = doc_with_lenses(
+            "foo\n  bar\nbaz\n",
+            &[
+                (0, Some("1 reference")),
+                (2, Some("run")),
+                (4, None),
+                (6, Some("2 references")),
+                (10, Some("test")),
+                (100, Some("out of range")),
+            ],
+        );
+        assert_eq!(
+            doc.code_lens_lines(),
+            [
+                lens_line(0, &["1 reference", "run"]),
+                lens_line(1, &["2 references"]),
+                lens_line(2, &["test"]),
+            ]
+        );
+
+        let doc = doc_with_lenses("foo\n", &[(0, None)]);
+        assert!(doc.code_lens_lines().is_empty());
+    }
+
+    #[test]
+    fn code_lenses_reserve_lines_above_their_line() {
+        let doc = doc_with_lenses("foo\nbar\nbaz\nqux", &[(0, Some("a")), (9, Some("c"))]);
+        let mut annotations = TextAnnotations::default();
+        annotations.add_line_annotation(CodeLenses::new(&doc));
+        let text = doc.text().slice(..);
+        let text_fmt = TextFormat::default();
+        let row = |block, pos| {
+            visual_offset_from_block(text, block, pos, &text_fmt, &annotations)
+                .0
+                .row
+        };
+
+        // the lenses of the first line are displayed above the document
+        assert_eq!(row(0, 0), 1);
+        assert_eq!(row(0, 4), 2);
+        // the lenses of other lines after the end of the previous line
+        assert_eq!(row(0, 8), 4);
+        assert_eq!(row(0, 12), 5);
+        assert_eq!(row(4, 8), 2);
+        assert_eq!(row(8, 12), 1);
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+
+use helix_core::diff::compare_ropes;
+use helix_core::text_annotations::LineAnnotation;
+use helix_core::{Operation, Position, Rope, RopeSlice};
+use helix_vcs::{Diff, Hunk};
+
+use crate::Document;
+
+/// The filler lines of a document in diff mode. Filler lines take the place of the lines
+/// that only exist in the other document so that the lines of both documents stay
+/// aligned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiffFiller {
+    /// The line after which the filler lines are displayed.
+    pub line: usize,
+    pub count: usize,
+}
+
+/// Returns the filler lines of `doc` if it is in diff mode, sorted by line.
+///
+/// The filler lines of a hunk at the start of the document are displayed above the first
+/// line and are not included, see [`top_diff_fillers`].
+pub fn diff_fillers(doc: &Document) -> Vec<DiffFiller> {
+    let Some(diff_mode) = doc.diff_mode() else {
+        return Vec::new();
+    };
+    let diff = diff_mode.handle.load();
+    (0..diff.len())
+        .map(|i| diff.nth_hunk(i))
+        .filter(|hunk: &Hunk| hunk.after.end != 0 && hunk.before.len() > hunk.after.len())
+        .map(|hunk| DiffFiller {
+            line: hunk.after.end as usize - 1,
+            count: hunk.before.len() - hunk.after.len(),
+        })
+        .collect()
+}
+
+/// Returns the number of filler lines displayed above the first line of `doc` for the
+/// lines at the start of the other document of the diff.
+pub fn top_diff_fillers(doc: &Document) -> usize {
+    let Some(diff_mode) = doc.diff_mode() else {
+        return 0;
+    };
+    let diff = diff_mode.handle.load();
+    if diff.is_empty() {
+        return 0;
+    }
+    let hunk = diff.nth_hunk(0);
+    if hunk.after.end == 0 {
+        hunk.before.len()
+    } else {
+        0
+    }
+}
+
+/// The changed characters of the hunks of a diff, computed on demand and cached until the
+/// diff is recomputed.
+#[derive(Debug, Default)]
+pub struct HunkTextChanges {
+    revision: u64,
+    hunks: HashMap<u32, Arc<[Range<usize>]>>,
+}
+
+impl HunkTextChanges {
+    pub(crate) fn get(&mut self, diff: &Diff, n: u32) -> Arc<[Range<usize>]> {
+        if self.revision != diff.revision() {
+            self.revision = diff.revision();
+            self.hunks.clear();
+        }
+        self.hunks
+            .entry(n)
+            .or_insert_with(|| hunk_text_changes(diff, &diff.nth_hunk(n)).into())
+            .clone()
+    }
+}
+
+/// Diffs the lines of `hunk` by character and returns the inserted ranges relative to the
+/// start of the hunk.
+fn hunk_text_changes(diff: &Diff, hunk: &Hunk) -> Vec<Range<usize>> {
+    if hunk.before.is_empty() || hunk.after.is_empty() {
+        return Vec::new();
+    }
+    let base = diff.diff_base().slice(..);
+    let text = diff.doc().slice(..);
+    let before = Rope::from(base.slice(
+        base.line_to_char(hunk.before.start as usize)..base.line_to_char(hunk.before.end as usize),
+    ));
+    let after = Rope::from(text.slice(
+        text.line_to_char(hunk.after.start as usize)..text.line_to_char(hunk.after.end as usize),
+    ));
+
+    let mut ranges = Vec::new();
+    let mut pos = 0;
+    for change in compare_ropes(&before, &after).changes().changes() {
+        match change {
+            Operation::Retain(n) => pos += n,
+            Operation::Delete(_) => (),
+            Operation::Insert(inserted) => {
+                let len = inserted.chars().count();
+                ranges.push(pos..pos + len);
+                pos += len;
+            }
+        }
+    }
+    // Large hunks are not diffed by character, they would be highlighted entirely.
+    if ranges.first() == Some(&(0..after.len_chars())) {
+        ranges.clear();
+    }
+    ranges
+}
+
+/// Reserves the filler lines of a document in diff mode.
+pub struct DiffFillers<'a> {
+    text: RopeSlice<'a>,
+    fillers: Vec<DiffFiller>,
+    top_fillers: usize,
+}
+
+impl<'a> DiffFillers<'a> {
+    pub fn new(doc: &'a Document) -> Box<Self> {
+        Box::new(DiffFillers {
+            text: doc.text().slice(..),
+            fillers: diff_fillers(doc),
+            top_fillers: top_diff_fillers(doc),
+        })
+    }
+}
+
+impl LineAnnotation for DiffFillers<'_> {
+    fn insert_virtual_lines(
+        &mut self,
+        line_end_char_idx: usize,
+        _line_end_visual_pos: Position,
+        doc_line: usize,
+    ) -> Position {
+        let next_line = doc_line + 1;
+        // soft wrapped lines end in the middle of the document line
+        let at_line_end = next_line >= self.text.len_lines()
+            || self.text.line_to_char(next_line) == line_end_char_idx;
+        if !at_line_end {
+            return Position::new(0, 0);
+        }
+        match self
+            .fillers
+            .binary_search_by_key(&doc_line, |filler| filler.line)
+        {
+            Ok(idx) => Position::new(self.fillers[idx].count, 0),
+            Err(_) => Position::new(0, 0),
+        }
+    }
+
+    fn insert_virtual_lines_above_text(&mut self) -> usize {
+        self.top_fillers
+    }
+}
@@ -9,13 +9,14 @@
 use helix_core::diagnostic::DiagnosticProvider;
 use helix_core::doc_formatter::TextFormat;
 use helix_core::encoding::Encoding;
+use helix_core::merge_conflict::{self, Conflict};
 use helix_core::snippets::{ActiveSnippet, SnippetRenderCtx};
 use helix_core::syntax::config::LanguageServerFeature;
-use helix_core::text_annotations::{InlineAnnotation, Overlay};
+use helix_core::text_annotations::{Fold, InlineAnnotation, Overlay};
 use helix_event::TaskController;
 use helix_lsp::util::lsp_pos_to_pos;
 use helix_stdx::faccess::{copy_metadata, readonly};
-use helix_vcs::{DiffHandle, DiffProviderRegistry};
+use helix_vcs::{BlameCommit, Diff, DiffHandle, DiffProviderRegistry, FileBlame};
 use once_cell::sync::OnceCell;
 use thiserror;
 
@@ -31,19 +32,21 @@
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::{Arc, Weak};
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 use helix_core::{
     editor_config::EditorConfig,
     encoding,
     history::{History, State, UndoKind},
     indent::{auto_detect_indent_style, IndentStyle},
-    line_ending::auto_detect_line_ending,
+    line_ending::{auto_detect_line_ending, line_end_char_index},
     syntax::{self, config::LanguageConfiguration},
     ChangeSet, Diagnostic, LineEnding, Range, Rope, RopeBuilder, Selection, Syntax, Transaction,
 };
 
 use crate::{
+    annotations::code_lens::{code_lens_lines, CodeLensLine},
+    annotations::diff::HunkTextChanges,
     editor::Config,
     events::{DocumentDidChange, SelectionDidChange},
     expansion,
@@ -130,6 +133,33 @@ pub struct SavePoint {
     revert: Mutex<Transaction>,
 }
 
+/// A side by side comparison with another document, see [`Editor::diff_documents`].
+#[derive(Debug, Clone)]
+pub struct DiffMode {
+    /// The document this document is compared with.
+    pub partner: DocumentId,
+    /// The diff with the partner as the diff base.
+    pub handle: DiffHandle,
+    /// The changed characters of the hunks, see [`DiffMode::hunk_text_changes`].
+    text_changes: Arc<Mutex<HunkTextChanges>>,
+}
+
+impl DiffMode {
+    pub fn new(partner: DocumentId, handle: DiffHandle) -> Self {
+        DiffMode {
+            partner,
+            handle,
+            text_changes: Arc::default(),
+        }
+    }
+
+    /// Returns the ranges of the characters that changed within the `n`th hunk of `diff`,
+    /// relative to the start of the hunk. The ranges are cached until the diff is recomputed.
+    pub fn hunk_text_changes(&self, diff: &Diff, n: u32) -> Arc<[std::ops::Range<usize>]> {
+        self.text_changes.lock().get(diff, n)
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum DocumentOpenError {
     #[error("path must be a regular file, symlink, or directory")]
@@ -151,6 +181,8 @@ pub struct Document {
     pub(crate) inlay_hints: HashMap<ViewId, DocumentInlayHints>,
     /// Jump label overlays for each view.
     pub(crate) jump_labels: HashMap<ViewId, Vec<Overlay>>,
+    /// Folded regions for each view, sorted by their start and never overlapping.
+    pub(crate) folds: HashMap<ViewId, Vec<Fold>>,
     /// LSP document highlights for each view, stored as char ranges.
     pub(crate) document_highlights: HashMap<ViewId, DocumentHighlights>,
     /// Set to `true` when the document is updated, reset to `false` on the next inlay hints
@@ -200,7 +232,15 @@ pub struct Document {
     pub(crate) language_servers: HashMap<LanguageServerName, Arc<Client>>,
 
     diff_handle: Option<DiffHandle>,
+    /// The diff with the staged version of the file, see [`Document::staged_diff_handle`].
+    staged_diff_handle: Option<DiffHandle>,
+    /// Set while the document is compared with another document, see [`Document::diff_mode`].
+    pub(crate) diff_mode: Option<DiffMode>,
     version_control_head: Option<Arc<ArcSwap<Box<str>>>>,
+    /// The blame of the committed version of the file, see [`Document::line_blame`].
+    pub blame: Option<FileBlame>,
+    /// The merge conflicts in the document, see [`Document::conflicts`].
+    conflicts: Vec<Conflict>,
 
     // when document was used for most-recent-used buffer picker
     pub focused_at: std::time::Instant,
@@ -211,15 +251,23 @@ pub struct Document {
 
     /// Annotations for LSP document color swatches
     pub color_swatches: Option<DocumentColorSwatches>,
+    /// LSP semantic tokens, layered on top of the tree-sitter highlights.
+    pub semantic_tokens: Option<DocumentSemanticTokens>,
     /// Cached LSP document links for navigation (e.g. goto_file).
     pub document_links: Vec<DocumentLink>,
+    /// LSP code lenses sorted by their position, displayed above the lines they apply to.
+    /// [`Self::refresh_code_lens_lines`] must be called after changing them.
+    pub code_lenses: Vec<DocumentCodeLens>,
+    code_lens_lines: Vec<CodeLensLine>,
     // NOTE: ideally this would live on the handler for color swatches. This is blocked on a
     // large refactor that would make `&mut Editor` available on the `DocumentDidChange` event.
     pub color_swatch_controller: TaskController,
+    pub semantic_tokens_controller: TaskController,
     /// Per-view task controllers for canceling in-flight document highlight requests.
     pub document_highlight_controllers: HashMap<ViewId, TaskController>,
     pub pull_diagnostic_controller: TaskController,
     pub document_link_controller: TaskController,
+    pub code_lens_controller: TaskController,
 
     // NOTE: this field should eventually go away - we should use the Editor's syn_loader instead
     // of storing a copy on every doc. Then we can remove the surrounding `Arc` and use the
@@ -234,6 +282,31 @@ pub struct DocumentColorSwatches {
     pub color_swatches_padding: Vec<InlineAnnotation>,
 }
 
+/// Semantic tokens returned by LSP `textDocument/semanticTokens/full` for a document.
+#[derive(Debug, Clone)]
+pub struct DocumentSemanticTokens {
+    /// The language server which sent the tokens. Its legend decodes the token types and
+    /// modifiers.
+    pub language_server_id: LanguageServerId,
+    /// The `resultId` of the last response, used to request deltas.
+    pub result_id: Option<String>,
+    /// The tokens as sent by the server, needed to apply deltas.
+    pub data: Vec<lsp::SemanticToken>,
+    /// The decoded tokens, sorted and non-overlapping.
+    pub tokens: Vec<SemanticToken>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SemanticToken {
+    /// Character offsets in the document for the token range.
+    pub start: usize,
+    pub end: usize,
+    /// Index into the legend's token types.
+    pub token_type: u32,
+    /// Bitset of indices into the legend's token modifiers.
+    pub token_modifiers: u32,
+}
+
 /// Highlight ranges returned by LSP `textDocument/documentHighlight` for a view.
 #[derive(Debug, Clone, Default)]
 pub struct DocumentHighlights {
@@ -249,6 +322,23 @@ pub struct DocumentLink {
     pub language_server_id: LanguageServerId,
 }
 
+/// The blame of a single line of a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineBlame<'a> {
+    /// The line is unchanged since the commit that last changed it.
+    Committed(&'a BlameCommit),
+    /// The line was added or modified since the last commit.
+    Uncommitted,
+}
+
+#[derive(Debug, Clone)]
+pub struct DocumentCodeLens {
+    /// Character offset in the document of the start of the lens range.
+    pub start: usize,
+    pub lens: lsp::CodeLens,
+    pub language_server_id: LanguageServerId,
+}
+
 /// Inlay hints for a single `(Document, View)` combo.
 ///
 /// There are `*_inlay_hints` field for each kind of hints an LSP can send since we offer the
@@ -720,6 +810,7 @@ pub fn from(
         let line_ending = config.load().default_line_ending.into();
         let changes = ChangeSet::new(text.slice(..));
         let old_state = None;
+        let conflicts = merge_conflict::find_conflicts(text.slice(..));
 
         Self {
             id: DocumentId::default(),
@@ -750,20 +841,30 @@ pub fn from(
             modified_since_accessed: false,
             language_servers: HashMap::new(),
             diff_handle: None,
+            staged_diff_handle: None,
+            diff_mode: None,
             config,
             version_control_head: None,
             focused_at: std::time::Instant::now(),
             readonly: false,
             jump_labels: HashMap::new(),
+            folds: HashMap::new(),
             document_highlights: HashMap::new(),
             color_swatches: None,
+            semantic_tokens: None,
             document_links: Vec::new(),
+            code_lenses: Vec::new(),
+            code_lens_lines: Vec::new(),
+            blame: None,
+            conflicts,
             color_swatch_controller: TaskController::new(),
+            semantic_tokens_controller: TaskController::new(),
             document_highlight_controllers: HashMap::new(),
             syn_loader,
             previous_diagnostic_ids: HashMap::new(),
             pull_diagnostic_controller: TaskController::new(),
             document_link_controller: TaskController::new(),
+            code_lens_controller: TaskController::new(),
         }
     }
 
@@ -1257,6 +1358,17 @@ pub fn pickup_last_saved_time(&mut self) {
         };
     }
 
+    /// Whether the file was written to since the document was last loaded or saved. The
+    /// contents of the file may still be the same, see [`contents_differ`].
+    pub fn modified_on_disk(&self) -> bool {
+        let Some(path) = self.path() else {
+            return false;
+        };
+        path.metadata()
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|mtime| mtime > self.last_saved_time)
+    }
+
     // Detect if the file is readonly and change the readonly field if necessary (unix only)
     pub fn detect_readonly(&mut self) {
         // Allows setting the flag for files the user cannot modify, like root files
@@ -1297,11 +1409,7 @@ pub fn reload(
         self.pickup_last_saved_time();
         self.detect_indent_and_line_ending();
 
-        match provider_registry.get_diff_base(&path) {
-            Some(diff_base) => self.set_diff_base(diff_base),
-            None => self.diff_handle = None,
-        }
-
+        self.update_diff_bases(provider_registry);
         self.version_control_head = provider_registry.get_current_head_name(&path);
 
         Ok(())
@@ -1380,6 +1488,7 @@ pub fn set_language_by_language_id(
     /// Select text within the [`Document`].
     pub fn set_selection(&mut self, view_id: ViewId, selection: Selection) {
         // TODO: use a transaction?
+        let selection = self.skip_folds(view_id, selection);
         self.selections
             .insert(view_id, selection.ensure_invariants(self.text().slice(..)));
         helix_event::dispatch(SelectionDidChange {
@@ -1427,6 +1536,7 @@ pub fn remove_view(&mut self, view_id: ViewId) {
         self.view_data.remove(&view_id);
         self.inlay_hints.remove(&view_id);
         self.jump_labels.remove(&view_id);
+        self.folds.remove(&view_id);
         self.document_highlights.remove(&view_id);
         self.document_highlight_controllers.remove(&view_id);
     }
@@ -1512,6 +1622,19 @@ fn apply_impl(
         if let Some(diff_handle) = &self.diff_handle {
             diff_handle.update_document(self.text.clone(), false);
         }
+        if let Some(diff_handle) = &self.staged_diff_handle {
+            diff_handle.update_document(self.text.clone(), false);
+        }
+        if let Some(diff_mode) = &self.diff_mode {
+            diff_mode.handle.update_document(self.text.clone(), false);
+        }
+
+        merge_conflict::update_conflicts(
+            &mut self.conflicts,
+            old_doc.slice(..),
+            self.text.slice(..),
+            changes,
+        );
 
         // map diagnostics over changes too
         changes.update_positions(self.diagnostics.iter_mut().map(|diagnostic| {
@@ -1580,6 +1703,25 @@ fn apply_impl(
             apply_inlay_hint_changes(padding_after_inlay_hints);
         }
 
+        // Folds are kept as long as the folded text itself is unchanged.
+        for folds in self.folds.values_mut() {
+            let text = self.text.slice(..);
+            folds.retain_mut(|fold| {
+                let len = fold.end - fold.start;
+                changes.update_positions(
+                    [
+                        (&mut fold.start, Assoc::After),
+                        (&mut fold.end, Assoc::Before),
+                    ]
+                    .into_iter(),
+                );
+                fold.start + len == fold.end
+                    && fold.end <= text.len_chars()
+                    && fold.start == line_end_char_index(&text, text.char_to_line(fold.start))
+                    && fold.end == line_end_char_index(&text, text.char_to_line(fold.end))
+            });
+        }
+
         for highlights in self.document_highlights.values_mut() {
             let text_len = self.text.len_chars();
             let mut updated = Vec::with_capacity(highlights.ranges.len());
@@ -1833,6 +1975,59 @@ pub fn reset_modified(&mut self) {
         self.last_saved_revision = current_revision;
     }
 
+    /// The resolved code lenses grouped by line.
+    pub fn code_lens_lines(&self) -> &[CodeLensLine] {
+        &self.code_lens_lines
+    }
+
+    /// Groups the code lenses by line again after [`Self::code_lenses`] changed.
+    pub fn refresh_code_lens_lines(&mut self) {
+        self.code_lens_lines = code_lens_lines(self);
+    }
+
+    /// Writes the undo history to the data directory in the background so that it can be
+    /// restored with [`Self::load_history`] when the file at `path` is opened again.
+    /// `revision` and `text` are the revision and contents that were written to `path`.
+    pub fn save_history(&self, path: &Path, revision: usize, text: &Rope) {
+        let history = self.history.take();
+        let snapshot = history.clone();
+        self.history.set(history);
+
+        let undo_file = undo_file(path);
+        let text = text.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(err) = write_history(&undo_file, &snapshot, revision, &text) {
+                log::error!("failed to save undo history: {err}");
+            }
+            prune_undo_dir();
+        });
+    }
+
+    /// Restores the undo history saved by [`Self::save_history`] if the document's
+    /// contents match the contents written when the history was saved. A history that
+    /// doesn't match is discarded.
+    pub fn load_history(&mut self) -> Result<(), Error> {
+        let Some(path) = self.path() else {
+            return Ok(());
+        };
+        let undo_file = undo_file(path);
+        let file = match std::fs::File::open(&undo_file) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+        let history = match History::deserialize(io::BufReader::new(file), self.text()) {
+            Ok(history) => history,
+            Err(err) => {
+                let _ = std::fs::remove_file(&undo_file);
+                return Err(err);
+            }
+        };
+        self.last_saved_revision = history.current_revision();
+        self.history.set(history);
+        Ok(())
+    }
+
     /// Set the document's latest saved revision to the given one.
     pub fn set_last_saved_revision(&mut self, rev: usize, save_time: SystemTime) {
         log::debug!(
@@ -1945,10 +2140,64 @@ pub fn supports_language_server(&self, id: LanguageServerId) -> bool {
         self.language_servers().any(|l| l.id() == id)
     }
 
+    /// The diff shown in the gutter and used by diff navigation. While the document is
+    /// compared with another document this is the diff with that document, otherwise the
+    /// diff with the version control base.
     pub fn diff_handle(&self) -> Option<&DiffHandle> {
+        match &self.diff_mode {
+            Some(diff_mode) => Some(&diff_mode.handle),
+            None => self.diff_handle.as_ref(),
+        }
+    }
+
+    /// The diff with the version control base, even while in diff mode.
+    pub fn vcs_diff_handle(&self) -> Option<&DiffHandle> {
         self.diff_handle.as_ref()
     }
 
+    /// The comparison with another document if the document is in diff mode.
+    pub fn diff_mode(&self) -> Option<&DiffMode> {
+        self.diff_mode.as_ref()
+    }
+
+    /// The diff with the staged version of the file. Only set while the staged version
+    /// differs from the version control base, lines which are changed in the
+    /// [`Self::vcs_diff_handle`] but not in this diff are staged.
+    pub fn staged_diff_handle(&self) -> Option<&DiffHandle> {
+        self.staged_diff_handle.as_ref()
+    }
+
+    /// Reads the version control base and the staged version of the file again and
+    /// updates the diffs with them.
+    pub fn update_diff_bases(&mut self, provider_registry: &DiffProviderRegistry) {
+        let Some(path) = self.path() else {
+            return;
+        };
+        let diff_base = provider_registry.get_diff_base(path);
+        let index_base = diff_base
+            .as_ref()
+            .and_then(|_| provider_registry.get_index_base(path))
+            .filter(|index_base| Some(index_base) != diff_base.as_ref())
+            .and_then(|index_base| {
+                from_reader(&mut index_base.as_slice(), Some(self.encoding)).ok()
+            });
+        match index_base {
+            Some((index_base, ..)) => match &self.staged_diff_handle {
+                Some(differ) => {
+                    differ.update_diff_base(index_base);
+                }
+                None => {
+                    self.staged_diff_handle = Some(DiffHandle::new(index_base, self.text.clone()))
+                }
+            },
+            None => self.staged_diff_handle = None,
+        }
+        match diff_base {
+            Some(diff_base) => self.set_diff_base(diff_base),
+            None => self.diff_handle = None,
+        }
+    }
+
     /// Intialize/updates the differ for this document with a new base.
     pub fn set_diff_base(&mut self, diff_base: Vec<u8>) {
         if let Ok((diff_base, ..)) = from_reader(&mut diff_base.as_slice(), Some(self.encoding)) {
@@ -1962,6 +2211,33 @@ pub fn set_diff_base(&mut self, diff_base: Vec<u8>) {
         }
     }
 
+    /// The merge conflicts delimited by conflict markers in the document, sorted by
+    /// their position.
+    pub fn conflicts(&self) -> &[Conflict] {
+        &self.conflicts
+    }
+
+    /// Returns the conflict containing `line`.
+    pub fn conflict_at_line(&self, line: usize) -> Option<&Conflict> {
+        let idx = self
+            .conflicts
+            .partition_point(|conflict| conflict.end < line);
+        self.conflicts
+            .get(idx)
+            .filter(|conflict| conflict.start <= line)
+    }
+
+    /// Returns the blame of `line`, mapping it to the committed version of the
+    /// file through the diff with the diff base.
+    pub fn line_blame(&self, line: usize) -> Option<LineBlame<'_>> {
+        let blame = self.blame.as_ref()?;
+        let base_line = self.diff_handle.as_ref()?.load().base_line(line as u32);
+        match base_line {
+            Some(base_line) => blame.line(base_line).map(LineBlame::Committed),
+            None => Some(LineBlame::Uncommitted),
+        }
+    }
+
     pub fn version_control_head(&self) -> Option<Arc<Box<str>>> {
         self.version_control_head.as_ref().map(|a| a.load_full())
     }
@@ -2368,6 +2644,108 @@ pub fn remove_jump_labels(&mut self, view_id: ViewId) {
         self.jump_labels.remove(&view_id);
     }
 
+    /// Returns the folded regions of this document in `view_id`.
+    pub fn folds(&self, view_id: ViewId) -> &[Fold] {
+        self.folds.get(&view_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Folds the lines `start_line..=end_line` in `view_id`. The first line stays visible
+    /// and the following lines are replaced by a placeholder. Folds inside of the new fold are
+    /// merged into it.
+    ///
+    /// Returns `false` if the lines can't be folded or are already hidden by another fold.
+    pub fn fold_lines(&mut self, view_id: ViewId, start_line: usize, end_line: usize) -> bool {
+        let text = self.text.slice(..);
+        if start_line >= end_line || end_line >= text.len_lines() {
+            return false;
+        }
+        let mut fold = Fold::new(
+            line_end_char_index(&text, start_line),
+            line_end_char_index(&text, end_line),
+        );
+        if fold.start >= fold.end {
+            return false;
+        }
+
+        let folds = self.folds.entry(view_id).or_default();
+        if folds
+            .iter()
+            .any(|other| other.start <= fold.start && fold.end <= other.end)
+        {
+            return false;
+        }
+        folds.retain(|other| {
+            let overlaps = other.start < fold.end && fold.start < other.end;
+            if overlaps {
+                fold.start = fold.start.min(other.start);
+                fold.end = fold.end.max(other.end);
+            }
+            !overlaps
+        });
+        let i = folds.partition_point(|other| other.start < fold.start);
+        folds.insert(i, fold);
+        true
+    }
+
+    /// Removes all folds in `view_id` that overlap the lines `start_line..=end_line`.
+    ///
+    /// Returns the number of removed folds.
+    pub fn unfold_lines(&mut self, view_id: ViewId, start_line: usize, end_line: usize) -> usize {
+        let Some(folds) = self.folds.get_mut(&view_id) else {
+            return 0;
+        };
+        let text = self.text.slice(..);
+        let len = folds.len();
+        folds.retain(|fold| {
+            text.char_to_line(fold.end) < start_line || end_line < text.char_to_line(fold.start)
+        });
+        len - folds.len()
+    }
+
+    /// Removes all folds in `view_id`.
+    pub fn clear_folds(&mut self, view_id: ViewId) {
+        self.folds.remove(&view_id);
+    }
+
+    /// Moves cursors that ended up inside of a fold to the closest edge of the fold in the
+    /// direction the cursor moved in, so that movements skip over folded text.
+    fn skip_folds(&self, view_id: ViewId, selection: Selection) -> Selection {
+        let folds = self.folds(view_id);
+        if folds.is_empty() {
+            return selection;
+        }
+        let text = self.text.slice(..);
+        let old_cursors: Vec<_> = self
+            .selections
+            .get(&view_id)
+            .map(|selection| selection.iter().map(|range| range.cursor(text)).collect())
+            .unwrap_or_default();
+
+        let mut i = 0;
+        selection.transform(|range| {
+            let old_cursor = old_cursors.get(i).copied();
+            i += 1;
+            let cursor = range.cursor(text);
+            let fold_idx = folds.partition_point(|fold| fold.start < cursor);
+            let Some(fold) = folds[..fold_idx]
+                .last()
+                .filter(|fold| fold.conceals(cursor))
+            else {
+                return range;
+            };
+            let cursor = if old_cursor.is_some_and(|old_cursor| old_cursor > cursor) {
+                fold.start
+            } else {
+                fold.end
+            };
+            if helix_core::graphemes::next_grapheme_boundary(text, range.from()) >= range.to() {
+                Range::point(cursor)
+            } else {
+                range.put_cursor(text, cursor, true)
+            }
+        })
+    }
+
     pub fn set_document_highlights(
         &mut self,
         view_id: ViewId,
@@ -2454,6 +2832,67 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     }
 }
 
+/// Whether the contents of the file at `path` differ from `text`. Reads the whole file so
+/// this should be called off the main thread.
+pub fn contents_differ(path: &Path, encoding: &'static encoding::Encoding, text: &Rope) -> bool {
+    let Ok(mut file) = std::fs::File::open(path) else {
+        return false;
+    };
+    match from_reader(&mut file, Some(encoding)) {
+        Ok((contents, ..)) => contents != *text,
+        Err(_) => false,
+    }
+}
+
+/// The file the undo history of the document at `path` is persisted to.
+fn undo_file(path: &Path) -> PathBuf {
+    helix_loader::undo_dir().join(helix_loader::path_hash(path))
+}
+
+/// Undo histories that weren't written for this long are removed by [`prune_undo_dir`].
+const UNDO_FILE_MAX_AGE: Duration = Duration::from_secs(90 * 24 * 60 * 60);
+
+fn write_history(
+    undo_file: &Path,
+    history: &History,
+    revision: usize,
+    text: &Rope,
+) -> Result<(), Error> {
+    use std::io::Write;
+
+    let parent = undo_file.parent().unwrap_or(Path::new("."));
+    std::fs::create_dir_all(parent)?;
+    let mut file = tempfile::NamedTempFile::new_in(parent)?;
+    let mut writer = io::BufWriter::new(&mut file);
+    history.serialize(&mut writer, revision, text)?;
+    writer.flush()?;
+    drop(writer);
+    file.persist(undo_file)?;
+    Ok(())
+}
+
+/// Removes the undo histories of files that weren't saved for [`UNDO_FILE_MAX_AGE`]. Only
+/// runs once per process.
+fn prune_undo_dir() {
+    static PRUNED: std::sync::Once = std::sync::Once::new();
+    PRUNED.call_once(|| {
+        let Ok(entries) = std::fs::read_dir(helix_loader::undo_dir()) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let expired = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age > UNDO_FILE_MAX_AGE);
+            if expired {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    });
+}
+
 #[cfg(test)]
 mod test {
     use arc_swap::ArcSwap;
@@ -2499,6 +2938,41 @@ fn changeset_to_changes_ignore_line_endings() {
         );
     }
 
+    #[test]
+    fn folds() {
+        let text = Rope::from("a\nb\nc\nd\ne\n");
+        let mut doc = Document::from(
+            text,
+            None,
+            Arc::new(ArcSwap::new(Arc::new(Config::default()))),
+            Arc::new(ArcSwap::from_pointee(syntax::Loader::default())),
+        );
+        let view = ViewId::default();
+        doc.set_selection(view, Selection::single(0, 0));
+
+        assert!(doc.fold_lines(view, 1, 2));
+        assert_eq!(doc.folds(view), &[Fold::new(3, 5)]);
+        // folds hidden by another fold are rejected
+        assert!(!doc.fold_lines(view, 1, 2));
+        // enclosing folds absorb the folds inside of them
+        assert!(doc.fold_lines(view, 0, 3));
+        assert_eq!(doc.folds(view), &[Fold::new(1, 7)]);
+        assert_eq!(doc.unfold_lines(view, 4, 4), 0);
+        assert_eq!(doc.unfold_lines(view, 3, 3), 1);
+        assert!(doc.folds(view).is_empty());
+
+        // edits outside of a fold move it
+        assert!(doc.fold_lines(view, 1, 2));
+        let transaction = Transaction::insert(doc.text(), &Selection::point(0), "x".into());
+        doc.apply(&transaction, view);
+        assert_eq!(doc.folds(view), &[Fold::new(4, 6)]);
+
+        // edits inside of a fold remove it
+        let transaction = Transaction::insert(doc.text(), &Selection::point(5), "y".into());
+        doc.apply(&transaction, view);
+        assert!(doc.folds(view).is_empty());
+    }
+
     #[test]
     fn changeset_to_changes() {
         use helix_lsp::{lsp, Client, OffsetEncoding};
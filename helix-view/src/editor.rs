@@ -1,10 +1,15 @@
 use crate::{
-    annotations::diagnostics::{DiagnosticFilter, InlineDiagnosticsConfig},
+    annotations::{
+        diagnostics::{DiagnosticFilter, InlineDiagnosticsConfig},
+        diff::top_diff_fillers,
+    },
     clipboard::ClipboardProvider,
     document::{
-        DocumentOpenError, DocumentSavedEventFuture, DocumentSavedEventResult, Mode, SavePoint,
+        DiffMode, DocumentOpenError, DocumentSavedEventFuture, DocumentSavedEventResult, Mode,
+        SavePoint,
     },
-    events::{DocumentDidClose, DocumentDidOpen, DocumentFocusLost},
+    events::{DocumentDidChangeLanguage, DocumentDidClose, DocumentDidOpen, DocumentFocusLost},
+    file_watcher::FileWatcher,
     graphics::{CursorKind, Rect},
     handlers::Handlers,
     info::Info,
@@ -12,15 +17,16 @@
     register::Registers,
     theme::{self, Theme},
     tree::{self, Tree},
+    view::ViewPosition,
     Document, DocumentId, View, ViewId,
 };
 use helix_event::dispatch;
 use helix_loader::workspace_trust::TrustStatus;
-use helix_vcs::DiffProviderRegistry;
+use helix_vcs::{BlameCommit, DiffHandle, DiffProviderRegistry};
 
 use futures_util::stream::select_all::SelectAll;
 use futures_util::{future, StreamExt};
-use helix_lsp::{Call, LanguageServerId};
+use helix_lsp::{Call, LanguageServerId, OffsetEncoding};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
 use std::{
@@ -337,6 +343,12 @@ pub struct Config {
     /// Time delay defaults to false with 3000ms delay. Focus lost defaults to false.
     #[serde(deserialize_with = "deserialize_auto_save")]
     pub auto_save: AutoSave,
+    /// Watching of open documents and the workspace for external changes.
+    pub file_watcher: FileWatcherConfig,
+    /// Display of the commit that last changed the cursor line.
+    pub inline_blame: InlineBlameConfig,
+    /// Saving registers like the command line and search history between runs.
+    pub persistent_history: PersistentHistoryConfig,
     /// Set a global text_width
     pub text_width: usize,
     /// Time in milliseconds since last keypress before idle timers trigger.
@@ -353,6 +365,13 @@ pub struct Config {
         deserialize_with = "deserialize_duration_millis"
     )]
     pub completion_timeout: Duration,
+    /// Time in milliseconds to complete a key sequence in insert mode before its typed
+    /// characters are kept as text. Defaults to 1000ms.
+    #[serde(
+        serialize_with = "serialize_duration_millis",
+        deserialize_with = "deserialize_duration_millis"
+    )]
+    pub insert_sequence_timeout: Duration,
     /// Whether to insert the completion suggestion on hover. Defaults to true.
     pub preview_completion_insert: bool,
     pub completion_trigger_len: u8,
@@ -406,6 +425,15 @@ pub struct Config {
     /// Whether to automatically remove all whitespace characters preceding line-endings on write.
     /// Defaults to `false`.
     pub trim_trailing_whitespace: bool,
+    /// Whether to save the undo history of documents on write and restore it when the
+    /// unchanged file is opened again. Defaults to `false`.
+    pub persistent_undo: bool,
+    /// Whether to save the session of the workspace on quit and restore it when started
+    /// without files. Defaults to `false`.
+    pub auto_session: bool,
+    /// Whether to listen for remote control requests, for example from `hx --remote`.
+    /// Defaults to `false`.
+    pub remote_control: bool,
     /// Enables smart tab
     pub smart_tab: Option<SmartTabConfig>,
     /// Draw border around popups.
@@ -562,6 +590,10 @@ pub struct LspConfig {
     pub inlay_hints_length_limit: Option<NonZeroU8>,
     /// Display document color swatches
     pub display_color_swatches: bool,
+    /// Highlight the document with LSP semantic tokens
+    pub display_semantic_tokens: bool,
+    /// Display LSP code lenses above the lines they apply to
+    pub display_code_lenses: bool,
     /// Whether to enable snippet support
     pub snippets: bool,
     /// Whether to include declaration in the goto reference query
@@ -582,6 +614,8 @@ fn default() -> Self {
             snippets: true,
             goto_reference_include_declaration: true,
             display_color_swatches: true,
+            display_semantic_tokens: false,
+            display_code_lenses: false,
         }
     }
 }
@@ -593,6 +627,9 @@ pub struct SearchConfig {
     pub smart_case: bool,
     /// Whether the search should wrap after depleting the matches. Default to true.
     pub wrap_around: bool,
+    /// Whether all matches of the last search are highlighted in the visible lines.
+    /// Defaults to false.
+    pub highlight_matches: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -614,6 +651,7 @@ fn default() -> Self {
         Self {
             left: vec![
                 E::Mode,
+                E::MinorMode,
                 E::Spinner,
                 E::FileName,
                 E::ReadOnlyIndicator,
@@ -659,6 +697,9 @@ pub enum StatusLineElement {
     /// The editor mode (Normal, Insert, Visual/Selection)
     Mode,
 
+    /// The name of the active sticky minor mode, like `View` for `Z`
+    MinorMode,
+
     /// The LSP activity spinner
     Spinner,
 
@@ -724,6 +765,9 @@ pub enum StatusLineElement {
 
     /// The base of current working directory
     CurrentWorkingDirectory,
+
+    /// The index of the search match at the cursor out of the total matches
+    SearchPosition,
 }
 
 // Cursor shape is read and used on every rendered frame and so needs
@@ -927,6 +971,80 @@ pub fn newline(&self) -> WhitespaceRenderValue {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct FileWatcherConfig {
+    /// Reload unmodified documents when their file changes on disk. Defaults to true.
+    pub enable: bool,
+    /// Also watch the workspace recursively so that language servers are notified
+    /// about changes to files which are not open. Defaults to false.
+    pub watch_workspace: bool,
+}
+
+impl Default for FileWatcherConfig {
+    fn default() -> Self {
+        Self {
+            enable: true,
+            watch_workspace: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct InlineBlameConfig {
+    /// Show the commit that last changed the cursor line at the end of the line.
+    /// Defaults to false.
+    pub enable: bool,
+    /// The text shown for the commit. `{author}`, `{email}`, `{date}`, `{commit}`
+    /// and `{summary}` are replaced with the details of the commit.
+    pub format: String,
+}
+
+impl Default for InlineBlameConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            format: "{author}, {date} • {summary}".to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct PersistentHistoryConfig {
+    /// Save `registers` to the history file on exit and restore them on startup.
+    /// Defaults to false.
+    pub enable: bool,
+    /// The registers to save. Defaults to the command line (`:`), search (`/`) and
+    /// shell (`|`) histories and the default macro register (`@`).
+    pub registers: Vec<char>,
+    /// The maximum number of entries saved of each history. Defaults to 100.
+    pub max_entries: usize,
+}
+
+impl Default for PersistentHistoryConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            registers: vec![':', '/', '|', '@'],
+            max_entries: 100,
+        }
+    }
+}
+
+impl InlineBlameConfig {
+    /// Formats `commit` according to [`InlineBlameConfig::format`].
+    pub fn format_commit(&self, commit: &BlameCommit) -> String {
+        self.format
+            .replace("{author}", &commit.author)
+            .replace("{email}", &commit.email)
+            .replace("{date}", &commit.date)
+            .replace("{commit}", commit.short_id())
+            .replace("{summary}", &commit.summary)
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct AutoSave {
@@ -1113,8 +1231,12 @@ fn default() -> Self {
             auto_format: true,
             default_yank_register: '"',
             auto_save: AutoSave::default(),
+            file_watcher: FileWatcherConfig::default(),
+            inline_blame: InlineBlameConfig::default(),
+            persistent_history: PersistentHistoryConfig::default(),
             idle_timeout: Duration::from_millis(250),
             completion_timeout: Duration::from_millis(250),
+            insert_sequence_timeout: Duration::from_millis(1000),
             preview_completion_insert: true,
             completion_trigger_len: 2,
             auto_info: true,
@@ -1145,6 +1267,9 @@ fn default() -> Self {
             atomic_save: true,
             trim_final_newlines: false,
             trim_trailing_whitespace: false,
+            persistent_undo: false,
+            auto_session: false,
+            remote_control: false,
             smart_tab: Some(SmartTabConfig::default()),
             popup_border: PopupBorderConfig::None,
             indent_heuristic: IndentationHeuristic::default(),
@@ -1166,6 +1291,7 @@ fn default() -> Self {
         Self {
             wrap_around: true,
             smart_case: true,
+            highlight_matches: false,
         }
     }
 }
@@ -1183,6 +1309,39 @@ pub struct Breakpoint {
     pub log_message: Option<String>,
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct FunctionBreakpoint {
+    pub id: Option<usize>,
+    pub verified: bool,
+    pub message: Option<String>,
+
+    pub name: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct DataBreakpoint {
+    pub id: Option<usize>,
+    pub verified: bool,
+    pub message: Option<String>,
+
+    /// The expression the breakpoint was created for.
+    pub expression: String,
+    /// The id returned by `dataBreakpointInfo` identifying the watched data.
+    pub data_id: String,
+    /// Description of the watched data returned by `dataBreakpointInfo`.
+    pub description: String,
+    /// Whether the data id stays valid across debug sessions.
+    pub can_persist: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WatchExpression {
+    pub expression: String,
+    /// The result of the last evaluation or the error if it failed. `None` until the
+    /// expression was evaluated in a stopped debug session.
+    pub value: Option<Result<String, String>>,
+}
+
 use futures_util::stream::{Flatten, Once};
 
 type Diagnostics = BTreeMap<Uri, Vec<(lsp::Diagnostic, DiagnosticProvider)>>;
@@ -1208,9 +1367,18 @@ pub struct Editor {
     pub language_servers: helix_lsp::Registry,
     pub diagnostics: Diagnostics,
     pub diff_providers: DiffProviderRegistry,
+    pub file_watcher: FileWatcher,
 
     pub debug_adapters: dap::registry::Registry,
     pub breakpoints: HashMap<PathBuf, Vec<Breakpoint>>,
+    pub function_breakpoints: Vec<FunctionBreakpoint>,
+    pub data_breakpoints: Vec<DataBreakpoint>,
+    /// Expressions evaluated whenever a debugger stops.
+    pub watch_expressions: Vec<WatchExpression>,
+    /// The scratch buffer used as a debug console, see [`Editor::debug_console`].
+    pub debug_console: Option<DocumentId>,
+    /// Debugger output that wasn't added to the debug console yet because it isn't displayed.
+    pub debug_console_output: String,
 
     pub syn_loader: Arc<ArcSwap<syntax::Loader>>,
     pub theme_loader: Arc<theme::Loader>,
@@ -1356,8 +1524,14 @@ pub fn new(
             language_servers,
             diagnostics: Diagnostics::new(),
             diff_providers: DiffProviderRegistry::default(),
+            file_watcher: FileWatcher::new(handlers.file_watcher.clone()),
             debug_adapters: dap::registry::Registry::new(),
             breakpoints: HashMap::new(),
+            function_breakpoints: Vec::new(),
+            data_breakpoints: Vec::new(),
+            watch_expressions: Vec::new(),
+            debug_console: None,
+            debug_console_output: String::new(),
             syn_loader,
             theme_loader,
             last_theme: None,
@@ -1423,6 +1597,7 @@ pub fn refresh_config(&mut self, old_config: &Config) {
         let config = self.config();
         self.auto_pairs = (&config.auto_pairs).into();
         self.reset_idle_timer();
+        self.update_file_watcher();
         self._refresh();
         helix_event::dispatch(crate::events::ConfigDidChange {
             editor: self,
@@ -1710,7 +1885,16 @@ pub fn set_doc_path(&mut self, doc_id: DocumentId, path: &Path) {
         doc.language_servers.clear();
         doc.set_path(Some(path));
         doc.detect_editor_config();
-        self.refresh_doc_language(doc_id)
+        self.refresh_doc_language(doc_id);
+        self.update_file_watcher();
+    }
+
+    /// Updates the directories watched for external changes to the open documents.
+    pub fn update_file_watcher(&mut self) {
+        self.file_watcher.update(
+            &self.config().file_watcher,
+            self.documents.values().filter_map(Document::path),
+        );
     }
 
     pub fn refresh_doc_language(&mut self, doc_id: DocumentId) {
@@ -1724,6 +1908,10 @@ pub fn refresh_doc_language(&mut self, doc_id: DocumentId) {
         let diagnostics = Editor::doc_diagnostics(&self.language_servers, &self.diagnostics, doc);
         doc.replace_diagnostics(diagnostics, &[], None);
         doc.reset_all_inlay_hints();
+        dispatch(DocumentDidChangeLanguage {
+            editor: self,
+            doc: doc_id,
+        });
     }
 
     /// Launch a language server for a given document
@@ -1961,7 +2149,7 @@ pub fn switch(&mut self, id: DocumentId, action: Action) {
     }
 
     /// Generate an id for a new document and register it.
-    fn new_document(&mut self, mut doc: Document) -> DocumentId {
+    pub(crate) fn new_document(&mut self, mut doc: Document) -> DocumentId {
         let id = self.next_document_id;
         // Safety: adding 1 from 1 is fine, practically impossible to reach usize max
         self.next_document_id =
@@ -1975,10 +2163,12 @@ fn new_document(&mut self, mut doc: Document) -> DocumentId {
         let stream = UnboundedReceiverStream::new(save_receiver).flatten();
         self.save_queue.push(stream);
 
+        self.update_file_watcher();
+
         id
     }
 
-    fn new_file_from_document(&mut self, action: Action, doc: Document) -> DocumentId {
+    pub fn new_file_from_document(&mut self, action: Action, doc: Document) -> DocumentId {
         let id = self.new_document(doc);
         self.switch(id, action);
         id
@@ -2035,9 +2225,13 @@ pub fn open(&mut self, path: &Path, action: Action) -> Result<DocumentId, Docume
                 Editor::doc_diagnostics(&self.language_servers, &self.diagnostics, &doc);
             doc.replace_diagnostics(diagnostics, &[], None);
 
-            if let Some(diff_base) = self.diff_providers.get_diff_base(&path) {
-                doc.set_diff_base(diff_base);
+            if self.config().persistent_undo {
+                if let Err(err) = doc.load_history() {
+                    log::warn!("failed to restore undo history for {path:?}: {err}");
+                }
             }
+
+            doc.update_diff_bases(&self.diff_providers);
             doc.set_version_control_head(self.diff_providers.get_current_head_name(&path));
 
             let id = self.new_document(doc);
@@ -2070,7 +2264,8 @@ pub fn close_document(&mut self, doc_id: DocumentId, force: bool) -> Result<(),
             Some(doc) => doc,
             None => return Err(CloseError::DoesNotExist),
         };
-        if !force && doc.is_modified() {
+        // The debug console is a log, closing it doesn't lose any work.
+        if !force && doc.is_modified() && self.debug_console != Some(doc_id) {
             return Err(CloseError::BufferModified(doc.display_name().into_owned()));
         }
 
@@ -2113,7 +2308,9 @@ enum Action {
             }
         }
 
+        self.end_diff(doc_id);
         let doc = self.documents.remove(&doc_id).unwrap();
+        self.update_file_watcher();
 
         // If the document we removed was visible in all views, we will have no more views. We don't
         // want to close the editor just for a simple buffer close, so we need to create a new view
@@ -2203,6 +2400,8 @@ pub fn focus(&mut self, view_id: ViewId) {
             view.sync_changes(doc);
         }
 
+        // Zooming is temporary, the other splits are shown again when moving to them.
+        self.tree.set_zoom(false);
         let prev_id = std::mem::replace(&mut self.tree.focus, view_id);
         doc_mut!(self).mark_as_focused();
 
@@ -2277,6 +2476,82 @@ pub fn document_by_path_mut<P: AsRef<Path>>(&mut self, path: P) -> Option<&mut D
             .find(|doc| doc.path().is_some_and(|p| p == path.as_ref()))
     }
 
+    /// Compares the document `doc_id` with `other` side by side. The gutter and diff
+    /// commands of both documents then use the diff with the other document instead of the
+    /// version control base until [Editor::end_diff] is called. Any previous comparison of
+    /// either document ends.
+    pub fn diff_documents(&mut self, doc_id: DocumentId, other: DocumentId) {
+        self.end_diff(doc_id);
+        self.end_diff(other);
+        let handle = DiffHandle::new(
+            doc!(self, &other).text().clone(),
+            doc!(self, &doc_id).text().clone(),
+        );
+        let mut inverted = handle.clone();
+        inverted.invert();
+        doc_mut!(self, &doc_id).diff_mode = Some(DiffMode::new(other, handle));
+        doc_mut!(self, &other).diff_mode = Some(DiffMode::new(doc_id, inverted));
+    }
+
+    /// Ends the comparison of the document with another document for both documents.
+    pub fn end_diff(&mut self, doc_id: DocumentId) {
+        let Some(diff_mode) = self
+            .document_mut(doc_id)
+            .and_then(|doc| doc.diff_mode.take())
+        else {
+            return;
+        };
+        if let Some(partner) = self.document_mut(diff_mode.partner) {
+            partner.diff_mode = None;
+        }
+    }
+
+    /// Scrolls the views of the document compared with the focused document so that their
+    /// first line is aligned with the first line of the focused view.
+    pub fn sync_diff_scroll(&mut self) {
+        let view = self.tree.get(self.tree.focus);
+        let Some(doc) = self.documents.get(&view.doc) else {
+            return;
+        };
+        let Some(diff_mode) = doc.diff_mode() else {
+            return;
+        };
+        let view_offset = doc.view_offset(view.id);
+        let text = doc.text().slice(..);
+        let line = text.char_to_line(view_offset.anchor.min(text.len_chars()));
+        // The lines that only exist at the start of one document are aligned by the filler
+        // lines above the first line of the other, the vertical offset scrolls through them.
+        let partner_line = if line == 0 && view_offset.vertical_offset < top_diff_fillers(doc) {
+            view_offset.vertical_offset
+        } else {
+            diff_mode.handle.load().aligned_base_line(line as u32) as usize
+        };
+        let partner_id = diff_mode.partner;
+
+        let Some(partner) = self.documents.get_mut(&partner_id) else {
+            return;
+        };
+        let partner_text = partner.text().slice(..);
+        let anchor = partner_text.line_to_char(partner_line.min(partner_text.len_lines() - 1));
+        let vertical_offset = if partner_line == 0 {
+            line.min(top_diff_fillers(partner))
+        } else {
+            0
+        };
+        for (view, _) in self.tree.views() {
+            if view.doc == partner_id {
+                partner.set_view_offset(
+                    view.id,
+                    ViewPosition {
+                        anchor,
+                        horizontal_offset: view_offset.horizontal_offset,
+                        vertical_offset,
+                    },
+                );
+            }
+        }
+    }
+
     /// Returns all supported diagnostics for the document
     pub fn doc_diagnostics<'a>(
         language_servers: &'a helix_lsp::Registry,
@@ -2301,29 +2576,36 @@ pub fn doc_diagnostics_with_filter<'a>(
             .and_then(|uri| diagnostics.get(&uri))
             .map(|diags| {
                 diags.iter().filter_map(move |(diagnostic, provider)| {
-                    let server_id = provider.language_server_id()?;
-                    let ls = language_servers.get_by_id(server_id)?;
-                    language_config
-                        .as_ref()
-                        .and_then(|c| {
-                            c.language_servers.iter().find(|features| {
+                    let config = language_config.as_ref()?;
+                    let offset_encoding = match provider {
+                        DiagnosticProvider::Lsp { server_id, .. } => {
+                            let ls = language_servers.get_by_id(*server_id)?;
+                            config.language_servers.iter().find(|features| {
                                 features.name == ls.name()
                                     && features.has_feature(LanguageServerFeature::Diagnostics)
-                            })
-                        })
-                        .and_then(|_| {
-                            if filter(diagnostic, provider) {
-                                Document::lsp_diagnostic_to_diagnostic(
-                                    &text,
-                                    language_config.as_deref(),
-                                    diagnostic,
-                                    provider.clone(),
-                                    ls.offset_encoding(),
-                                )
-                            } else {
-                                None
-                            }
-                        })
+                            })?;
+                            ls.offset_encoding()
+                        }
+                        // Linter diagnostics are positioned in characters.
+                        DiagnosticProvider::Linter { name } => {
+                            config
+                                .linters
+                                .iter()
+                                .find(|linter| *linter.name == **name)?;
+                            OffsetEncoding::Utf32
+                        }
+                    };
+                    if filter(diagnostic, provider) {
+                        Document::lsp_diagnostic_to_diagnostic(
+                            &text,
+                            language_config.as_deref(),
+                            diagnostic,
+                            provider.clone(),
+                            offset_encoding,
+                        )
+                    } else {
+                        None
+                    }
                 })
             })
             .into_iter()
@@ -16,6 +16,10 @@
         changes: &'a ChangeSet,
         ghost_transaction: bool
     }
+    DocumentDidSave<'a> {
+        editor: &'a mut Editor,
+        doc: DocumentId
+    }
     DocumentDidClose<'a> {
         editor: &'a mut Editor,
         doc: Document
@@ -24,6 +28,8 @@
     DiagnosticsDidChange<'a> { editor: &'a mut Editor, doc: DocumentId }
     // called **after** a document loses focus (but not when its closed)
     DocumentFocusLost<'a> { editor: &'a mut Editor, doc: DocumentId }
+    // called after the language of an open document changed, when it was set or detected again
+    DocumentDidChangeLanguage<'a> { editor: &'a mut Editor, doc: DocumentId }
 
     LanguageServerInitialized<'a> {
         editor: &'a mut Editor,
@@ -114,10 +114,32 @@ pub fn from_name(s: &str) -> Option<Self> {
 /// Note that the lifetime of the expanded variable is only bound to the input token and not the
 /// `Editor`. See `expand_variable` below for more discussion of lifetimes.
 pub fn expand<'a>(editor: &Editor, token: Token<'a>) -> Result<Cow<'a, str>> {
+    expand_with_arguments(editor, token, None)
+}
+
+/// Expands the given command line token of a custom command called with `arguments`.
+///
+/// Besides the usual expansions, `%{arg}` expands to the first argument and `%{args}` to all
+/// arguments separated by spaces.
+pub fn expand_with_arguments<'a>(
+    editor: &Editor,
+    token: Token<'a>,
+    arguments: Option<&[String]>,
+) -> Result<Cow<'a, str>> {
     // Note: see the `TokenKind` documentation for more details on how each branch should expand.
     match token.kind {
         TokenKind::Unquoted | TokenKind::Quoted(_) => Ok(token.content),
         TokenKind::Expansion(ExpansionKind::Variable) => {
+            if let Some(arguments) = arguments {
+                match token.content.as_ref() {
+                    "arg" => {
+                        let arg = arguments.first().map_or("", String::as_str);
+                        return Ok(Cow::Owned(arg.to_owned()));
+                    }
+                    "args" => return Ok(Cow::Owned(arguments.join(" "))),
+                    _ => (),
+                }
+            }
             let var = Variable::from_name(&token.content)
                 .ok_or_else(|| anyhow!("unknown variable '{}'", token.content))?;
 
@@ -136,8 +158,10 @@ pub fn expand<'a>(editor: &Editor, token: Token<'a>) -> Result<Cow<'a, str>> {
                 ))
             }
         }
-        TokenKind::Expand => expand_inner(editor, token.content),
-        TokenKind::Expansion(ExpansionKind::Shell) => expand_shell(editor, token.content),
+        TokenKind::Expand => expand_inner(editor, token.content, arguments),
+        TokenKind::Expansion(ExpansionKind::Shell) => {
+            expand_shell_with_arguments(editor, token.content, arguments)
+        }
         TokenKind::Expansion(ExpansionKind::Register) => expand_register(editor, token.content),
         // Note: see the docs for this variant.
         TokenKind::ExpansionKind => unreachable!(
@@ -148,10 +172,18 @@ pub fn expand<'a>(editor: &Editor, token: Token<'a>) -> Result<Cow<'a, str>> {
 
 /// Expand a shell command.
 pub fn expand_shell<'a>(editor: &Editor, content: Cow<'a, str>) -> Result<Cow<'a, str>> {
+    expand_shell_with_arguments(editor, content, None)
+}
+
+fn expand_shell_with_arguments<'a>(
+    editor: &Editor,
+    content: Cow<'a, str>,
+    arguments: Option<&[String]>,
+) -> Result<Cow<'a, str>> {
     use std::process::{Command, Stdio};
 
     // Recursively expand the expansion's content before executing the shell command.
-    let content = expand_inner(editor, content)?;
+    let content = expand_inner(editor, content, arguments)?;
 
     let config = editor.config();
     let shell = &config.shell;
@@ -162,6 +194,9 @@ pub fn expand_shell<'a>(editor: &Editor, content: Cow<'a, str>) -> Result<Cow<'a
         .stdin(Stdio::null())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
+    if let Some(socket) = helix_loader::listening_remote_socket() {
+        process.env("HELIX_REMOTE_SOCKET", socket);
+    }
 
     // TODO: there is no protection here against a shell command taking a long time.
     // Ideally you should be able to hit `<ret>` in command mode and then be able to
@@ -211,7 +246,11 @@ pub fn expand_register<'a>(editor: &Editor, content: Cow<'a, str>) -> Result<Cow
 }
 
 /// Expand a token's contents recursively.
-fn expand_inner<'a>(editor: &Editor, content: Cow<'a, str>) -> Result<Cow<'a, str>> {
+fn expand_inner<'a>(
+    editor: &Editor,
+    content: Cow<'a, str>,
+    arguments: Option<&[String]>,
+) -> Result<Cow<'a, str>> {
     let mut escaped = String::new();
     let mut start = 0;
 
@@ -233,7 +272,7 @@ fn expand_inner<'a>(editor: &Editor, content: Cow<'a, str>) -> Result<Cow<'a, st
                 .unwrap()
                 .map_err(|err| anyhow!("{err}"))?;
             // expand it (this is the recursive part),
-            let expanded = expand(editor, token)?;
+            let expanded = expand_with_arguments(editor, token, arguments)?;
             escaped.push_str(expanded.as_ref());
             // and move forward to the end of the expansion.
             start = idx + tokenizer.pos();
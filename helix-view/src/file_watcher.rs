@@ -0,0 +1,418 @@
+//! Watches the files of open documents and the workspace for changes made
+//! outside of the editor.
+//!
+//! The directories containing open documents are watched rather than the
+//! files themselves so that files replaced by a rename (like atomic saves of
+//! other editors) keep being watched. The workspace can additionally be
+//! watched recursively so that language servers are notified about changes to
+//! files which are not open, like after a `git checkout`.
+//!
+//! Watching is currently only implemented with inotify on Linux. On other
+//! platforms the [`FileWatcher`] does nothing.
+
+use std::path::{Path, PathBuf};
+
+use tokio::sync::mpsc::Sender;
+
+use crate::editor::FileWatcherConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileEventKind {
+    Created,
+    Changed,
+    Deleted,
+}
+
+impl FileEventKind {
+    /// Combines two events for the same file into a single event, `self`
+    /// happened first.
+    pub fn merge(self, later: FileEventKind) -> FileEventKind {
+        use FileEventKind::*;
+        match (self, later) {
+            // A file that was deleted and created again was replaced, for
+            // example when it is saved atomically.
+            (Deleted, Created | Changed) => Changed,
+            (Created, Changed) => Created,
+            (_, later) => later,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEvent {
+    pub path: PathBuf,
+    pub kind: FileEventKind,
+}
+
+pub struct FileWatcher {
+    tx: Sender<FileEvent>,
+    workspace: Option<PathBuf>,
+    /// The last working directory and the workspace found for it.
+    #[cfg(target_os = "linux")]
+    cwd_workspace: Option<(PathBuf, PathBuf)>,
+    #[cfg(target_os = "linux")]
+    inotify: Option<inotify::Inotify>,
+}
+
+impl FileWatcher {
+    pub fn new(tx: Sender<FileEvent>) -> Self {
+        Self {
+            tx,
+            workspace: None,
+            #[cfg(target_os = "linux")]
+            cwd_workspace: None,
+            #[cfg(target_os = "linux")]
+            inotify: None,
+        }
+    }
+
+    /// Updates the watched directories to the parent directories of `documents`
+    /// and, if enabled in `config`, the workspace.
+    pub fn update<'a>(
+        &mut self,
+        config: &FileWatcherConfig,
+        documents: impl Iterator<Item = &'a Path>,
+    ) {
+        if !config.enable {
+            self.stop();
+            return;
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            if self.inotify.is_none() {
+                match inotify::Inotify::new(self.tx.clone()) {
+                    Ok(inotify) => self.inotify = Some(inotify),
+                    Err(err) => {
+                        log::error!("failed to start the file watcher: {err}");
+                        return;
+                    }
+                }
+            }
+            let workspace = config.watch_workspace.then(|| self.find_workspace());
+            let inotify = self.inotify.as_ref().unwrap();
+            if workspace != self.workspace {
+                inotify.unwatch_workspace();
+                if let Some(workspace) = &workspace {
+                    inotify.watch_workspace(workspace.clone());
+                }
+            }
+            inotify.watch_documents(documents.filter_map(Path::parent));
+            self.workspace = workspace;
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = documents;
+        }
+    }
+
+    /// Finds the workspace of the current working directory. The workspace is only searched
+    /// for again when the working directory changes.
+    #[cfg(target_os = "linux")]
+    fn find_workspace(&mut self) -> PathBuf {
+        let cwd = helix_stdx::env::current_working_dir();
+        match &self.cwd_workspace {
+            Some((last_cwd, workspace)) if *last_cwd == cwd => workspace.clone(),
+            _ => {
+                let (workspace, _) = helix_loader::find_workspace_in(&cwd);
+                self.cwd_workspace = Some((cwd, workspace.clone()));
+                workspace
+            }
+        }
+    }
+
+    /// Removes all watches.
+    pub fn stop(&mut self) {
+        self.workspace = None;
+        #[cfg(target_os = "linux")]
+        {
+            self.inotify = None;
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod inotify {
+    use std::{
+        collections::{HashMap, HashSet},
+        ffi::OsStr,
+        mem::MaybeUninit,
+        os::{fd::OwnedFd, unix::ffi::OsStrExt},
+        path::{Path, PathBuf},
+        sync::Arc,
+    };
+
+    use parking_lot::Mutex;
+    use rustix::{
+        fs::inotify::{self, ReadFlags, WatchFlags},
+        io::Errno,
+    };
+    use tokio::{io::unix::AsyncFd, sync::mpsc::Sender, task::JoinHandle};
+
+    use super::{FileEvent, FileEventKind};
+
+    const WATCH_FLAGS: WatchFlags = WatchFlags::CLOSE_WRITE
+        .union(WatchFlags::CREATE)
+        .union(WatchFlags::DELETE)
+        .union(WatchFlags::MOVED_FROM)
+        .union(WatchFlags::MOVED_TO)
+        .union(WatchFlags::ONLYDIR)
+        .union(WatchFlags::EXCL_UNLINK);
+
+    #[derive(Debug, Default)]
+    struct Watch {
+        wd: i32,
+        /// Whether the directory contains an open document.
+        documents: bool,
+        /// Whether the directory is part of the watched workspace.
+        workspace: bool,
+    }
+
+    struct State {
+        fd: Arc<AsyncFd<OwnedFd>>,
+        dirs: HashMap<PathBuf, Watch>,
+        wds: HashMap<i32, PathBuf>,
+        /// Bumped whenever the watched workspace changes so that a stale walk of
+        /// the previous workspace stops adding watches.
+        workspace_generation: usize,
+    }
+
+    impl State {
+        fn watch(&mut self, dir: &Path, documents: bool, workspace: bool) {
+            if let Some(watch) = self.dirs.get_mut(dir) {
+                watch.documents |= documents;
+                watch.workspace |= workspace;
+                return;
+            }
+            match inotify::add_watch(self.fd.get_ref(), dir, WATCH_FLAGS) {
+                Ok(wd) => {
+                    self.dirs.insert(
+                        dir.to_path_buf(),
+                        Watch {
+                            wd,
+                            documents,
+                            workspace,
+                        },
+                    );
+                    self.wds.insert(wd, dir.to_path_buf());
+                }
+                Err(err) => log::warn!("failed to watch {dir:?}: {err}"),
+            }
+        }
+
+        /// Removes the watches of directories which are no longer needed.
+        fn retain(&mut self) {
+            let fd = &self.fd;
+            let wds = &mut self.wds;
+            self.dirs.retain(|_, watch| {
+                if watch.documents || watch.workspace {
+                    return true;
+                }
+                wds.remove(&watch.wd);
+                let _ = inotify::remove_watch(fd.get_ref(), watch.wd);
+                false
+            });
+        }
+
+        /// Adds watches for `root` and all of its subdirectories that aren't ignored.
+        fn watch_recursive(state: &Mutex<State>, root: &Path, generation: usize) {
+            let walk = ignore::WalkBuilder::new(root)
+                .filter_entry(|entry| entry.file_name() != ".git")
+                .build();
+            for entry in walk.flatten() {
+                if !entry.file_type().is_some_and(|ft| ft.is_dir()) {
+                    continue;
+                }
+                let mut state = state.lock();
+                if state.workspace_generation != generation {
+                    return;
+                }
+                state.watch(entry.path(), false, true);
+            }
+        }
+    }
+
+    pub(super) struct Inotify {
+        state: Arc<Mutex<State>>,
+        task: JoinHandle<()>,
+    }
+
+    impl Inotify {
+        pub fn new(tx: Sender<FileEvent>) -> std::io::Result<Self> {
+            let fd = inotify::init(inotify::CreateFlags::CLOEXEC | inotify::CreateFlags::NONBLOCK)?;
+            let fd = Arc::new(AsyncFd::new(fd)?);
+            let state = Arc::new(Mutex::new(State {
+                fd: fd.clone(),
+                dirs: HashMap::new(),
+                wds: HashMap::new(),
+                workspace_generation: 0,
+            }));
+            let task = tokio::spawn(run(fd, state.clone(), tx));
+            Ok(Self { state, task })
+        }
+
+        pub fn watch_documents<'a>(&self, dirs: impl Iterator<Item = &'a Path>) {
+            let dirs: HashSet<_> = dirs.collect();
+            let mut state = self.state.lock();
+            for watch in state.dirs.values_mut() {
+                watch.documents = false;
+            }
+            for dir in dirs {
+                state.watch(dir, true, false);
+            }
+            state.retain();
+        }
+
+        pub fn watch_workspace(&self, root: PathBuf) {
+            let generation = self.state.lock().workspace_generation;
+            let state = self.state.clone();
+            tokio::task::spawn_blocking(move || State::watch_recursive(&state, &root, generation));
+        }
+
+        pub fn unwatch_workspace(&self) {
+            let mut state = self.state.lock();
+            state.workspace_generation += 1;
+            for watch in state.dirs.values_mut() {
+                watch.workspace = false;
+            }
+            state.retain();
+        }
+    }
+
+    impl Drop for Inotify {
+        fn drop(&mut self) {
+            self.task.abort();
+        }
+    }
+
+    async fn run(fd: Arc<AsyncFd<OwnedFd>>, state: Arc<Mutex<State>>, tx: Sender<FileEvent>) {
+        let mut buf = [MaybeUninit::uninit(); 4096];
+        loop {
+            let mut guard = match fd.readable().await {
+                Ok(guard) => guard,
+                Err(err) => {
+                    log::error!("file watcher stopped: {err}");
+                    return;
+                }
+            };
+
+            let mut events = Vec::new();
+            let mut new_dirs = Vec::new();
+            {
+                let mut state = state.lock();
+                let mut reader = inotify::Reader::new(fd.get_ref(), &mut buf);
+                loop {
+                    let event = match reader.next() {
+                        Ok(event) => event,
+                        Err(Errno::AGAIN) => break,
+                        Err(err) => {
+                            log::error!("failed to read file watcher events: {err}");
+                            break;
+                        }
+                    };
+                    let flags = event.events();
+                    if flags.contains(ReadFlags::QUEUE_OVERFLOW) {
+                        log::warn!("file watcher event queue overflowed, some changes were missed");
+                        continue;
+                    }
+                    if flags.contains(ReadFlags::IGNORED) {
+                        if let Some(dir) = state.wds.remove(&event.wd()) {
+                            state.dirs.remove(&dir);
+                        }
+                        continue;
+                    }
+                    let (Some(dir), Some(name)) = (state.wds.get(&event.wd()), event.file_name())
+                    else {
+                        continue;
+                    };
+                    let path = dir.join(OsStr::from_bytes(name.to_bytes()));
+
+                    if flags.contains(ReadFlags::ISDIR) {
+                        let in_workspace = state.dirs.get(dir).is_some_and(|watch| watch.workspace);
+                        if in_workspace && flags.intersects(ReadFlags::CREATE | ReadFlags::MOVED_TO)
+                        {
+                            new_dirs.push((path, state.workspace_generation));
+                        }
+                        continue;
+                    }
+
+                    let kind = if flags.intersects(ReadFlags::DELETE | ReadFlags::MOVED_FROM) {
+                        FileEventKind::Deleted
+                    } else if flags.contains(ReadFlags::CREATE) {
+                        FileEventKind::Created
+                    } else {
+                        FileEventKind::Changed
+                    };
+                    events.push(FileEvent { path, kind });
+                }
+            }
+            guard.clear_ready();
+
+            for (dir, generation) in new_dirs {
+                let state = state.clone();
+                tokio::task::spawn_blocking(move || {
+                    State::watch_recursive(&state, &dir, generation)
+                });
+            }
+            for event in events {
+                if tx.send(event).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod test {
+    use std::time::Duration;
+
+    use tokio::{sync::mpsc::channel, time::timeout};
+
+    use super::*;
+
+    #[test]
+    fn merge() {
+        use FileEventKind::*;
+        assert_eq!(Deleted.merge(Created), Changed);
+        assert_eq!(Created.merge(Changed), Created);
+        assert_eq!(Changed.merge(Deleted), Deleted);
+    }
+
+    #[tokio::test]
+    async fn watch_documents() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = helix_stdx::path::canonicalize(dir.path());
+        let path = dir.join("file.txt");
+        std::fs::write(&path, "foo").unwrap();
+
+        let (tx, mut rx) = channel(16);
+        let mut watcher = FileWatcher::new(tx);
+        let config = FileWatcherConfig {
+            enable: true,
+            watch_workspace: false,
+        };
+        watcher.update(&config, std::iter::once(path.as_path()));
+
+        std::fs::write(&path, "bar").unwrap();
+        let event = timeout(Duration::from_secs(5), rx.recv()).await.unwrap();
+        assert_eq!(
+            event,
+            Some(FileEvent {
+                path: path.clone(),
+                kind: FileEventKind::Changed
+            })
+        );
+
+        std::fs::remove_file(&path).unwrap();
+        let event = timeout(Duration::from_secs(5), rx.recv()).await.unwrap();
+        assert_eq!(
+            event,
+            Some(FileEvent {
+                path,
+                kind: FileEventKind::Deleted
+            })
+        );
+    }
+}
@@ -97,10 +97,21 @@ pub fn diff<'doc>(
     let added = theme.get("diff.plus.gutter");
     let deleted = theme.get("diff.minus.gutter");
     let modified = theme.get("diff.delta.gutter");
+    let staged_added = theme.get("diff.plus.gutter.staged");
+    let staged_deleted = theme.get("diff.minus.gutter.staged");
+    let staged_modified = theme.get("diff.delta.gutter.staged");
     if let Some(diff_handle) = doc.diff_handle() {
         let hunks = diff_handle.load();
         let mut hunk_i = 0;
         let mut hunk = hunks.nth_hunk(hunk_i);
+        // Changes which are not in the diff with the staged version of the file are staged.
+        let staged_hunks = doc
+            .diff_mode()
+            .is_none()
+            .then(|| doc.staged_diff_handle())
+            .flatten()
+            .map(|handle| handle.load());
+        let mut staged_hunk_i = 0;
         Box::new(
             move |line: usize, _selected: bool, first_visual_line: bool, out: &mut String| {
                 // truncating the line is fine here because we don't compute diffs
@@ -119,13 +130,30 @@ pub fn diff<'doc>(
                     return None;
                 }
 
+                let staged = staged_hunks.as_ref().is_some_and(|staged_hunks| {
+                    let mut unstaged = staged_hunks.nth_hunk(staged_hunk_i);
+                    while unstaged.after.end < line as u32
+                        || !unstaged.is_pure_removal() && line as u32 == unstaged.after.end
+                    {
+                        staged_hunk_i += 1;
+                        unstaged = staged_hunks.nth_hunk(staged_hunk_i);
+                    }
+                    unstaged.after.start > line as u32
+                });
+
                 let (icon, style) = if hunk.is_pure_insertion() {
-                    ("▍", added)
+                    if staged {
+                        ("┆", staged_added)
+                    } else {
+                        ("▍", added)
+                    }
                 } else if hunk.is_pure_removal() {
                     if !first_visual_line {
                         return None;
                     }
-                    ("▔", deleted)
+                    ("▔", if staged { staged_deleted } else { deleted })
+                } else if staged {
+                    ("┆", staged_modified)
                 } else {
                     ("▍", modified)
                 };
@@ -2,6 +2,7 @@
 use helix_event::send_blocking;
 use tokio::sync::mpsc::Sender;
 
+use crate::file_watcher::FileEvent;
 use crate::handlers::lsp::SignatureHelpInvoked;
 use crate::{DocumentId, Editor, ViewId};
 
@@ -11,6 +12,12 @@
 pub mod lsp;
 pub mod word_index;
 
+/// Runs the linters of a document that run while it is edited.
+#[derive(Debug)]
+pub struct LintEvent {
+    pub document_id: DocumentId,
+}
+
 #[derive(Debug)]
 pub enum AutoSaveEvent {
     DocumentChanged { save_after: u64 },
@@ -23,10 +30,14 @@ pub struct Handlers {
     pub signature_hints: Sender<lsp::SignatureHelpEvent>,
     pub auto_save: Sender<AutoSaveEvent>,
     pub document_colors: Sender<lsp::DocumentColorsEvent>,
+    pub semantic_tokens: Sender<lsp::SemanticTokensEvent>,
     pub document_links: Sender<lsp::DocumentLinksEvent>,
+    pub code_lens: Sender<lsp::CodeLensEvent>,
     pub word_index: word_index::Handler,
     pub pull_diagnostics: Sender<lsp::PullDiagnosticsEvent>,
     pub pull_all_documents_diagnostics: Sender<lsp::PullAllDocumentsDiagnosticsEvent>,
+    pub file_watcher: Sender<FileEvent>,
+    pub linters: Sender<LintEvent>,
 }
 
 impl Handlers {
@@ -1,9 +1,10 @@
-use crate::editor::{Action, Breakpoint};
-use crate::{align_view, Align, Editor};
+use crate::document::Mode;
+use crate::editor::{Action, Breakpoint, DataBreakpoint, FunctionBreakpoint, WatchExpression};
+use crate::{align_view, Align, Document, DocumentId, Editor};
 use anyhow::bail;
 use dap::requests::DisconnectArguments;
 use dap::requests::ThreadsArguments;
-use helix_core::Selection;
+use helix_core::{line_ending::get_line_ending_of_str, LineEnding, Rope, Selection, Transaction};
 use helix_dap::{
     self as dap, registry::DebugAdapterId, Client, ConnectionType, Payload, Request, ThreadId,
 };
@@ -13,6 +14,10 @@
 use std::fmt::Write;
 use std::path::PathBuf;
 
+/// The bytes of debugger output that are buffered at most while the debug console isn't
+/// displayed. The oldest lines are dropped first.
+const MAX_DEBUG_CONSOLE_OUTPUT: usize = 1024 * 1024;
+
 #[macro_export]
 macro_rules! debugger {
     ($editor:expr) => {{
@@ -143,7 +148,175 @@ pub fn breakpoints_changed(
     Ok(())
 }
 
+pub fn function_breakpoints_changed(
+    debugger: &mut dap::Client,
+    breakpoints: &mut [FunctionBreakpoint],
+) -> Result<(), anyhow::Error> {
+    if !debugger
+        .caps
+        .as_ref()
+        .and_then(|caps| caps.supports_function_breakpoints)
+        .unwrap_or_default()
+    {
+        bail!("Debugger does not support function breakpoints")
+    }
+    let function_breakpoints = breakpoints
+        .iter()
+        .map(|breakpoint| helix_dap::FunctionBreakpoint {
+            name: breakpoint.name.clone(),
+            ..Default::default()
+        })
+        .collect();
+
+    let request = debugger.set_function_breakpoints(function_breakpoints);
+    match block_on(request) {
+        Ok(dap_breakpoints) => {
+            for (breakpoint, dap_breakpoint) in breakpoints.iter_mut().zip(dap_breakpoints) {
+                breakpoint.id = dap_breakpoint.id;
+                breakpoint.verified = dap_breakpoint.verified;
+                breakpoint.message = dap_breakpoint.message;
+            }
+        }
+        Err(e) => anyhow::bail!("Failed to set function breakpoints: {}", e),
+    };
+    Ok(())
+}
+
+pub fn data_breakpoints_changed(
+    debugger: &mut dap::Client,
+    breakpoints: &mut [DataBreakpoint],
+) -> Result<(), anyhow::Error> {
+    if !debugger
+        .caps
+        .as_ref()
+        .and_then(|caps| caps.supports_data_breakpoints)
+        .unwrap_or_default()
+    {
+        bail!("Debugger does not support data breakpoints")
+    }
+    let data_breakpoints = breakpoints
+        .iter()
+        .map(|breakpoint| helix_dap::DataBreakpoint {
+            data_id: breakpoint.data_id.clone(),
+            access_type: None,
+            condition: None,
+            hit_condition: None,
+        })
+        .collect();
+
+    let request = debugger.set_data_breakpoints(data_breakpoints);
+    match block_on(request) {
+        Ok(dap_breakpoints) => {
+            for (breakpoint, dap_breakpoint) in breakpoints.iter_mut().zip(dap_breakpoints) {
+                breakpoint.id = dap_breakpoint.id;
+                breakpoint.verified = dap_breakpoint.verified;
+                breakpoint.message = dap_breakpoint.message;
+            }
+        }
+        Err(e) => anyhow::bail!("Failed to set data breakpoints: {}", e),
+    };
+    Ok(())
+}
+
+/// Evaluates the watch expressions in the current stack frame of `debugger`.
+pub async fn evaluate_watch_expressions(
+    debugger: &dap::Client,
+    watch_expressions: &mut [WatchExpression],
+) {
+    let frame_id = debugger.current_stack_frame().map(|frame| frame.id);
+    for watch in watch_expressions {
+        let response = debugger
+            .eval(watch.expression.clone(), frame_id, Some("watch"))
+            .await;
+        watch.value = Some(
+            response
+                .map(|response| response.result)
+                .map_err(|err| err.to_string()),
+        );
+    }
+}
+
 impl Editor {
+    /// Returns the scratch buffer used as a debug console, creating it from the
+    /// buffered debugger output if it doesn't exist. The buffer is not opened in
+    /// a view.
+    pub fn debug_console(&mut self) -> DocumentId {
+        if let Some(doc_id) = self
+            .debug_console
+            .filter(|doc_id| self.documents.contains_key(doc_id))
+        {
+            return doc_id;
+        }
+
+        let doc = if self.debug_console_output.is_empty() {
+            Document::default(self.config.clone(), self.syn_loader.clone())
+        } else {
+            let text = Rope::from(std::mem::take(&mut self.debug_console_output));
+            Document::from(text, None, self.config.clone(), self.syn_loader.clone())
+        };
+        let doc_id = self.new_document(doc);
+        self.debug_console = Some(doc_id);
+        doc_id
+    }
+
+    /// Appends `output` to the debug console. The output is buffered while the
+    /// console isn't displayed in a view.
+    pub fn append_to_debug_console(&mut self, output: &str) {
+        if output.is_empty() {
+            return;
+        }
+
+        self.debug_console_output.push_str(output);
+        if get_line_ending_of_str(output).is_none() {
+            let line_ending: LineEnding = self.config().default_line_ending.into();
+            self.debug_console_output.push_str(line_ending.as_str());
+        }
+        let len = self.debug_console_output.len();
+        if len > MAX_DEBUG_CONSOLE_OUTPUT {
+            let excess = len - MAX_DEBUG_CONSOLE_OUTPUT;
+            let end = self.debug_console_output.as_bytes()[excess..]
+                .iter()
+                .position(|&byte| byte == b'\n')
+                .map_or(len, |i| excess + i + 1);
+            self.debug_console_output.drain(..end);
+        }
+        self.flush_debug_console();
+    }
+
+    /// Adds the buffered debugger output to the debug console if it is displayed
+    /// in a view. The output is inserted above the last line so that it doesn't
+    /// interfere with an expression that is being typed. The output is its own
+    /// revision in the history, unless an expression is being typed in insert mode:
+    /// the output is then committed together with the expression.
+    pub fn flush_debug_console(&mut self) {
+        if self.debug_console_output.is_empty() {
+            return;
+        }
+        let Some(doc_id) = self.debug_console else {
+            return;
+        };
+        let Some(view_id) = self
+            .tree
+            .views()
+            .find(|(view, _)| view.doc == doc_id)
+            .map(|(view, _)| view.id)
+        else {
+            return;
+        };
+
+        let output = std::mem::take(&mut self.debug_console_output);
+        let doc = doc_mut!(self, &doc_id);
+        let text = doc.text();
+        let pos = text.line_to_char(text.len_lines() - 1);
+        let transaction =
+            Transaction::change(text, std::iter::once((pos, pos, Some(output.into()))));
+        doc.apply(&transaction, view_id);
+        let typing = self.mode == Mode::Insert && self.tree.get(self.tree.focus).doc == doc_id;
+        if !typing {
+            doc.append_changes_to_history(self.tree.get_mut(view_id));
+        }
+    }
+
     pub async fn handle_debugger_message(
         &mut self,
         id: DebugAdapterId,
@@ -198,6 +371,10 @@ pub async fn handle_debugger_message(
                             select_thread_id(self, thread_id, false).await;
                         }
 
+                        if let Some(debugger) = self.debug_adapters.get_client_mut(id) {
+                            evaluate_watch_expressions(debugger, &mut self.watch_expressions).await;
+                        }
+
                         let scope = match thread_id {
                             Some(id) => format!("Thread {}", id),
                             None => "Target".to_owned(),
@@ -294,6 +471,23 @@ pub async fn handle_debugger_message(
                                             breakpoint.column.or(breakpoints[i].column);
                                     }
                                 }
+                                let function_breakpoints = self
+                                    .function_breakpoints
+                                    .iter_mut()
+                                    .map(|b| (b.id, &mut b.verified, &mut b.message));
+                                let data_breakpoints = self
+                                    .data_breakpoints
+                                    .iter_mut()
+                                    .map(|b| (b.id, &mut b.verified, &mut b.message));
+                                for (_, verified, message) in function_breakpoints
+                                    .chain(data_breakpoints)
+                                    .filter(|(b_id, ..)| *b_id == Some(id))
+                                {
+                                    *verified = breakpoint.verified;
+                                    if breakpoint.message.is_some() {
+                                        message.clone_from(&breakpoint.message);
+                                    }
+                                }
                                 if let Some(message) = &breakpoint.message {
                                     self.set_status(format!("Breakpoint: {}", message));
                                 }
@@ -319,18 +513,16 @@ pub async fn handle_debugger_message(
                     Event::Output(events::OutputBody {
                         category, output, ..
                     }) => {
-                        let prefix = match category {
-                            Some(category) => {
-                                if &category == "telemetry" {
-                                    return false;
-                                }
-                                format!("Debug ({}):", category)
+                        match category.as_deref() {
+                            Some("telemetry") => return false,
+                            Some("important") => {
+                                self.set_status(format!("Debug: {}", output.trim_end()))
                             }
-                            None => "Debug:".to_owned(),
-                        };
+                            _ => (),
+                        }
 
                         log::info!("{}", output);
-                        self.set_status(format!("{} {}", prefix, output));
+                        self.append_to_debug_console(&output);
                     }
                     Event::ProgressStart(body) => {
                         let status = {
@@ -384,6 +576,21 @@ pub async fn handle_debugger_message(
                             // TODO: call futures in parallel, await all
                             let _ = breakpoints_changed(debugger, path.clone(), breakpoints);
                         }
+                        if !self.function_breakpoints.is_empty() {
+                            if let Err(err) = function_breakpoints_changed(
+                                debugger,
+                                &mut self.function_breakpoints,
+                            ) {
+                                warn!("{err}");
+                            }
+                        }
+                        if !self.data_breakpoints.is_empty() {
+                            if let Err(err) =
+                                data_breakpoints_changed(debugger, &mut self.data_breakpoints)
+                            {
+                                warn!("{err}");
+                            }
+                        }
                         // TODO: fetch breakpoints (in case we're attaching)
 
                         if let Err(err) = debugger.configuration_done().await {
@@ -439,6 +646,15 @@ pub async fn handle_debugger_message(
                                         breakpoint.verified = false;
                                     }
                                 }
+                                for breakpoint in &mut self.function_breakpoints {
+                                    breakpoint.verified = false;
+                                }
+                                // data ids are only valid within a session unless the
+                                // debugger says otherwise
+                                self.data_breakpoints.retain(|b| b.can_persist);
+                                for breakpoint in &mut self.data_breakpoints {
+                                    breakpoint.verified = false;
+                                }
                             }
                             Some(val) => {
                                 log::info!("Attempting to restart debug session.");
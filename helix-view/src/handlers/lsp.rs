@@ -16,7 +16,9 @@
 use super::Handlers;
 
 pub struct DocumentColorsEvent(pub DocumentId);
+pub struct SemanticTokensEvent(pub DocumentId);
 pub struct DocumentLinksEvent(pub DocumentId);
+pub struct CodeLensEvent(pub DocumentId);
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum SignatureHelpInvoked {
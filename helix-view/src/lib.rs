@@ -7,6 +7,7 @@
 pub mod editor;
 pub mod events;
 pub mod expansion;
+pub mod file_watcher;
 pub mod graphics;
 pub mod gutter;
 pub mod handlers;
@@ -14,6 +15,7 @@
 pub mod input;
 pub mod keyboard;
 pub mod register;
+pub mod session;
 pub mod theme;
 pub mod tree;
 pub mod view;
@@ -69,11 +71,10 @@ pub fn align_view(doc: &mut Document, view: &View, align: Align) {
     };
 
     let text_fmt = doc.text_format(viewport.width, None);
-    (view_offset.anchor, view_offset.vertical_offset) = char_idx_at_visual_offset(
+    (view_offset.anchor, view_offset.vertical_offset) = view_offset_at_visual_offset(
         doc_text,
         cursor,
         -(relative as isize),
-        0,
         &text_fmt,
         &view.text_annotations(doc, None),
     );
@@ -82,6 +83,6 @@ pub fn align_view(doc: &mut Document, view: &View, align: Align) {
 
 pub use document::Document;
 pub use editor::Editor;
-use helix_core::char_idx_at_visual_offset;
+use helix_core::view_offset_at_visual_offset;
 pub use theme::Theme;
 pub use view::View;
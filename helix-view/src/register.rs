@@ -1,8 +1,14 @@
-use std::{borrow::Cow, collections::HashMap, iter};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fs, io, iter,
+    path::{Path, PathBuf},
+};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use arc_swap::access::DynAccess;
 use helix_core::NATIVE_LINE_ENDING;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     clipboard::{ClipboardError, ClipboardProvider, ClipboardType},
@@ -27,6 +33,10 @@ pub struct Registers {
     /// The order is reversed again in `Registers::read`. This allows us to
     /// efficiently prepend new values in `Registers::push`.
     inner: HashMap<char, Vec<String>>,
+    /// The values of the registers loaded from the history file with
+    /// `Registers::load_persisted`, used to find the changes made by this instance
+    /// when saving.
+    persisted: HashMap<char, Vec<String>>,
     clipboard_provider: Box<dyn DynAccess<ClipboardProvider>>,
     pub last_search_register: char,
 }
@@ -35,6 +45,7 @@ impl Registers {
     pub fn new(clipboard_provider: Box<dyn DynAccess<ClipboardProvider>>) -> Self {
         Self {
             inner: Default::default(),
+            persisted: Default::default(),
             clipboard_provider,
             last_search_register: '/',
         }
@@ -217,6 +228,132 @@ fn clear_clipboard(&mut self, clipboard_type: ClipboardType) {
     pub fn clipboard_provider_name(&self) -> String {
         self.clipboard_provider.load().name().into_owned()
     }
+
+    /// Loads the registers `names` from the history file at `path`. A missing file is
+    /// not an error.
+    pub fn load_persisted(&mut self, path: &Path, names: &[char]) -> Result<()> {
+        let Some(history) = PersistedRegisters::read(path)? else {
+            return Ok(());
+        };
+        for (name, values) in history.registers {
+            if is_persistable(name) && names.contains(&name) {
+                self.persisted.insert(name, values.clone());
+                self.inner.insert(name, values);
+            }
+        }
+        Ok(())
+    }
+
+    /// Saves the registers `names` to the history file at `path`.
+    ///
+    /// Other instances may have saved the file since it was loaded so the registers
+    /// changed by this instance are merged into the file: new history entries are
+    /// appended to the histories in the file and other registers are replaced. Registers
+    /// that weren't changed keep the value in the file.
+    pub fn save_persisted(&self, path: &Path, names: &[char], max_entries: usize) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let _lock = FileLock::acquire(path.with_extension("lock"))?;
+        let mut history = PersistedRegisters::read(path)?.unwrap_or_default();
+
+        for &name in names.iter().filter(|&&name| is_persistable(name)) {
+            let current = self.inner.get(&name).map_or(&[][..], Vec::as_slice);
+            let loaded = self.persisted.get(&name).map_or(&[][..], Vec::as_slice);
+            let saved = history.registers.remove(&name).unwrap_or_default();
+            let merged = merge_persisted(name, saved, loaded, current, max_entries);
+            if !merged.is_empty() {
+                history.registers.insert(name, merged);
+            }
+        }
+
+        let parent = path.parent().unwrap_or(Path::new("."));
+        let mut file = tempfile::NamedTempFile::new_in(parent)?;
+        serde_json::to_writer(io::BufWriter::new(&mut file), &history)?;
+        file.persist(path)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(())
+    }
+}
+
+/// The registers in the history file. The values are stored in the same order as in
+/// `Registers::inner`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PersistedRegisters {
+    registers: HashMap<char, Vec<String>>,
+}
+
+impl PersistedRegisters {
+    fn read(path: &Path) -> Result<Option<Self>> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to read {}", path.display()))
+            }
+        };
+        serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse {}", path.display()))
+            .map(Some)
+    }
+}
+
+/// Special registers are computed or backed by the clipboard and are never saved.
+fn is_persistable(name: char) -> bool {
+    !matches!(name, '_' | '#' | '.' | '%' | '*' | '+')
+}
+
+/// Registers that prompts push their history to.
+fn is_history(name: char) -> bool {
+    matches!(name, ':' | '/' | '|')
+}
+
+/// Merges the `current` values of a register into the values `saved` in the history
+/// file, where `loaded` are the values the register had when the file was loaded.
+fn merge_persisted(
+    name: char,
+    saved: Vec<String>,
+    loaded: &[String],
+    current: &[String],
+    max_entries: usize,
+) -> Vec<String> {
+    if current == loaded {
+        return saved;
+    }
+    if !is_history(name) {
+        return current.to_vec();
+    }
+    let new = current.strip_prefix(loaded).unwrap_or(current);
+    let mut merged: Vec<_> = saved
+        .into_iter()
+        .filter(|value| !new.contains(value))
+        .chain(new.iter().cloned())
+        .collect();
+    merged.drain(..merged.len().saturating_sub(max_entries));
+    merged
+}
+
+/// A lock on a lock file held while the history file is merged so that instances exiting
+/// at the same time don't lose each other's changes. The lock is an advisory lock of the
+/// operating system so it is released when the instance holding it exits or crashes, the
+/// lock file itself is left in place.
+struct FileLock {
+    _file: fs::File,
+}
+
+impl FileLock {
+    /// Waits until no other instance holds the lock.
+    fn acquire(path: PathBuf) -> Result<Self> {
+        let file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        file.lock()
+            .with_context(|| format!("failed to lock {}", path.display()))?;
+        Ok(Self { _file: file })
+    }
 }
 
 fn read_from_clipboard<'a>(
@@ -333,3 +470,72 @@ fn len(&self) -> usize {
 trait DoubleEndedExactSizeIterator: DoubleEndedIterator + ExactSizeIterator {}
 
 impl<I: DoubleEndedIterator + ExactSizeIterator> DoubleEndedExactSizeIterator for I {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn values(values: &[&str]) -> Vec<String> {
+        values.iter().map(|&value| value.to_owned()).collect()
+    }
+
+    #[test]
+    fn merge_history() {
+        // Another instance added `c` after this one loaded `a b`, this one added `d a`.
+        let merged = merge_persisted(
+            ':',
+            values(&["a", "b", "c"]),
+            &values(&["a", "b"]),
+            &values(&["a", "b", "d", "a"]),
+            100,
+        );
+        assert_eq!(merged, values(&["b", "c", "d", "a"]));
+
+        let merged = merge_persisted(
+            '/',
+            values(&["a", "b", "c"]),
+            &values(&["a"]),
+            &values(&["a", "d"]),
+            2,
+        );
+        assert_eq!(merged, values(&["c", "d"]));
+
+        // Unchanged registers keep the value saved by other instances.
+        let merged = merge_persisted(':', values(&["a", "b"]), &[], &[], 100);
+        assert_eq!(merged, values(&["a", "b"]));
+    }
+
+    #[test]
+    fn merge_register() {
+        let merged = merge_persisted('a', values(&["x"]), &values(&["y"]), &values(&["z"]), 100);
+        assert_eq!(merged, values(&["z"]));
+        let merged = merge_persisted('a', values(&["x"]), &values(&["y"]), &values(&["y"]), 100);
+        assert_eq!(merged, values(&["x"]));
+        let merged = merge_persisted('@', values(&["x"]), &values(&["y"]), &[], 100);
+        assert!(merged.is_empty());
+    }
+
+    #[test]
+    fn persisted_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        let mut history = PersistedRegisters::default();
+        history.registers.insert(':', values(&["write", "quit"]));
+        fs::write(&path, serde_json::to_string(&history).unwrap()).unwrap();
+
+        let history = PersistedRegisters::read(&path).unwrap().unwrap();
+        assert_eq!(history.registers[&':'], values(&["write", "quit"]));
+        assert!(PersistedRegisters::read(&dir.path().join("missing"))
+            .unwrap()
+            .is_none());
+
+        let lock_path = path.with_extension("lock");
+        let lock = FileLock::acquire(lock_path.clone()).unwrap();
+        let other = fs::File::open(&lock_path).unwrap();
+        assert!(other.try_lock().is_err());
+        drop(lock);
+        assert!(other.try_lock().is_ok());
+        other.unlock().unwrap();
+        drop(FileLock::acquire(lock_path).unwrap());
+    }
+}
@@ -0,0 +1,351 @@
+//! Saving and restoring the split layout, the documents of the views and their positions.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use helix_core::{Range, RopeSlice, Selection};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    editor::Action,
+    tree::{Layout, SplitLayout},
+    view::{JumpList, ViewPosition},
+    DocumentId, Editor, ViewId,
+};
+
+/// A snapshot of the layout of the editor that can be restored with
+/// [Editor::restore_session].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Session {
+    /// The working directory.
+    pub cwd: PathBuf,
+    pub layout: SessionNode,
+    /// The index of the focused view in the order the views appear in `layout`.
+    pub focus: usize,
+}
+
+/// A node of the split tree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", tag = "type")]
+pub enum SessionNode {
+    Split {
+        layout: Layout,
+        /// The sizes of the children relative to each other, empty for equal sizes.
+        #[serde(default)]
+        weights: Vec<u32>,
+        children: Vec<SessionNode>,
+    },
+    View(SessionView),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SessionView {
+    /// The path of the document of the view, `None` for scratch buffers.
+    pub path: Option<PathBuf>,
+    pub selection: SessionSelection,
+    pub view_position: ViewPosition,
+    /// The jumplist of the view. Jumps to scratch buffers are not saved.
+    pub jumps: Vec<SessionJump>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SessionJump {
+    pub path: PathBuf,
+    pub selection: SessionSelection,
+}
+
+/// A selection as `(anchor, head)` char indices.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SessionSelection {
+    pub ranges: Vec<(usize, usize)>,
+    pub primary: usize,
+}
+
+impl SessionSelection {
+    pub fn new(selection: &Selection) -> Self {
+        SessionSelection {
+            ranges: selection
+                .ranges()
+                .iter()
+                .map(|range| (range.anchor, range.head))
+                .collect(),
+            primary: selection.primary_index(),
+        }
+    }
+
+    /// Converts the selection back, clamping it to `text` in case the file changed since
+    /// the session was saved.
+    pub fn to_selection(&self, text: RopeSlice) -> Selection {
+        let len = text.len_chars();
+        let ranges = self
+            .ranges
+            .iter()
+            .map(|&(anchor, head)| Range::new(anchor.min(len), head.min(len)))
+            .collect();
+        if self.ranges.is_empty() {
+            return Selection::point(0);
+        }
+        let primary = self.primary.min(self.ranges.len() - 1);
+        Selection::new(ranges, primary).ensure_invariants(text)
+    }
+}
+
+impl Session {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read session {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse session {}", path.display()))
+    }
+
+    /// Saves the session to `path`. The file is replaced atomically so that a session is
+    /// never left half written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let parent = path.parent().unwrap_or(Path::new("."));
+        std::fs::create_dir_all(parent)?;
+        let mut file = tempfile::NamedTempFile::new_in(parent)?;
+        serde_json::to_writer_pretty(std::io::BufWriter::new(&mut file), self)?;
+        file.persist(path)
+            .with_context(|| format!("failed to write session {}", path.display()))?;
+        Ok(())
+    }
+}
+
+/// The file the session of the current workspace is saved to by default.
+pub fn workspace_session_file() -> PathBuf {
+    let workspace = helix_loader::find_workspace().0;
+    helix_loader::session_dir().join(format!("{}.json", helix_loader::path_hash(&workspace)))
+}
+
+impl Editor {
+    /// Captures the current layout as a [Session].
+    pub fn session(&self) -> Session {
+        let mut views = Vec::new();
+        let layout = self.session_node(&self.tree.split_layout(), &mut views);
+        Session {
+            cwd: helix_stdx::env::current_working_dir(),
+            layout,
+            focus: views
+                .iter()
+                .position(|&view_id| view_id == self.tree.focus)
+                .unwrap_or(0),
+        }
+    }
+
+    fn session_node(&self, layout: &SplitLayout, views: &mut Vec<ViewId>) -> SessionNode {
+        match layout {
+            SplitLayout::Container(layout, weights, children) => SessionNode::Split {
+                layout: *layout,
+                weights: weights.clone(),
+                children: children
+                    .iter()
+                    .map(|child| self.session_node(child, views))
+                    .collect(),
+            },
+            &SplitLayout::View(view_id) => {
+                views.push(view_id);
+                let view = self.tree.get(view_id);
+                let doc = &self.documents[&view.doc];
+                let jumps = view
+                    .jumps
+                    .iter()
+                    .filter_map(|(doc_id, selection)| {
+                        Some(SessionJump {
+                            path: self.document(*doc_id)?.path()?.to_path_buf(),
+                            selection: SessionSelection::new(selection),
+                        })
+                    })
+                    .collect();
+                SessionNode::View(SessionView {
+                    path: doc.path().map(Path::to_path_buf),
+                    selection: SessionSelection::new(doc.selection(view_id)),
+                    view_position: doc.view_offset(view_id),
+                    jumps,
+                })
+            }
+        }
+    }
+
+    /// Replaces the views with the views of `session` and changes to its working
+    /// directory. Documents that are already open stay open.
+    ///
+    /// Files that can't be opened are replaced with scratch buffers and reported in the
+    /// returned error after the rest of the session was restored.
+    pub fn restore_session(&mut self, session: &Session) -> Result<()> {
+        if let Err(err) = self.set_cwd(&session.cwd) {
+            log::warn!(
+                "failed to restore working directory {}: {err}",
+                session.cwd.display()
+            );
+        }
+
+        let focus = self.tree.focus;
+        let views: Vec<_> = self
+            .tree
+            .views()
+            .map(|(view, _)| view.id)
+            .filter(|&view_id| view_id != focus)
+            .collect();
+        for view_id in views {
+            self.close(view_id);
+        }
+
+        let mut views = Vec::new();
+        let mut failed = Vec::new();
+        self.restore_session_node(&session.layout, &mut views, &mut failed);
+        if let Some(&view_id) = views.get(session.focus) {
+            self.focus(view_id);
+        }
+
+        if !failed.is_empty() {
+            bail!("failed to open {}", failed.join(", "));
+        }
+        Ok(())
+    }
+
+    /// Restores `node` into the focused view, splitting it as needed.
+    fn restore_session_node(
+        &mut self,
+        node: &SessionNode,
+        views: &mut Vec<ViewId>,
+        failed: &mut Vec<String>,
+    ) {
+        match node {
+            SessionNode::Split {
+                layout,
+                weights,
+                children,
+            } => {
+                let action = match layout {
+                    Layout::Horizontal => Action::HorizontalSplit,
+                    Layout::Vertical => Action::VerticalSplit,
+                };
+                // Create a view for each child first so that they end up in the same
+                // container, then restore the children into them.
+                let mut placeholders = vec![self.tree.focus];
+                for _ in 1..children.len() {
+                    let doc_id = view!(self).doc;
+                    self.switch(doc_id, action);
+                    placeholders.push(self.tree.focus);
+                }
+                // Splitting the children later replaces them in place, keeping their weight.
+                self.tree.set_split_weights(self.tree.focus, weights);
+                for (child, view_id) in children.iter().zip(placeholders) {
+                    self.focus(view_id);
+                    self.restore_session_node(child, views, failed);
+                }
+            }
+            SessionNode::View(session_view) => {
+                let view_id = self.tree.focus;
+                views.push(view_id);
+                self.restore_session_view(view_id, session_view, failed);
+            }
+        }
+    }
+
+    fn restore_session_view(
+        &mut self,
+        view_id: ViewId,
+        session_view: &SessionView,
+        failed: &mut Vec<String>,
+    ) {
+        let doc_id = match &session_view.path {
+            Some(path) => match self.open(path, Action::Replace) {
+                Ok(doc_id) => doc_id,
+                Err(err) => {
+                    log::error!("failed to open {} from session: {err}", path.display());
+                    failed.push(path.display().to_string());
+                    self.new_file(Action::Replace)
+                }
+            },
+            None => self.new_file(Action::Replace),
+        };
+
+        let doc = doc_mut!(self, &doc_id);
+        let text = doc.text().slice(..);
+        let selection = session_view.selection.to_selection(text);
+        let view_position = ViewPosition {
+            anchor: session_view.view_position.anchor.min(text.len_chars()),
+            ..session_view.view_position
+        };
+        doc.set_selection(view_id, selection.clone());
+        doc.set_view_offset(view_id, view_position);
+
+        let mut jumps = session_view.jumps.iter().filter_map(|jump| {
+            let doc_id = self.restore_jump_document(&jump.path)?;
+            let text = self.documents[&doc_id].text().slice(..);
+            Some((doc_id, jump.selection.to_selection(text)))
+        });
+        let mut jumplist = JumpList::new(jumps.next().unwrap_or((doc_id, selection)));
+        for jump in jumps {
+            jumplist.push(jump);
+        }
+        self.tree.get_mut(view_id).jumps = jumplist;
+    }
+
+    fn restore_jump_document(&mut self, path: &Path) -> Option<DocumentId> {
+        if let Some(doc_id) = self.document_id_by_path(path) {
+            return Some(doc_id);
+        }
+        // Don't create new files for jumps to files that were deleted.
+        if !path.exists() {
+            return None;
+        }
+        self.open(path, Action::Load).ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use helix_core::Rope;
+
+    #[test]
+    fn selection_is_clamped() {
+        let text = Rope::from("abc\n");
+        let selection = SessionSelection {
+            ranges: vec![(1, 2), (10, 20)],
+            primary: 5,
+        };
+        let selection = selection.to_selection(text.slice(..));
+        assert_eq!(
+            selection.ranges(),
+            [Range::new(1, 2), Range::new(4, 4)].as_slice()
+        );
+        assert_eq!(selection.primary_index(), 1);
+
+        let selection = SessionSelection {
+            ranges: Vec::new(),
+            primary: 0,
+        };
+        assert_eq!(selection.to_selection(text.slice(..)), Selection::point(0));
+    }
+
+    #[test]
+    fn save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sessions").join("session.json");
+        let session = Session {
+            cwd: dir.path().to_path_buf(),
+            layout: SessionNode::View(SessionView {
+                path: None,
+                selection: SessionSelection {
+                    ranges: vec![(0, 1)],
+                    primary: 0,
+                },
+                view_position: ViewPosition::default(),
+                jumps: Vec::new(),
+            }),
+            focus: 0,
+        };
+        session.save(&path).unwrap();
+        assert_eq!(Session::load(&path).unwrap(), session);
+        // Saving replaces the previous session.
+        session.save(&path).unwrap();
+        assert_eq!(Session::load(&path).unwrap(), session);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::{graphics::Rect, View, ViewId};
+use serde::{Deserialize, Serialize};
 use slotmap::SlotMap;
 
 // the dimensions are recomputed on window resize/tree change.
@@ -8,7 +9,8 @@ pub struct Tree {
     root: ViewId,
     // (container, index inside the container)
     pub focus: ViewId,
-    // fullscreen: bool,
+    /// A view which temporarily takes up the whole area, see [Tree::set_zoom].
+    zoomed: Option<ViewId>,
     area: Rect,
 
     nodes: SlotMap<ViewId, Node>,
@@ -45,13 +47,22 @@ pub fn view(view: View) -> Self {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Layout {
     Horizontal,
     Vertical,
     // could explore stacked/tabbed
 }
 
+/// The structure of the splits of a [Tree], see [Tree::split_layout].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SplitLayout {
+    /// A container with its layout, the weights of its children and its children.
+    Container(Layout, Vec<u32>, Vec<SplitLayout>),
+    View(ViewId),
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum Direction {
     Up,
@@ -60,10 +71,18 @@ pub enum Direction {
     Right,
 }
 
+/// The smallest width of a split when resizing.
+const MIN_SPLIT_WIDTH: u16 = 5;
+/// The smallest height of a split when resizing, a line of text and the statusline.
+const MIN_SPLIT_HEIGHT: u16 = 2;
+
 #[derive(Debug)]
 pub struct Container {
     layout: Layout,
     children: Vec<ViewId>,
+    /// The share of the container's area of each child, children with equal weights have
+    /// equal sizes.
+    weights: Vec<u32>,
     area: Rect,
 }
 
@@ -72,9 +91,26 @@ pub fn new(layout: Layout) -> Self {
         Self {
             layout,
             children: Vec::new(),
+            weights: Vec::new(),
             area: Rect::default(),
         }
     }
+
+    /// Inserts a child with the average weight of the other children so that evenly sized
+    /// children stay even.
+    fn insert_child(&mut self, pos: usize, child: ViewId) {
+        let weight = match self.weights.len() {
+            0 => 1,
+            len => self.weights.iter().sum::<u32>() / len as u32,
+        };
+        self.children.insert(pos, child);
+        self.weights.insert(pos, weight.max(1));
+    }
+
+    fn remove_child(&mut self, pos: usize) {
+        self.children.remove(pos);
+        self.weights.remove(pos);
+    }
 }
 
 impl Default for Container {
@@ -96,7 +132,7 @@ pub fn new(area: Rect) -> Self {
         Self {
             root,
             focus: root,
-            // fullscreen: false,
+            zoomed: None,
             area,
             nodes,
             stack: Vec::new(),
@@ -131,9 +167,10 @@ pub fn insert(&mut self, view: View) -> ViewId {
             pos + 1
         };
 
-        container.children.insert(pos, node);
+        container.insert_child(pos, node);
         // focus the new node
         self.focus = node;
+        self.zoomed = None;
 
         // recalculate all the sizes
         self.recalculate();
@@ -168,7 +205,7 @@ pub fn split(&mut self, view: View, layout: Layout) -> ViewId {
                     .unwrap();
                 pos + 1
             };
-            container.children.insert(pos, node);
+            container.insert_child(pos, node);
             self.nodes[node].parent = parent;
         } else {
             let mut split = Node::container(layout);
@@ -182,8 +219,8 @@ pub fn split(&mut self, view: View, layout: Layout) -> ViewId {
                 } => container,
                 _ => unreachable!(),
             };
-            container.children.push(focus);
-            container.children.push(node);
+            container.insert_child(0, focus);
+            container.insert_child(1, node);
             self.nodes[focus].parent = split;
             self.nodes[node].parent = split;
 
@@ -207,6 +244,7 @@ pub fn split(&mut self, view: View, layout: Layout) -> ViewId {
 
         // focus the new node
         self.focus = node;
+        self.zoomed = None;
 
         // recalculate all the sizes
         self.recalculate();
@@ -243,7 +281,7 @@ fn remove_or_replace(&mut self, child: ViewId, replacement: Option<ViewId>) {
             container.children[pos] = new;
             self.nodes[new].parent = parent;
         } else {
-            container.children.remove(pos);
+            container.remove_child(pos);
         }
     }
 
@@ -263,12 +301,36 @@ pub fn remove(&mut self, index: ViewId) {
             // Lets merge the only child back to its grandparent so that Views
             // are equally spaced.
             let sibling = parent_container.children.pop().unwrap();
+            parent_container.weights.clear();
             self.remove_or_replace(parent, Some(sibling));
         }
 
+        if self.zoomed == Some(index) {
+            self.zoomed = None;
+        }
         self.recalculate()
     }
 
+    /// Returns the structure of the splits starting at the root container.
+    pub fn split_layout(&self) -> SplitLayout {
+        self.split_layout_of(self.root)
+    }
+
+    fn split_layout_of(&self, index: ViewId) -> SplitLayout {
+        match &self.nodes[index].content {
+            Content::View(_) => SplitLayout::View(index),
+            Content::Container(container) => SplitLayout::Container(
+                container.layout,
+                container.weights.clone(),
+                container
+                    .children
+                    .iter()
+                    .map(|&child| self.split_layout_of(child))
+                    .collect(),
+            ),
+        }
+    }
+
     pub fn views(&self) -> impl Iterator<Item = (&View, bool)> {
         let focus = self.focus;
         self.nodes.iter().filter_map(move |(key, node)| match node {
@@ -293,6 +355,13 @@ pub fn views_mut(&mut self) -> impl Iterator<Item = (&mut View, bool)> {
             })
     }
 
+    /// The views which are displayed: only the zoomed view while a view is zoomed.
+    pub fn visible_views(&self) -> impl Iterator<Item = (&View, bool)> {
+        let zoomed = self.zoomed;
+        self.views()
+            .filter(move |(view, _)| zoomed.is_none_or(|zoomed| zoomed == view.id))
+    }
+
     /// Get reference to a [View] by index.
     /// # Panics
     ///
@@ -356,6 +425,7 @@ pub fn recalculate(&mut self) {
         if self.is_empty() {
             // There are no more views, so the tree should focus itself again.
             self.focus = self.root;
+            self.zoomed = None;
 
             return;
         }
@@ -382,12 +452,19 @@ pub fn recalculate(&mut self) {
                     match container.layout {
                         Layout::Horizontal => {
                             let len = container.children.len();
-
-                            let height = area.height / len as u16;
+                            let total_weight: u32 = container.weights.iter().sum();
 
                             let mut child_y = area.y;
 
-                            for (i, child) in container.children.iter().enumerate() {
+                            for (i, (child, &weight)) in container
+                                .children
+                                .iter()
+                                .zip(&container.weights)
+                                .enumerate()
+                            {
+                                let height = (area.height as u64 * weight as u64
+                                    / total_weight as u64)
+                                    as u16;
                                 let mut area = Rect::new(
                                     container.area.x,
                                     child_y,
@@ -413,11 +490,18 @@ pub fn recalculate(&mut self) {
                             let total_gap = inner_gap * len_u16.saturating_sub(2);
 
                             let used_area = area.width.saturating_sub(total_gap);
-                            let width = used_area / len_u16;
+                            let total_weight: u32 = container.weights.iter().sum();
 
                             let mut child_x = area.x;
 
-                            for (i, child) in container.children.iter().enumerate() {
+                            for (i, (child, &weight)) in container
+                                .children
+                                .iter()
+                                .zip(&container.weights)
+                                .enumerate()
+                            {
+                                let width =
+                                    (used_area as u64 * weight as u64 / total_weight as u64) as u16;
                                 let mut area = Rect::new(
                                     child_x,
                                     container.area.y,
@@ -439,6 +523,183 @@ pub fn recalculate(&mut self) {
                 }
             }
         }
+
+        if let Some(zoomed) = self.zoomed {
+            let area = self.area;
+            self.get_mut(zoomed).area = area;
+        }
+    }
+
+    /// Returns the area of a view or container.
+    fn node_area(&self, index: ViewId) -> Rect {
+        match &self.nodes[index].content {
+            Content::View(view) => view.area,
+            Content::Container(container) => container.area,
+        }
+    }
+
+    /// Finds the closest container with `layout` where the child containing `id` is
+    /// followed by another child if `followed` is set, or where it has any sibling
+    /// otherwise. Returns the container and the index of the child.
+    fn find_resizable_container(
+        &self,
+        mut id: ViewId,
+        layout: Layout,
+        followed: bool,
+    ) -> Option<(ViewId, usize)> {
+        loop {
+            let parent = self.nodes[id].parent;
+            if parent == id {
+                return None;
+            }
+            let Content::Container(container) = &self.nodes[parent].content else {
+                unreachable!()
+            };
+            let len = container.children.len();
+            if container.layout == layout && len > 1 {
+                let index = container.children.iter().position(|&child| child == id)?;
+                if !followed || index + 1 < len {
+                    return Some((parent, index));
+                }
+            }
+            id = parent;
+        }
+    }
+
+    /// Moves the border between the child at `index` of `container` and the next child by
+    /// `delta` cells. Both children keep at least a minimum size.
+    fn move_border(&mut self, container: ViewId, index: usize, delta: i32) -> bool {
+        let Content::Container(node) = &self.nodes[container].content else {
+            unreachable!()
+        };
+        let layout = node.layout;
+        let sizes: Vec<u32> = node
+            .children
+            .iter()
+            .map(|&child| {
+                let area = self.node_area(child);
+                match layout {
+                    Layout::Vertical => area.width as u32,
+                    Layout::Horizontal => area.height as u32,
+                }
+            })
+            .collect();
+        let min = match layout {
+            Layout::Vertical => MIN_SPLIT_WIDTH,
+            Layout::Horizontal => MIN_SPLIT_HEIGHT,
+        } as i32;
+        let delta = delta
+            .max(min - sizes[index] as i32)
+            .min(sizes[index + 1] as i32 - min);
+        if delta == 0 {
+            return false;
+        }
+
+        // The current sizes become the weights so that the other children keep their size.
+        let container = self.container_mut(container);
+        container.weights = sizes.into_iter().map(|size| size.max(1)).collect();
+        container.weights[index] = container.weights[index].saturating_add_signed(delta);
+        container.weights[index + 1] = container.weights[index + 1].saturating_add_signed(-delta);
+        self.recalculate();
+        true
+    }
+
+    /// Grows the view `id` by `delta` cells, or shrinks it if `delta` is negative: the
+    /// width for a [Layout::Vertical] and the height for a [Layout::Horizontal] `layout`.
+    /// The split next to the view is resized in the opposite direction.
+    pub fn resize_view(&mut self, id: ViewId, layout: Layout, delta: i32) -> bool {
+        self.set_zoom(false);
+        let Some((container, index)) = self.find_resizable_container(id, layout, false) else {
+            return false;
+        };
+        let Content::Container(node) = &self.nodes[container].content else {
+            unreachable!()
+        };
+        if index + 1 < node.children.len() {
+            self.move_border(container, index, delta)
+        } else {
+            self.move_border(container, index - 1, -delta)
+        }
+    }
+
+    /// Returns the view and the layout of the split border at a screen position: the gap to
+    /// the right of a vertical split or the statusline of a split above another split.
+    pub fn split_border_at(&self, column: u16, row: u16) -> Option<(ViewId, Layout)> {
+        if self.zoomed.is_some() {
+            return None;
+        }
+        self.views().find_map(|(view, _)| {
+            let area = view.area;
+            if column == area.right()
+                && (area.top()..area.bottom()).contains(&row)
+                && area.right() < self.area.right()
+            {
+                Some((view.id, Layout::Vertical))
+            } else if row + 1 == area.bottom()
+                && (area.left()..area.right()).contains(&column)
+                && area.bottom() < self.area.bottom()
+            {
+                Some((view.id, Layout::Horizontal))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Moves a split border found with [Tree::split_border_at] to a screen position.
+    pub fn drag_split_border(&mut self, id: ViewId, layout: Layout, column: u16, row: u16) {
+        let Some((container, index)) = self.find_resizable_container(id, layout, true) else {
+            return;
+        };
+        let Content::Container(node) = &self.nodes[container].content else {
+            unreachable!()
+        };
+        let area = self.node_area(node.children[index]);
+        let delta = match layout {
+            Layout::Vertical => column as i32 - area.right() as i32,
+            Layout::Horizontal => row as i32 - (area.bottom() as i32 - 1),
+        };
+        self.move_border(container, index, delta);
+    }
+
+    /// Sets the weights of the children of the container of the view `id`, for example to
+    /// restore a [SplitLayout]. Ignored unless there is one weight for each child.
+    pub fn set_split_weights(&mut self, id: ViewId, weights: &[u32]) {
+        let container = self.container_mut(self.nodes[id].parent);
+        if container.weights.len() != weights.len() {
+            return;
+        }
+        // Weights are at most the size of a split so that their sum can't overflow.
+        for (weight, &new) in container.weights.iter_mut().zip(weights) {
+            *weight = new.clamp(1, u16::MAX as u32);
+        }
+        self.recalculate();
+    }
+
+    /// Gives all splits equal sizes again.
+    pub fn equalize(&mut self) {
+        self.zoomed = None;
+        for node in self.nodes.values_mut() {
+            if let Content::Container(container) = &mut node.content {
+                container.weights.fill(1);
+            }
+        }
+        self.recalculate();
+    }
+
+    /// Returns the view which takes up the whole area, if any.
+    pub fn zoomed(&self) -> Option<ViewId> {
+        self.zoomed
+    }
+
+    /// Makes the focused view take up the whole area until the zoom is turned off, the
+    /// sizes of the other splits are kept.
+    pub fn set_zoom(&mut self, zoom: bool) {
+        let zoomed = (zoom && !self.is_empty()).then_some(self.focus);
+        if self.zoomed != zoomed {
+            self.zoomed = zoomed;
+            self.recalculate();
+        }
     }
 
     pub fn traverse(&self) -> Traverse<'_> {
@@ -965,4 +1226,100 @@ fn vsplit_gap_rounding() {
                 .collect::<Vec<_>>()
         );
     }
+
+    #[test]
+    fn resize_views() {
+        let mut tree = Tree::new(Rect::new(0, 0, 81, 40));
+        let view = View::new(DocumentId::default(), GutterConfig::default());
+        tree.insert(view);
+        let l0 = tree.focus;
+        let view = View::new(DocumentId::default(), GutterConfig::default());
+        tree.split(view, Layout::Vertical);
+        let r0 = tree.focus;
+        let view = View::new(DocumentId::default(), GutterConfig::default());
+        tree.split(view, Layout::Horizontal);
+        let r1 = tree.focus;
+
+        // | L0 | R0 |
+        // |    | R1 |
+        let widths = |tree: &Tree| [tree.get(l0).area.width, tree.get(r0).area.width];
+        let heights = |tree: &Tree| [tree.get(r0).area.height, tree.get(r1).area.height];
+        assert_eq!(widths(&tree), [40, 40]);
+        assert_eq!(heights(&tree), [20, 20]);
+
+        assert!(tree.resize_view(l0, Layout::Vertical, 10));
+        assert_eq!(widths(&tree), [50, 30]);
+        // The last split grows by moving the border before it.
+        assert!(tree.resize_view(r1, Layout::Vertical, 5));
+        assert_eq!(widths(&tree), [45, 35]);
+        assert!(tree.resize_view(r1, Layout::Horizontal, 4));
+        assert_eq!(heights(&tree), [16, 24]);
+        // Splits keep a minimum size.
+        assert!(tree.resize_view(r0, Layout::Horizontal, -100));
+        assert_eq!(heights(&tree), [MIN_SPLIT_HEIGHT, 40 - MIN_SPLIT_HEIGHT]);
+        assert!(!tree.resize_view(r0, Layout::Horizontal, -1));
+        assert!(!tree.resize_view(l0, Layout::Horizontal, 1));
+
+        // Sizes are kept when the tree is resized and new splits share evenly.
+        tree.resize(Rect::new(0, 0, 81, 40));
+        assert_eq!(widths(&tree), [45, 35]);
+
+        assert_eq!(tree.split_border_at(45, 3), Some((l0, Layout::Vertical)));
+        assert_eq!(
+            tree.split_border_at(60, MIN_SPLIT_HEIGHT - 1),
+            Some((r0, Layout::Horizontal))
+        );
+        assert_eq!(tree.split_border_at(60, 39), None);
+        tree.drag_split_border(l0, Layout::Vertical, 30, 3);
+        assert_eq!(widths(&tree), [30, 50]);
+        tree.drag_split_border(r0, Layout::Horizontal, 60, 9);
+        assert_eq!(heights(&tree), [10, 30]);
+
+        let SplitLayout::Container(_, weights, _) = tree.split_layout() else {
+            unreachable!()
+        };
+        tree.equalize();
+        assert_eq!(widths(&tree), [40, 40]);
+        assert_eq!(heights(&tree), [20, 20]);
+
+        // The weights of a split layout restore its sizes.
+        tree.set_split_weights(l0, &weights);
+        assert_eq!(widths(&tree), [30, 50]);
+        tree.set_split_weights(l0, &[1]);
+        assert_eq!(widths(&tree), [30, 50]);
+        tree.set_split_weights(l0, &[u32::MAX, u32::MAX]);
+        assert_eq!(widths(&tree), [40, 40]);
+    }
+
+    #[test]
+    fn zoom_view() {
+        let area = Rect::new(0, 0, 81, 40);
+        let mut tree = Tree::new(area);
+        let view = View::new(DocumentId::default(), GutterConfig::default());
+        tree.insert(view);
+        let l0 = tree.focus;
+        let view = View::new(DocumentId::default(), GutterConfig::default());
+        tree.split(view, Layout::Vertical);
+        let r0 = tree.focus;
+
+        tree.set_zoom(true);
+        assert_eq!(tree.zoomed(), Some(r0));
+        assert_eq!(tree.get(r0).area, area);
+        assert_eq!(
+            tree.visible_views()
+                .map(|(view, _)| view.id)
+                .collect::<Vec<_>>(),
+            [r0]
+        );
+
+        tree.set_zoom(false);
+        assert_eq!(tree.get(r0).area, Rect::new(41, 0, 40, 40));
+        assert_eq!(tree.visible_views().count(), 2);
+
+        // Removing the zoomed view turns off the zoom.
+        tree.set_zoom(true);
+        tree.remove(r0);
+        assert_eq!(tree.zoomed(), None);
+        assert_eq!(tree.get(l0).area, area);
+    }
 }
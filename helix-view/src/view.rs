@@ -1,6 +1,6 @@
 use crate::{
     align_view,
-    annotations::diagnostics::InlineDiagnostics,
+    annotations::{code_lens::CodeLenses, diagnostics::InlineDiagnostics, diff::DiffFillers},
     document::{DocumentColorSwatches, DocumentInlayHints},
     editor::{GutterConfig, GutterType},
     graphics::Rect,
@@ -12,11 +12,12 @@
     char_idx_at_visual_offset,
     doc_formatter::TextFormat,
     text_annotations::TextAnnotations,
-    visual_offset_from_anchor, visual_offset_from_block, Position, RopeSlice, Selection,
-    Transaction,
+    view_offset_at_visual_offset, visual_offset_from_anchor, visual_offset_from_block, Position,
+    RopeSlice, Selection, Transaction,
     VisualOffsetError::{PosAfterMaxRow, PosBeforeAnchorRow},
 };
 
+use serde::{Deserialize, Serialize};
 use std::{
     collections::{HashMap, VecDeque},
     fmt,
@@ -135,7 +136,8 @@ fn apply(&mut self, transaction: &Transaction, doc: &Document) {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Copy, Default)]
+#[derive(Clone, Debug, PartialEq, Eq, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub struct ViewPosition {
     pub anchor: usize,
     pub horizontal_offset: usize,
@@ -312,7 +314,7 @@ pub fn offset_coords_to_in_view_center<const CENTERING: bool>(
                 viewport.height as isize - scrolloff_bottom as isize - 1
             };
             (offset.anchor, offset.vertical_offset) =
-                char_idx_at_visual_offset(doc_text, cursor, -v_off, 0, &text_fmt, &annotations);
+                view_offset_at_visual_offset(doc_text, cursor, -v_off, &text_fmt, &annotations);
         }
 
         if text_fmt.soft_wrap {
@@ -467,6 +469,11 @@ pub fn text_annotations<'a>(
             text_annotations.add_overlay(labels, style);
         }
 
+        if let Some(folds) = doc.folds.get(&self.id) {
+            let style = theme.and_then(|t| t.find_highlight("ui.virtual.fold"));
+            text_annotations.add_folds(folds, style);
+        }
+
         if let Some(DocumentInlayHints {
             id: _,
             type_inlay_hints,
@@ -509,6 +516,7 @@ pub fn text_annotations<'a>(
             }
         }
 
+        let display_code_lenses = config.lsp.display_code_lenses;
         let width = self.inner_width(doc);
         let enable_cursor_line = self
             .diagnostics_handler
@@ -528,6 +536,17 @@ pub fn text_annotations<'a>(
             ));
         }
 
+        // Must be added after the inline diagnostics to match the order of the decorations.
+        if doc.diff_mode().is_some() {
+            text_annotations.add_line_annotation(DiffFillers::new(doc));
+        }
+
+        // Must be added after the diff fillers to match the order of the decorations, the
+        // lenses of a line are displayed right above it.
+        if display_code_lenses && !doc.code_lens_lines().is_empty() {
+            text_annotations.add_line_annotation(CodeLenses::new(doc));
+        }
+
         text_annotations
     }
 
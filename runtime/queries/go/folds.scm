@@ -0,0 +1,15 @@
+[
+  (block)
+  (import_spec_list)
+  (const_declaration)
+  (var_declaration)
+  (field_declaration_list)
+  (interface_type)
+  (literal_value)
+  (argument_list)
+  (parameter_list)
+  (expression_switch_statement)
+  (type_switch_statement)
+  (select_statement)
+  (comment)
+] @fold
@@ -0,0 +1,20 @@
+[
+  (function_definition)
+  (class_definition)
+
+  (if_statement)
+  (for_statement)
+  (while_statement)
+  (with_statement)
+  (try_statement)
+  (match_statement)
+  (case_clause)
+  (import_from_statement)
+
+  (list)
+  (tuple)
+  (dictionary)
+  (set)
+  (argument_list)
+  (parameters)
+] @fold